@@ -0,0 +1,357 @@
+use crate::input::*;
+use crate::state::*;
+
+// Mapping from physical inputs to game actions.
+//
+// The platform layer only translates its raw events into an InputSource and a value and
+// hands them to action_map_process, everything else about bindings lives here. Buttons and
+// keys report 0 or 1, analog axes report how far they're pushed. An action can have any
+// number of bindings and takes the largest value of them, so holding two keys bound to the
+// same action and letting one go leaves it down.
+//
+// Bindings are loaded from text files, one action per line followed by its inputs.
+// # starts a comment and an action can be listed more than once to add more inputs.
+//
+//   turn_left   key:a key:left pad:dpad_left axis:left_x-
+//   launch      key:space mouse:left pad:a
+//
+// Keys are letters, digits, space, enter, escape, tab, backspace, the arrows (left, right,
+// up, down), shift, control, alt and f1 to f12. Mouse buttons are left, right and middle.
+// Axes need a + or - for the half of the axis that drives the action.
+// Actions that aren't listed have no bindings.
+
+pub const DEFAULT_BINDINGS: &str = include_str!("../bindings/default.bindings");
+
+// Analog values at or above this count as the action being down
+pub const ACTION_DOWN_THRESHOLD: f32 = 0.25;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GameAction {
+    TurnLeft,
+    TurnRight,
+    Accelerate,
+    Decelerate,
+    Launch,
+    Pause,
+}
+
+pub const GAME_ACTIONS: [GameAction; 6] = [
+    GameAction::TurnLeft,
+    GameAction::TurnRight,
+    GameAction::Accelerate,
+    GameAction::Decelerate,
+    GameAction::Launch,
+    GameAction::Pause,
+];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Key {
+    // Letters are upper case
+    Char(char),
+    Space,
+    Enter,
+    Escape,
+    Tab,
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    Shift,
+    Control,
+    Alt,
+    // 1 to 12
+    F(u8),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    Start,
+    Back,
+    LeftShoulder,
+    RightShoulder,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InputSource {
+    Key(Key),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+    // One half of an axis, positive or negative
+    GamepadAxis { axis: GamepadAxis, positive: bool },
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ActionBinding {
+    pub action: GameAction,
+    pub source: InputSource,
+    // Last value the platform reported for the source, 0 to 1
+    pub value: f32,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct ActionMap {
+    pub bindings: Vec<ActionBinding>,
+}
+
+#[derive(Debug)]
+pub struct ActionMapError {
+    // 1 based, 0 when the error isn't about a specific line
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ActionMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line > 0 {
+            write!(f, "bindings line {}: {}", self.line, self.message)
+        }
+        else {
+            write!(f, "bindings: {}", self.message)
+        }
+    }
+}
+
+impl std::error::Error for ActionMapError {}
+
+fn action_map_error(line: usize, message: String) -> ActionMapError {
+    ActionMapError { line, message }
+}
+
+pub fn action_binding(action: GameAction, source: InputSource) -> ActionBinding {
+    ActionBinding { action, source, value: 0.0 }
+}
+
+pub fn action_button(input: &GameInput, action: GameAction) -> &GameButton {
+    match action {
+        GameAction::TurnLeft => &input.turn_left,
+        GameAction::TurnRight => &input.turn_right,
+        GameAction::Accelerate => &input.accelerate,
+        GameAction::Decelerate => &input.decelerate,
+        GameAction::Launch => &input.launch,
+        GameAction::Pause => &input.pause,
+    }
+}
+
+pub fn action_button_mut(input: &mut GameInput, action: GameAction) -> &mut GameButton {
+    match action {
+        GameAction::TurnLeft => &mut input.turn_left,
+        GameAction::TurnRight => &mut input.turn_right,
+        GameAction::Accelerate => &mut input.accelerate,
+        GameAction::Decelerate => &mut input.decelerate,
+        GameAction::Launch => &mut input.launch,
+        GameAction::Pause => &mut input.pause,
+    }
+}
+
+// value is 0 or 1 for keys and buttons and 0 to 1 for half an axis.
+// Sources nothing is bound to are ignored.
+pub fn action_map_process(map: &mut ActionMap, input: &mut GameInput, source: InputSource, value: f32) {
+    let value = value.clamp(0.0, 1.0);
+    for binding in map.bindings.iter_mut().filter(|b| b.source == source) {
+        binding.value = value;
+    }
+
+    for action in GAME_ACTIONS {
+        if !map.bindings.iter().any(|b| b.action == action && b.source == source) {
+            continue;
+        }
+        let action_value = map.bindings.iter()
+            .filter(|b| b.action == action)
+            .fold(0.0f32, |v, b| v.max(b.value));
+        let button = action_button_mut(input, action);
+        button.value = action_value;
+        button_process(button, action_value >= ACTION_DOWN_THRESHOLD);
+    }
+}
+
+// For when the platform stops getting releases, e.g. the window lost focus with keys held
+pub fn action_map_release_all(map: &mut ActionMap, input: &mut GameInput) {
+    let sources: Vec<InputSource> = map.bindings.iter().filter(|b| b.value > 0.0).map(|b| b.source).collect();
+    for source in sources {
+        action_map_process(map, input, source, 0.0);
+    }
+}
+
+// value is the whole axis, -1 to 1 for sticks and 0 to 1 for triggers
+pub fn action_map_process_axis(map: &mut ActionMap, input: &mut GameInput, axis: GamepadAxis, value: f32) {
+    action_map_process(map, input, InputSource::GamepadAxis { axis, positive: true }, value.max(0.0));
+    action_map_process(map, input, InputSource::GamepadAxis { axis, positive: false }, (-value).max(0.0));
+}
+
+pub fn action_name(action: GameAction) -> &'static str {
+    match action {
+        GameAction::TurnLeft => "turn_left",
+        GameAction::TurnRight => "turn_right",
+        GameAction::Accelerate => "accelerate",
+        GameAction::Decelerate => "decelerate",
+        GameAction::Launch => "launch",
+        GameAction::Pause => "pause",
+    }
+}
+
+fn action_parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphanumeric() {
+            return Some(Key::Char(c.to_ascii_uppercase()));
+        }
+    }
+    match name {
+        "space" => Some(Key::Space),
+        "enter" => Some(Key::Enter),
+        "escape" => Some(Key::Escape),
+        "tab" => Some(Key::Tab),
+        "backspace" => Some(Key::Backspace),
+        "left" => Some(Key::Left),
+        "right" => Some(Key::Right),
+        "up" => Some(Key::Up),
+        "down" => Some(Key::Down),
+        "shift" => Some(Key::Shift),
+        "control" => Some(Key::Control),
+        "alt" => Some(Key::Alt),
+        _ => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            Some(n) if (1..=12).contains(&n) => Some(Key::F(n)),
+            _ => None,
+        },
+    }
+}
+
+fn action_parse_mouse_button(name: &str) -> Option<MouseButton> {
+    match name {
+        "left" => Some(MouseButton::Left),
+        "right" => Some(MouseButton::Right),
+        "middle" => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+fn action_parse_gamepad_button(name: &str) -> Option<GamepadButton> {
+    match name {
+        "a" => Some(GamepadButton::A),
+        "b" => Some(GamepadButton::B),
+        "x" => Some(GamepadButton::X),
+        "y" => Some(GamepadButton::Y),
+        "start" => Some(GamepadButton::Start),
+        "back" => Some(GamepadButton::Back),
+        "left_shoulder" => Some(GamepadButton::LeftShoulder),
+        "right_shoulder" => Some(GamepadButton::RightShoulder),
+        "left_thumb" => Some(GamepadButton::LeftThumb),
+        "right_thumb" => Some(GamepadButton::RightThumb),
+        "dpad_up" => Some(GamepadButton::DPadUp),
+        "dpad_down" => Some(GamepadButton::DPadDown),
+        "dpad_left" => Some(GamepadButton::DPadLeft),
+        "dpad_right" => Some(GamepadButton::DPadRight),
+        _ => None,
+    }
+}
+
+fn action_parse_gamepad_axis(name: &str) -> Option<InputSource> {
+    let (name, positive) = match (name.strip_suffix('+'), name.strip_suffix('-')) {
+        (Some(n), _) => (n, true),
+        (_, Some(n)) => (n, false),
+        _ => return None,
+    };
+    let axis = match name {
+        "left_x" => GamepadAxis::LeftX,
+        "left_y" => GamepadAxis::LeftY,
+        "right_x" => GamepadAxis::RightX,
+        "right_y" => GamepadAxis::RightY,
+        "left_trigger" => GamepadAxis::LeftTrigger,
+        "right_trigger" => GamepadAxis::RightTrigger,
+        _ => return None,
+    };
+    Some(InputSource::GamepadAxis { axis, positive })
+}
+
+fn action_parse_source(line: usize, token: &str) -> Result<InputSource, ActionMapError> {
+    let (device, name) = match token.split_once(':') {
+        Some(parts) => parts,
+        None => return Err(action_map_error(line, format!("expected device:input but found '{}'", token))),
+    };
+    let source = match device {
+        "key" => action_parse_key(name).map(InputSource::Key),
+        "mouse" => action_parse_mouse_button(name).map(InputSource::Mouse),
+        "pad" => action_parse_gamepad_button(name).map(InputSource::Gamepad),
+        "axis" => action_parse_gamepad_axis(name),
+        other => return Err(action_map_error(line, format!("unknown device '{}'", other))),
+    };
+    match source {
+        Some(s) => Ok(s),
+        None => Err(action_map_error(line, format!("unknown {} input '{}'", device, name))),
+    }
+}
+
+pub fn action_map_parse(text: &str) -> Result<ActionMap, ActionMapError> {
+    let mut map = ActionMap::default();
+
+    for (i, line_text) in text.lines().enumerate() {
+        let line = i + 1;
+        let line_text = match line_text.find('#') {
+            Some(comment) => &line_text[..comment],
+            None => line_text,
+        };
+        let mut tokens = line_text.split_whitespace();
+        let name = match tokens.next() {
+            Some(n) => n,
+            None => continue,
+        };
+        let action = match GAME_ACTIONS.iter().find(|a| action_name(**a) == name) {
+            Some(a) => *a,
+            None => return Err(action_map_error(line, format!("unknown action '{}'", name))),
+        };
+
+        let mut any = false;
+        for token in tokens {
+            let source = action_parse_source(line, token)?;
+            if map.bindings.iter().any(|b| b.action == action && b.source == source) {
+                return Err(action_map_error(line, format!("'{}' is bound to {} more than once", token, name)));
+            }
+            map.bindings.push(action_binding(action, source));
+            any = true;
+        }
+        if !any {
+            return Err(action_map_error(line, format!("{} has no inputs", name)));
+        }
+    }
+
+    Ok(map)
+}
+
+pub fn action_map_load_file(path: &std::path::Path) -> Result<ActionMap, ActionMapError> {
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        action_map_error(0, format!("unable to read {}: {}", path.display(), e))
+    })?;
+    action_map_parse(&text)
+}
+
+pub fn action_map_default() -> ActionMap {
+    action_map_parse(DEFAULT_BINDINGS).expect("the default bindings parse")
+}
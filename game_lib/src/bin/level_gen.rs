@@ -0,0 +1,131 @@
+// Previews procedurally generated solar systems.
+//
+//   cargo run -p game --bin level_gen -- --seed 42 --out seed42.level --preview seed42.ppm
+//
+// Prints the generated level to stdout unless --out is given. The level file can be
+// loaded by the game with --level, or run the game with --seed 42 --generate.
+
+use game::{
+    bitmap::*,
+    generate::*,
+    level::*,
+    math::*,
+    random::*,
+    render::*,
+    render_software::*,
+    state::*,
+    update_and_render,
+};
+
+fn level_gen_usage() -> ! {
+    eprintln!("usage: level_gen [--seed <n>] [--planets <n>] [--world <width>x<height>] [--min-spacing <n>] [--out <file.level>] [--preview <file.ppm>]");
+    std::process::exit(2);
+}
+
+fn level_gen_arg<T: std::str::FromStr>(args: &mut std::env::Args, name: &str) -> T {
+    match args.next().and_then(|s| s.parse().ok()) {
+        Some(v) => v,
+        None => {
+            eprintln!("{} expects a value", name);
+            level_gen_usage();
+        }
+    }
+}
+
+// Renders the whole world at one pixel per unit with the camera on the sun
+fn level_gen_preview(level: &Level, seed: u64, world_size: Vector2f, path: &std::path::Path) -> Result<(), std::io::Error> {
+    // the game world is twice the screen size
+    let input = GameInput {
+        screen_width: (world_size.x / 2.0) as i32,
+        screen_height: (world_size.y / 2.0) as i32,
+        ..Default::default()
+    };
+    let mut ctx = GameState {
+        seed,
+        title_fade: 1.0,
+        ..Default::default()
+    };
+    level_apply(level, &mut ctx);
+
+    // one zero length frame fills in the stars and the render buffer
+    let mut render_buffer = RenderBuffer::default();
+    update_and_render(&input, &mut ctx, &mut render_buffer);
+
+    let width = world_size.x as i32;
+    let height = world_size.y as i32;
+    let mut renderer = software_renderer_create(width, height);
+    // only the world is interesting in a preview, drop the hud
+    if let Some(hud) = render_buffer.commands.iter().position(|c| matches!(c, RenderCommand::SetLayer(RenderLayer::Hud))) {
+        render_buffer.commands.truncate(hud);
+    }
+    renderer.render(0, 0, width, height, &render_buffer);
+    bitmap_write_ppm(&renderer.framebuffer, path)
+}
+
+fn main() {
+    let mut seed: u64 = 0;
+    // world of the default 1600x900 window
+    let mut world_size = vector_2f(3200.0, 1800.0);
+    let mut planet_count = None;
+    let mut min_spacing = None;
+    let mut out_path: Option<String> = None;
+    let mut preview_path: Option<String> = None;
+
+    let mut args = std::env::args();
+    args.next();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = level_gen_arg(&mut args, "--seed"),
+            "--planets" => planet_count = Some(level_gen_arg(&mut args, "--planets")),
+            "--min-spacing" => min_spacing = Some(level_gen_arg(&mut args, "--min-spacing")),
+            "--world" => {
+                let size: String = level_gen_arg(&mut args, "--world");
+                let parsed = size.split_once('x').and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+                match parsed {
+                    Some((w, h)) if w > 0.0 && h > 0.0 => world_size = vector_2f(w, h),
+                    _ => {
+                        eprintln!("--world expects <width>x<height>");
+                        level_gen_usage();
+                    }
+                }
+            },
+            "--out" => out_path = Some(level_gen_arg(&mut args, "--out")),
+            "--preview" => preview_path = Some(level_gen_arg(&mut args, "--preview")),
+            _ => level_gen_usage(),
+        }
+    }
+
+    let mut params = generate_params(world_size);
+    if let Some(count) = planet_count {
+        params.planet_count = count;
+    }
+    if let Some(spacing) = min_spacing {
+        params.min_spacing = spacing;
+    }
+
+    // seeded the same way as the game, so with a matching world size this is the
+    // system the game builds for --seed <n> --generate
+    let mut rng = random_seed(seed);
+    let level = generate_level(&params, &mut rng);
+    if level.planets.len() < params.planet_count as usize {
+        eprintln!("only {} of {} planets fit in a {}x{} world", level.planets.len(), params.planet_count, world_size.x, world_size.y);
+    }
+
+    let text = format!("# generated by level_gen --seed {}\n{}", seed, level_write(&level));
+    match &out_path {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, &text) {
+                eprintln!("unable to write {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => print!("{}", text),
+    }
+
+    if let Some(path) = &preview_path {
+        if let Err(e) = level_gen_preview(&level, seed, world_size, std::path::Path::new(path)) {
+            eprintln!("unable to write {}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::math::*;
+
+pub const BITMAP_BYTES_PER_PIXEL: i32 = 4; // RGBA
+#[derive(Default, Clone)]
+pub struct LoadedBitmap {
+    pub width: i32,
+    pub height: i32,
+    pub pitch: i32,
+    pub data: Option<Box<[u8]>>,
+}
+
+pub fn bitmap_create(width: i32, height: i32) -> LoadedBitmap {
+    let pitch = width * BITMAP_BYTES_PER_PIXEL;
+    let total_bytes = height.checked_mul(pitch).expect("Bitmap bytes exceeds i32");
+    LoadedBitmap {
+        width,
+        height,
+        pitch,
+        data: Some(vec![0u8; total_bytes as usize].into_boxed_slice()),
+    }
+}
+
+pub fn bitmap_pack_color(color: Vector4f) -> [u8; 4] {
+    [
+        (color.x.clamp(0.0, 1.0) * 255.0 + 0.5) as u8,
+        (color.y.clamp(0.0, 1.0) * 255.0 + 0.5) as u8,
+        (color.z.clamp(0.0, 1.0) * 255.0 + 0.5) as u8,
+        (color.w.clamp(0.0, 1.0) * 255.0 + 0.5) as u8,
+    ]
+}
+
+pub fn bitmap_get_pixel(bitmap: &LoadedBitmap, x: i32, y: i32) -> [u8; 4] {
+    debug_assert!(x >= 0 && x < bitmap.width && y >= 0 && y < bitmap.height);
+    let data = bitmap.data.as_ref().expect("Bitmap has no data");
+    let offset = (y * bitmap.pitch + x * BITMAP_BYTES_PER_PIXEL) as usize;
+    [data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]
+}
+
+// Nearest texel at uv where (0, 0) is the first pixel in the data
+pub fn bitmap_sample(bitmap: &LoadedBitmap, uv: Vector2f) -> Vector4f {
+    if bitmap.width <= 0 || bitmap.height <= 0 || bitmap.data.is_none() {
+        return vector_4f(1.0, 1.0, 1.0, 1.0);
+    }
+    let x = ((uv.x * bitmap.width as f32) as i32).clamp(0, bitmap.width - 1);
+    let y = ((uv.y * bitmap.height as f32) as i32).clamp(0, bitmap.height - 1);
+    let p = bitmap_get_pixel(bitmap, x, y);
+    vector_4f(
+        p[0] as f32 / 255.0,
+        p[1] as f32 / 255.0,
+        p[2] as f32 / 255.0,
+        p[3] as f32 / 255.0,
+    )
+}
+
+// Binary PPM (P6) of the rgb channels, rows written in the order they are stored
+pub fn bitmap_write_ppm(bitmap: &LoadedBitmap, path: &std::path::Path) -> Result<(), std::io::Error> {
+    let mut bytes = format!("P6\n{} {}\n255\n", bitmap.width, bitmap.height).into_bytes();
+    if let Some(data) = bitmap.data.as_ref() {
+        for p in data.chunks_exact(BITMAP_BYTES_PER_PIXEL as usize) {
+            bytes.extend_from_slice(&p[0..3]);
+        }
+    }
+    std::fs::write(path, bytes)
+}
@@ -0,0 +1,140 @@
+use crate::math::*;
+use crate::render::*;
+use crate::ship::*;
+use crate::state::*;
+use crate::world::*;
+
+// The camera that follows the ship.
+//
+// It's updated once per rendered frame with the real frame time, after the simulation, so it
+// follows the interpolated ship smoothly at any render rate. Position and zoom chase their
+// targets on critically damped springs: as fast as possible without overshooting. The
+// position target leads the ship in the direction it's moving and the zoom target pulls out
+// near gravity wells so the planet being approached stays in view.
+
+// Angular frequencies of the springs, higher follows more tightly
+pub const CAMERA_FOLLOW_OMEGA: f32 = 6.0;
+pub const CAMERA_ZOOM_OMEGA: f32 = 2.5;
+// How far ahead of the ship to look, as time at its current velocity and at most a distance
+pub const CAMERA_LOOK_AHEAD_SEC: f32 = 0.4;
+pub const CAMERA_LOOK_AHEAD_MAX: f32 = 150.0;
+// Zoom inside a gravity well and how far outside the well it starts pulling out
+pub const CAMERA_WELL_ZOOM: f32 = 0.75;
+pub const CAMERA_WELL_MARGIN: f32 = 150.0;
+// Shake lost per second and what an exploding ship adds
+pub const CAMERA_SHAKE_DECAY: f32 = 1.2;
+pub const CAMERA_EXPLOSION_SHAKE: f32 = 0.9;
+const CAMERA_SHAKE_MAX_OFFSET: f32 = 14.0;
+const CAMERA_SHAKE_MAX_ROT: f32 = 0.03;
+
+// Steps x toward target. Implicit so it's stable for any dt.
+pub fn camera_spring(x: f32, v: f32, target: f32, omega: f32, dt: f32) -> (f32, f32) {
+    let f = 1.0 + 2.0 * dt * omega;
+    let oo = omega * omega;
+    let hoo = dt * oo;
+    let hhoo = dt * hoo;
+    let det_inv = 1.0 / (f + hhoo);
+    let x_next = (f * x + dt * v + hhoo * target) * det_inv;
+    let v_next = (v + hoo * (target - x)) * det_inv;
+    (x_next, v_next)
+}
+
+// Adds to the shake, which tops out at 1
+pub fn camera_shake(camera: &mut Camera, amount: f32) {
+    camera.shake = (camera.shake + amount).min(1.0);
+}
+
+pub fn camera_target_pos(ctx: &GameState) -> Vector2f {
+    let mut look_ahead = vector_2f_scale(ctx.player.d_pos, CAMERA_LOOK_AHEAD_SEC);
+    let look_ahead_length = vector_2f_length(look_ahead);
+    if look_ahead_length > CAMERA_LOOK_AHEAD_MAX {
+        look_ahead = vector_2f_scale(look_ahead, CAMERA_LOOK_AHEAD_MAX / look_ahead_length);
+    }
+    vector_2f_add(ship_drawn_pos(ctx), look_ahead)
+}
+
+pub fn camera_target_zoom(ctx: &GameState) -> f32 {
+    let planets = ctx.planets.as_deref().unwrap_or(&[]);
+    let mut closeness: f32 = 0.0;
+    for planet in planets.iter() {
+        let outside = world_distance(ctx.player.pos, planet.pos, ctx.world_size) - planet.g_radius;
+        closeness = closeness.max(1.0 - (outside / CAMERA_WELL_MARGIN).clamp(0.0, 1.0));
+    }
+    1.0 + (CAMERA_WELL_ZOOM - 1.0) * closeness
+}
+
+// How far to move min, max, in whole worlds, to get the copy of them centered nearest pos
+fn camera_bounds_shift(pos: f32, min: f32, max: f32, world: f32) -> f32 {
+    if world > 0.0 {
+        world * ((pos - 0.5 * (min + max)) / world).round()
+    } else {
+        0.0
+    }
+}
+
+pub fn camera_update(ctx: &mut GameState, dt: f32) {
+    let world_size = ctx.world_size;
+    let target = camera_target_pos(ctx);
+    let target_zoom = camera_target_zoom(ctx);
+    let explosions = ctx.events.iter().filter(|e| matches!(e, GameEvent::Explosion { .. })).count();
+    let camera = &mut ctx.camera;
+
+    if !camera.initialized {
+        camera.pos = target;
+        camera.d_pos = vector_2f_zero();
+        camera.zoom = target_zoom;
+        camera.d_zoom = 0.0;
+        camera.initialized = true;
+    }
+
+    // when the ship wraps around the world the camera wraps with it instead of flying back
+    let half_world = vector_2f_scale(world_size, 0.5);
+    if target.x - camera.pos.x > half_world.x {
+        camera.pos.x += world_size.x;
+    } else if target.x - camera.pos.x < -half_world.x {
+        camera.pos.x -= world_size.x;
+    }
+    if target.y - camera.pos.y > half_world.y {
+        camera.pos.y += world_size.y;
+    } else if target.y - camera.pos.y < -half_world.y {
+        camera.pos.y -= world_size.y;
+    }
+
+    (camera.pos.x, camera.d_pos.x) = camera_spring(camera.pos.x, camera.d_pos.x, target.x, CAMERA_FOLLOW_OMEGA, dt);
+    (camera.pos.y, camera.d_pos.y) = camera_spring(camera.pos.y, camera.d_pos.y, target.y, CAMERA_FOLLOW_OMEGA, dt);
+    (camera.zoom, camera.d_zoom) = camera_spring(camera.zoom, camera.d_zoom, target_zoom, CAMERA_ZOOM_OMEGA, dt);
+
+    if let Some((min, max)) = camera.bounds {
+        // after wrapping with the ship the camera can be a whole world away from the bounds,
+        // so clamp against the copy of them nearest the ship
+        let shift = vector_2f(
+            camera_bounds_shift(target.x, min.x, max.x, world_size.x),
+            camera_bounds_shift(target.y, min.y, max.y, world_size.y));
+        let (min, max) = (vector_2f_add(min, shift), vector_2f_add(max, shift));
+        let clamped = vector_2f(camera.pos.x.clamp(min.x, max.x), camera.pos.y.clamp(min.y, max.y));
+        if clamped.x != camera.pos.x {
+            camera.d_pos.x = 0.0;
+        }
+        if clamped.y != camera.pos.y {
+            camera.d_pos.y = 0.0;
+        }
+        camera.pos = clamped;
+    }
+
+    for _ in 0..explosions {
+        camera_shake(camera, CAMERA_EXPLOSION_SHAKE);
+    }
+    camera.shake = (camera.shake - CAMERA_SHAKE_DECAY * dt).max(0.0);
+    camera.shake_sec += dt;
+}
+
+// What the renderers see, with the shake on top
+pub fn camera_view(camera: &Camera) -> RenderCamera {
+    let amount = camera.shake * camera.shake;
+    let t = camera.shake_sec;
+    let offset = vector_2f((t * 47.0).sin(), (t * 59.0 + 1.7).sin());
+    render_camera(
+        vector_2f_add(camera.pos, vector_2f_scale(offset, amount * CAMERA_SHAKE_MAX_OFFSET)),
+        camera.zoom,
+        camera.rot + (t * 31.0).sin() * amount * CAMERA_SHAKE_MAX_ROT)
+}
@@ -0,0 +1,133 @@
+use crate::action::*;
+use crate::input::*;
+use crate::math::*;
+use crate::state::*;
+
+// Gamepads.
+//
+// The platform reads the device into a ControllerReading with sticks from -1 to 1 and
+// triggers from 0 to 1, exactly as the hardware reports them, and controller_update turns
+// that into the GameController the game sees. Sticks get a radial deadzone so they don't
+// drift at rest, the travel past the deadzone is stretched back to the full 0 to 1 range
+// and then bent by the response curve so small pushes give finer control.
+
+// Fractions of the full range, roughly XInput's recommended values
+pub const CONTROLLER_STICK_DEADZONE: f32 = 0.24;
+pub const CONTROLLER_TRIGGER_DEADZONE: f32 = 0.12;
+// 1 is linear, higher is gentler near the center
+pub const CONTROLLER_RESPONSE_EXPONENT: f32 = 1.5;
+
+pub const GAMEPAD_BUTTONS: [GamepadButton; 14] = [
+    GamepadButton::A,
+    GamepadButton::B,
+    GamepadButton::X,
+    GamepadButton::Y,
+    GamepadButton::Start,
+    GamepadButton::Back,
+    GamepadButton::LeftShoulder,
+    GamepadButton::RightShoulder,
+    GamepadButton::LeftThumb,
+    GamepadButton::RightThumb,
+    GamepadButton::DPadUp,
+    GamepadButton::DPadDown,
+    GamepadButton::DPadLeft,
+    GamepadButton::DPadRight,
+];
+
+#[derive(Default, Copy, Clone)]
+pub struct ControllerReading {
+    pub connected: bool,
+    pub left_stick: Vector2f,
+    pub right_stick: Vector2f,
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+    // Indexed by GamepadButton
+    pub buttons: [bool; 14],
+}
+
+// 0 inside the deadzone, then 0 to 1 over the rest of the travel
+pub fn controller_axis_response(value: f32, deadzone: f32, exponent: f32) -> f32 {
+    if value <= deadzone {
+        return 0.0;
+    }
+    ((value.min(1.0) - deadzone) / (1.0 - deadzone)).powf(exponent)
+}
+
+// Keeps the stick's direction and only reshapes how far it's pushed
+pub fn controller_stick(raw: Vector2f, deadzone: f32, exponent: f32) -> Vector2f {
+    let length = vector_2f_length(raw);
+    let magnitude = controller_axis_response(length, deadzone, exponent);
+    if magnitude <= 0.0 {
+        return vector_2f_zero();
+    }
+    vector_2f_scale(raw, magnitude / length)
+}
+
+pub fn controller_button(controller: &GameController, button: GamepadButton) -> &GameButton {
+    match button {
+        GamepadButton::A => &controller.a,
+        GamepadButton::B => &controller.b,
+        GamepadButton::X => &controller.x,
+        GamepadButton::Y => &controller.y,
+        GamepadButton::Start => &controller.start,
+        GamepadButton::Back => &controller.back,
+        GamepadButton::LeftShoulder => &controller.left_shoulder,
+        GamepadButton::RightShoulder => &controller.right_shoulder,
+        GamepadButton::LeftThumb => &controller.left_thumb,
+        GamepadButton::RightThumb => &controller.right_thumb,
+        GamepadButton::DPadUp => &controller.dpad_up,
+        GamepadButton::DPadDown => &controller.dpad_down,
+        GamepadButton::DPadLeft => &controller.dpad_left,
+        GamepadButton::DPadRight => &controller.dpad_right,
+    }
+}
+
+pub fn controller_button_mut(controller: &mut GameController, button: GamepadButton) -> &mut GameButton {
+    match button {
+        GamepadButton::A => &mut controller.a,
+        GamepadButton::B => &mut controller.b,
+        GamepadButton::X => &mut controller.x,
+        GamepadButton::Y => &mut controller.y,
+        GamepadButton::Start => &mut controller.start,
+        GamepadButton::Back => &mut controller.back,
+        GamepadButton::LeftShoulder => &mut controller.left_shoulder,
+        GamepadButton::RightShoulder => &mut controller.right_shoulder,
+        GamepadButton::LeftThumb => &mut controller.left_thumb,
+        GamepadButton::RightThumb => &mut controller.right_thumb,
+        GamepadButton::DPadUp => &mut controller.dpad_up,
+        GamepadButton::DPadDown => &mut controller.dpad_down,
+        GamepadButton::DPadLeft => &mut controller.dpad_left,
+        GamepadButton::DPadRight => &mut controller.dpad_right,
+    }
+}
+
+// Call once per frame after input_begin_frame. A disconnected controller reads as released.
+pub fn controller_update(controller: &mut GameController, reading: &ControllerReading) {
+    let reading = if reading.connected { *reading } else { ControllerReading::default() };
+    controller.connected = reading.connected;
+    controller.left_stick = controller_stick(reading.left_stick, CONTROLLER_STICK_DEADZONE, CONTROLLER_RESPONSE_EXPONENT);
+    controller.right_stick = controller_stick(reading.right_stick, CONTROLLER_STICK_DEADZONE, CONTROLLER_RESPONSE_EXPONENT);
+    controller.left_trigger = controller_axis_response(reading.left_trigger, CONTROLLER_TRIGGER_DEADZONE, CONTROLLER_RESPONSE_EXPONENT);
+    controller.right_trigger = controller_axis_response(reading.right_trigger, CONTROLLER_TRIGGER_DEADZONE, CONTROLLER_RESPONSE_EXPONENT);
+    for (i, button) in GAMEPAD_BUTTONS.iter().enumerate() {
+        let is_down = reading.buttons[i];
+        let game_button = controller_button_mut(controller, *button);
+        game_button.value = if is_down { 1.0 } else { 0.0 };
+        button_process(game_button, is_down);
+    }
+}
+
+// Feeds the controller through the bindings, after controller_update
+pub fn controller_process_actions(map: &mut ActionMap, input: &mut GameInput) {
+    let controller = input.controller;
+    for button in GAMEPAD_BUTTONS {
+        let value = if controller_button(&controller, button).ended_down { 1.0 } else { 0.0 };
+        action_map_process(map, input, InputSource::Gamepad(button), value);
+    }
+    action_map_process_axis(map, input, GamepadAxis::LeftX, controller.left_stick.x);
+    action_map_process_axis(map, input, GamepadAxis::LeftY, controller.left_stick.y);
+    action_map_process_axis(map, input, GamepadAxis::RightX, controller.right_stick.x);
+    action_map_process_axis(map, input, GamepadAxis::RightY, controller.right_stick.y);
+    action_map_process_axis(map, input, GamepadAxis::LeftTrigger, controller.left_trigger);
+    action_map_process_axis(map, input, GamepadAxis::RightTrigger, controller.right_trigger);
+}
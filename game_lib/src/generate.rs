@@ -0,0 +1,246 @@
+use crate::level::*;
+use crate::math::*;
+use crate::orbit::*;
+use crate::physics::*;
+use crate::random::*;
+use crate::state::*;
+
+// Procedural solar systems.
+//
+// Planets circle the sun on rings, all the same way round, and are placed by rejection
+// sampling. Planets sharing a ring turn together so only their spacing along it matters;
+// rings of different planets are kept apart by both wells (plus min_spacing) so they can
+// never meet however long the system runs. Rings stay clear of the spawn point at the sun
+// and fully inside the world so wells can't overlap across the wrap either. A planet may
+// get a moon orbiting inside its well, out past the planet's items.
+// The output is a Level so a generated system can be written out and hand edited.
+
+// Candidate positions tried per planet before giving up on it
+const GENERATE_MAX_ATTEMPTS: u32 = 200;
+
+// Item types the generator hands out, PlanetItemType::None is never placed.
+// Fuel is listed twice so it turns up more often than the rest.
+const GENERATE_ITEM_TYPES: [PlanetItemType; 6] = [
+    PlanetItemType::Fuel,
+    PlanetItemType::Fuel,
+    PlanetItemType::Repair,
+    PlanetItemType::Cargo,
+    PlanetItemType::Key,
+    PlanetItemType::Gem,
+];
+// Items float this far above the surface, at most half way out to the edge of the well
+const GENERATE_ITEM_MIN_ALTITUDE: f32 = 15.0;
+// Chance a new planet goes on a ring that already has one
+const GENERATE_SHARED_RING_CHANCE: f32 = 0.4;
+// Moon radius as a multiple of its planet's, and the moon's well as a multiple of its own
+const GENERATE_MOON_RADIUS_SCALE_MIN: f32 = 0.12;
+const GENERATE_MOON_RADIUS_SCALE_MAX: f32 = 0.2;
+const GENERATE_MOON_G_RADIUS_SCALE: f32 = 1.5;
+
+#[derive(Clone, Debug)]
+pub struct GenerateParams {
+    pub planet_count: u32,
+    // Full width and height of the world, centered on the origin
+    pub world_size: Vector2f,
+    // Empty space kept between gravity wells
+    pub min_spacing: f32,
+    pub radius_min: f32,
+    pub radius_max: f32,
+    // Gravity well radius as a multiple of the planet radius
+    pub g_radius_scale_min: f32,
+    pub g_radius_scale_max: f32,
+    // Pull at the surface, the planet's mass is worked out from it
+    pub g_force_min: f32, // units/s^2
+    pub g_force_max: f32, // units/s^2
+    // Items per planet are picked from [0, max_items]
+    pub max_items: u32,
+    // Chance in [0, 1] that a planet has a moon
+    pub moon_chance: f32,
+    pub gravity_g: f32,
+    pub sun_mass: f32,
+    pub sun_softening: f32,
+}
+
+pub fn generate_params(world_size: Vector2f) -> GenerateParams {
+    GenerateParams {
+        planet_count: 6,
+        world_size,
+        min_spacing: 40.0,
+        radius_min: 50.0,
+        radius_max: 110.0,
+        g_radius_scale_min: 1.8,
+        g_radius_scale_max: 2.6,
+        g_force_min: 200.0,
+        g_force_max: 350.0,
+        max_items: 3,
+        moon_chance: 0.3,
+        gravity_g: PHYSICS_DEFAULT_G,
+        sun_mass: PHYSICS_DEFAULT_SUN_MASS,
+        sun_softening: PHYSICS_DEFAULT_SUN_SOFTENING,
+    }
+}
+
+// Hue in [0, 1)
+fn generate_color_from_hsv(h: f32, s: f32, v: f32) -> Vector4f {
+    let h6 = h * 6.0;
+    let f = h6 - h6.floor();
+    let p = v * (1.0 - s);
+    let q = v * (1.0 - s * f);
+    let t = v * (1.0 - s * (1.0 - f));
+    let (r, g, b) = match h6 as i32 {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    };
+    vector_4f(r, g, b, 1.0)
+}
+
+// Item floating somewhere between the surface and half way out to the edge of the well
+fn generate_item(rng: &mut RandomSeries, itype: PlanetItemType, surface_radius: f32, g_radius: f32) -> PlanetItem {
+    let max_dist = surface_radius + 0.5 * (g_radius - surface_radius);
+    let dist = random_between(rng, surface_radius + GENERATE_ITEM_MIN_ALTITUDE, max_dist);
+    PlanetItem { itype, pos: vector_2f_scale(random_unit_vector_2f(rng), dist) }
+}
+
+fn generate_planet(params: &GenerateParams, rng: &mut RandomSeries, pos: Vector2f, radius: f32, g_radius: f32, max_items: u32, orbit: Orbit) -> Planet {
+    let hue = random_unilateral(rng);
+    let color = generate_color_from_hsv(hue, random_between(rng, 0.35, 0.7), random_between(rng, 0.45, 0.8));
+    // landing zone a darker shade of the planet
+    let lz_color = vector_4f(color.x * 0.4, color.y * 0.4, color.z * 0.4, 1.0);
+
+    let surface_radius = radius;
+    let lz_rel_pos = vector_2f_scale(random_unit_vector_2f(rng), surface_radius);
+
+    let mut items = Vec::new();
+    let item_count = random_choice(rng, max_items.min(MAX_PLANET_ITEMS as u32) + 1);
+    for _ in 0..item_count {
+        let itype = GENERATE_ITEM_TYPES[random_choice(rng, GENERATE_ITEM_TYPES.len() as u32) as usize];
+        items.push(generate_item(rng, itype, surface_radius, g_radius));
+    }
+
+    let g_force = random_between(rng, params.g_force_min, params.g_force_max);
+    Planet {
+        radius,
+        pos,
+        prev_pos: pos,
+        color,
+        g_radius,
+        mass: g_force * surface_radius * surface_radius / params.gravity_g,
+        surface_radius,
+        lz_rel_pos,
+        lz_color,
+        items,
+        orbit: Some(orbit),
+        ..Planet::default()
+    }
+}
+
+// Moon circling inside the well of the planet at parent_index, past where its items float.
+// None when the well has no room for one.
+fn generate_moon(params: &GenerateParams, rng: &mut RandomSeries, parent_index: usize, parent: &Planet, clockwise: bool) -> Option<Planet> {
+    let radius = parent.radius * random_between(rng, GENERATE_MOON_RADIUS_SCALE_MIN, GENERATE_MOON_RADIUS_SCALE_MAX);
+    let g_radius = radius * GENERATE_MOON_G_RADIUS_SCALE;
+    let items_max_dist = parent.surface_radius + 0.5 * (parent.g_radius - parent.surface_radius);
+    let min_dist = items_max_dist + g_radius;
+    let max_dist = parent.g_radius - g_radius;
+    if max_dist <= min_dist {
+        return None;
+    }
+
+    let rel_pos = vector_2f_scale(random_unit_vector_2f(rng), random_between(rng, min_dist, max_dist));
+    let orbit = orbit_circular(OrbitParent::Planet(parent_index), rel_pos, clockwise);
+    Some(generate_planet(params, rng, vector_2f_add(parent.pos, rel_pos), radius, g_radius, 0, orbit))
+}
+
+// Planets that can't be placed after GENERATE_MAX_ATTEMPTS are left out,
+// so the level can hold fewer than planet_count planets when the world is crowded.
+pub fn generate_level(params: &GenerateParams, rng: &mut RandomSeries) -> Level {
+    let mut level = Level {
+        gravity_g: params.gravity_g,
+        sun: Sun {
+            pos: vector_2f_zero(),
+            mass: params.sun_mass,
+            softening: params.sun_softening,
+        },
+        spawn_pos: vector_2f_zero(),
+        spawn_rot: 0.0,
+        planets: Vec::new(),
+    };
+
+    let half_world = vector_2f_scale(params.world_size, 0.5);
+    let max_ring = half_world.x.min(half_world.y);
+    let clockwise = random_choice(rng, 2) == 1;
+    let mut rings: Vec<f32> = Vec::new();
+    for _ in 0..params.planet_count {
+        for _ in 0..GENERATE_MAX_ATTEMPTS {
+            let radius = random_between(rng, params.radius_min, params.radius_max);
+            let g_radius = radius * random_between(rng, params.g_radius_scale_min, params.g_radius_scale_max);
+
+            // the player spawns at the sun, don't start them inside a well, and keep the
+            // whole ring inside the world
+            let min_ring = g_radius + params.min_spacing;
+            if max_ring - g_radius <= min_ring {
+                continue;
+            }
+            let ring = if !rings.is_empty() && random_unilateral(rng) < GENERATE_SHARED_RING_CHANCE {
+                rings[random_choice(rng, rings.len() as u32) as usize]
+            } else {
+                random_between(rng, min_ring, max_ring - g_radius)
+            };
+            if !ring.between(min_ring, max_ring - g_radius) {
+                continue;
+            }
+            let pos = vector_2f_scale(random_unit_vector_2f(rng), ring);
+
+            let clear_of_planets = level.planets.iter()
+                .filter_map(|p| p.orbit.filter(|o| o.parent == OrbitParent::Sun).map(|o| (p, o.semi_major)))
+                .all(|(p, p_ring)| {
+                    let min_dist = g_radius + p.g_radius + params.min_spacing;
+                    if p_ring == ring {
+                        vector_2f_length(vector_2f_sub(pos, p.pos)) >= min_dist
+                    } else {
+                        (ring - p_ring).abs() >= min_dist
+                    }
+                });
+            if clear_of_planets {
+                // exactly the ring so planets sharing it turn at exactly the same rate
+                let orbit = Orbit { semi_major: ring, ..orbit_circular(OrbitParent::Sun, pos, clockwise) };
+                let planet = generate_planet(params, rng, pos, radius, g_radius, params.max_items, orbit);
+                if !rings.contains(&ring) {
+                    rings.push(ring);
+                }
+                let parent_index = level.planets.len();
+                let moon = if random_unilateral(rng) < params.moon_chance {
+                    generate_moon(params, rng, parent_index, &planet, clockwise)
+                } else {
+                    None
+                };
+                level.planets.push(planet);
+                level.planets.extend(moon);
+                break;
+            }
+        }
+    }
+
+    // a level without gems is complete on the first safe landing, so every generated one
+    // has at least one to find, on a planet rather than a moon
+    let has_gem = level.planets.iter().flat_map(|p| p.items.iter()).any(|item| item.itype == PlanetItemType::Gem);
+    let planets: Vec<usize> = (0..level.planets.len())
+        .filter(|&i| level.planets[i].orbit.is_some_and(|o| o.parent == OrbitParent::Sun))
+        .collect();
+    if !has_gem && !planets.is_empty() {
+        let p = &mut level.planets[planets[random_choice(rng, planets.len() as u32) as usize]];
+        if p.items.is_empty() {
+            let gem = generate_item(rng, PlanetItemType::Gem, p.surface_radius, p.g_radius);
+            p.items.push(gem);
+        } else {
+            let i = random_choice(rng, p.items.len() as u32) as usize;
+            p.items[i].itype = PlanetItemType::Gem;
+        }
+    }
+
+    level
+}
@@ -0,0 +1,363 @@
+use crate::math::*;
+
+// 2D collision primitives and queries.
+//
+// Ray casts and sweeps report the first time something enters a shape. A ray that starts
+// inside a shape doesn't hit it, use the overlap tests for that. Ray t is measured in
+// lengths of dir, so a sweep with dir set to the frame's movement hits with t in [0, 1].
+
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub struct Circle {
+    pub center: Vector2f,
+    pub radius: f32,
+}
+
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector2f,
+    pub max: Vector2f,
+}
+
+// Box rotated counter clockwise by rot around its center
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub struct Obb {
+    pub center: Vector2f,
+    pub half_dim: Vector2f,
+    pub rot: f32,
+}
+
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub a: Vector2f,
+    pub b: Vector2f,
+}
+
+// Points along the ray are origin + dir*t, dir doesn't need to be unit length
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vector2f,
+    pub dir: Vector2f,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayHit {
+    pub t: f32,
+    // Point on the surface that was hit
+    pub point: Vector2f,
+    // Unit surface normal pointing back out towards the ray
+    pub normal: Vector2f,
+}
+
+#[inline]
+pub fn circle(center: Vector2f, radius: f32) -> Circle {
+    Circle { center, radius }
+}
+
+#[inline]
+pub fn aabb(min: Vector2f, max: Vector2f) -> Aabb {
+    Aabb { min, max }
+}
+
+#[inline]
+pub fn aabb_from_center(center: Vector2f, half_dim: Vector2f) -> Aabb {
+    Aabb {
+        min: vector_2f_sub(center, half_dim),
+        max: vector_2f_add(center, half_dim),
+    }
+}
+
+#[inline]
+pub fn obb(center: Vector2f, half_dim: Vector2f, rot: f32) -> Obb {
+    Obb { center, half_dim, rot }
+}
+
+#[inline]
+pub fn segment(a: Vector2f, b: Vector2f) -> Segment {
+    Segment { a, b }
+}
+
+#[inline]
+pub fn ray(origin: Vector2f, dir: Vector2f) -> Ray {
+    Ray { origin, dir }
+}
+
+#[inline]
+pub fn ray_point_at(r: &Ray, t: f32) -> Vector2f {
+    vector_2f_add(r.origin, vector_2f_scale(r.dir, t))
+}
+
+//
+// Containment and closest points
+//
+
+pub fn circle_contains_point(c: &Circle, p: Vector2f) -> bool {
+    vector_2f_distance_squared(c.center, p) <= c.radius*c.radius
+}
+
+pub fn aabb_contains_point(b: &Aabb, p: Vector2f) -> bool {
+    p.x.between(b.min.x, b.max.x) && p.y.between(b.min.y, b.max.y)
+}
+
+fn obb_to_local(b: &Obb, p: Vector2f) -> Vector2f {
+    vector_2f_rotate(vector_2f_sub(p, b.center), -b.rot)
+}
+
+fn obb_from_local(b: &Obb, p: Vector2f) -> Vector2f {
+    vector_2f_add(b.center, vector_2f_rotate(p, b.rot))
+}
+
+pub fn obb_contains_point(b: &Obb, p: Vector2f) -> bool {
+    let local = obb_to_local(b, p);
+    local.x.abs() <= b.half_dim.x && local.y.abs() <= b.half_dim.y
+}
+
+// Counter clockwise starting from the local -x -y corner
+pub fn obb_corners(b: &Obb) -> [Vector2f; 4] {
+    let h = b.half_dim;
+    [
+        obb_from_local(b, vector_2f(-h.x, -h.y)),
+        obb_from_local(b, vector_2f( h.x, -h.y)),
+        obb_from_local(b, vector_2f( h.x,  h.y)),
+        obb_from_local(b, vector_2f(-h.x,  h.y)),
+    ]
+}
+
+// Points inside the shape are their own closest point
+pub fn circle_closest_point(c: &Circle, p: Vector2f) -> Vector2f {
+    if circle_contains_point(c, p) {
+        return p;
+    }
+    let dir = vector_2f_normalize(vector_2f_sub(p, c.center));
+    vector_2f_add(c.center, vector_2f_scale(dir, c.radius))
+}
+
+pub fn aabb_closest_point(b: &Aabb, p: Vector2f) -> Vector2f {
+    vector_2f_clamp(p, b.min, b.max)
+}
+
+pub fn obb_closest_point(b: &Obb, p: Vector2f) -> Vector2f {
+    let local = obb_to_local(b, p);
+    let clamped = vector_2f_clamp(local, vector_2f_neg(b.half_dim), b.half_dim);
+    obb_from_local(b, clamped)
+}
+
+// Also returns how far along the segment the point is, in [0, 1]
+pub fn segment_closest_point(s: &Segment, p: Vector2f) -> (Vector2f, f32) {
+    let ab = vector_2f_sub(s.b, s.a);
+    let len_sq = vector_2f_length_squared(ab);
+    let t = if len_sq > 0.0 {
+        (vector_2f_dot(vector_2f_sub(p, s.a), ab)/len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (vector_2f_add(s.a, vector_2f_scale(ab, t)), t)
+}
+
+//
+// Overlap tests, touching counts as overlapping
+//
+
+pub fn circle_overlaps_circle(a: &Circle, b: &Circle) -> bool {
+    let r = a.radius + b.radius;
+    vector_2f_distance_squared(a.center, b.center) <= r*r
+}
+
+pub fn circle_overlaps_aabb(c: &Circle, b: &Aabb) -> bool {
+    circle_contains_point(c, aabb_closest_point(b, c.center))
+}
+
+pub fn circle_overlaps_obb(c: &Circle, b: &Obb) -> bool {
+    circle_contains_point(c, obb_closest_point(b, c.center))
+}
+
+pub fn circle_overlaps_segment(c: &Circle, s: &Segment) -> bool {
+    circle_contains_point(c, segment_closest_point(s, c.center).0)
+}
+
+pub fn aabb_overlaps_aabb(a: &Aabb, b: &Aabb) -> bool {
+    a.min.x <= b.max.x && b.min.x <= a.max.x &&
+    a.min.y <= b.max.y && b.min.y <= a.max.y
+}
+
+// Separating axis test on the two face normals of each box
+pub fn obb_overlaps_obb(a: &Obb, b: &Obb) -> bool {
+    let a_corners = obb_corners(a);
+    let b_corners = obb_corners(b);
+    let axes = [
+        vector_2f_from_angle(a.rot),
+        vector_2f_perp(vector_2f_from_angle(a.rot)),
+        vector_2f_from_angle(b.rot),
+        vector_2f_perp(vector_2f_from_angle(b.rot)),
+    ];
+    let project = |corners: &[Vector2f; 4], axis: Vector2f| {
+        corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), c| {
+            let d = vector_2f_dot(*c, axis);
+            (lo.min(d), hi.max(d))
+        })
+    };
+    axes.iter().all(|axis| {
+        let (a_lo, a_hi) = project(&a_corners, *axis);
+        let (b_lo, b_hi) = project(&b_corners, *axis);
+        a_lo <= b_hi && b_lo <= a_hi
+    })
+}
+
+// Intersection point of two segments, None for parallel segments
+pub fn segment_intersect_segment(s: &Segment, other: &Segment) -> Option<Vector2f> {
+    let r = vector_2f_sub(s.b, s.a);
+    let q = vector_2f_sub(other.b, other.a);
+    let denom = vector_2f_cross(r, q);
+    if denom.abs() <= f32::EPSILON {
+        return None;
+    }
+    let ao = vector_2f_sub(other.a, s.a);
+    let t = vector_2f_cross(ao, q)/denom;
+    let u = vector_2f_cross(ao, r)/denom;
+    if t.between(0.0, 1.0) && u.between(0.0, 1.0) {
+        Some(vector_2f_add(s.a, vector_2f_scale(r, t)))
+    } else {
+        None
+    }
+}
+
+//
+// Ray casts, only hits with t in [0, max_t] are reported
+//
+
+pub fn ray_cast_circle(r: &Ray, c: &Circle, max_t: f32) -> Option<RayHit> {
+    let m = vector_2f_sub(r.origin, c.center);
+    let a = vector_2f_dot(r.dir, r.dir);
+    let b = vector_2f_dot(m, r.dir);
+    let k = vector_2f_dot(m, m) - c.radius*c.radius;
+    // starting inside, or not moving
+    if k <= 0.0 || a <= 0.0 {
+        return None;
+    }
+    let discriminant = b*b - a*k;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt())/a;
+    if !t.between(0.0, max_t) {
+        return None;
+    }
+    let point = ray_point_at(r, t);
+    let normal = vector_2f_normalize(vector_2f_sub(point, c.center));
+    Some(RayHit { t, point, normal })
+}
+
+// Slab test
+pub fn ray_cast_aabb(r: &Ray, b: &Aabb, max_t: f32) -> Option<RayHit> {
+    if aabb_contains_point(b, r.origin) {
+        return None;
+    }
+    let mut t_enter = 0.0_f32;
+    let mut t_exit = max_t;
+    let mut normal = vector_2f_zero();
+    let slabs = [
+        (r.origin.x, r.dir.x, b.min.x, b.max.x, vector_2f_unitx()),
+        (r.origin.y, r.dir.y, b.min.y, b.max.y, vector_2f_unity()),
+    ];
+    for (origin, dir, min, max, axis) in slabs {
+        if dir == 0.0 {
+            if !origin.between(min, max) {
+                return None;
+            }
+            continue;
+        }
+        let t_min = (min - origin)/dir;
+        let t_max = (max - origin)/dir;
+        // entering through the min side means the surface faces -axis
+        let (t_near, t_far, face) = if t_min < t_max {
+            (t_min, t_max, vector_2f_neg(axis))
+        } else {
+            (t_max, t_min, axis)
+        };
+        if t_near > t_enter {
+            t_enter = t_near;
+            normal = face;
+        }
+        t_exit = t_exit.min(t_far);
+        if t_enter > t_exit {
+            return None;
+        }
+    }
+    Some(RayHit { t: t_enter, point: ray_point_at(r, t_enter), normal })
+}
+
+pub fn ray_cast_obb(r: &Ray, b: &Obb, max_t: f32) -> Option<RayHit> {
+    let local_ray = ray(obb_to_local(b, r.origin), vector_2f_rotate(r.dir, -b.rot));
+    let local_box = aabb(vector_2f_neg(b.half_dim), b.half_dim);
+    let hit = ray_cast_aabb(&local_ray, &local_box, max_t)?;
+    Some(RayHit {
+        t: hit.t,
+        point: obb_from_local(b, hit.point),
+        normal: vector_2f_rotate(hit.normal, b.rot),
+    })
+}
+
+pub fn ray_cast_segment(r: &Ray, s: &Segment, max_t: f32) -> Option<RayHit> {
+    let e = vector_2f_sub(s.b, s.a);
+    let denom = vector_2f_cross(r.dir, e);
+    if denom.abs() <= f32::EPSILON {
+        return None;
+    }
+    let ao = vector_2f_sub(s.a, r.origin);
+    let t = vector_2f_cross(ao, e)/denom;
+    let u = vector_2f_cross(ao, r.dir)/denom;
+    if !t.between(0.0, max_t) || !u.between(0.0, 1.0) {
+        return None;
+    }
+    // whichever side of the segment the ray came from
+    let mut normal = vector_2f_normalize(vector_2f_perp(e));
+    if vector_2f_dot(normal, r.dir) > 0.0 {
+        normal = vector_2f_neg(normal);
+    }
+    Some(RayHit { t, point: ray_point_at(r, t), normal })
+}
+
+//
+// Swept circles, time of impact for a circle moving by delta this frame
+//
+
+// Point is where the two circles touch, t in [0, 1]
+pub fn circle_sweep_circle(moving: &Circle, delta: Vector2f, target: &Circle) -> Option<RayHit> {
+    let r = ray(moving.center, delta);
+    let grown = circle(target.center, target.radius + moving.radius);
+    let hit = ray_cast_circle(&r, &grown, 1.0)?;
+    let point = vector_2f_add(target.center, vector_2f_scale(hit.normal, target.radius));
+    Some(RayHit { point, ..hit })
+}
+
+// Casts against the box grown by the radius with rounded corners. Point is on the box.
+pub fn circle_sweep_aabb(moving: &Circle, delta: Vector2f, target: &Aabb) -> Option<RayHit> {
+    let radius = vector_2f(moving.radius, moving.radius);
+    let grown = aabb(vector_2f_sub(target.min, radius), vector_2f_add(target.max, radius));
+    let r = ray(moving.center, delta);
+    if aabb_contains_point(&grown, moving.center) {
+        if circle_overlaps_aabb(moving, target) {
+            return None;
+        }
+        // starting in the empty part of a grown corner, the rounded corner is the only way in
+        let corner = aabb_closest_point(target, moving.center);
+        let corner_hit = ray_cast_circle(&r, &circle(corner, moving.radius), 1.0)?;
+        return Some(RayHit { point: corner, ..corner_hit });
+    }
+    let hit = ray_cast_aabb(&r, &grown, 1.0)?;
+
+    // entering a corner of the grown box only counts if it hits the rounded corner
+    let p = hit.point;
+    let outside_x = p.x < target.min.x || p.x > target.max.x;
+    let outside_y = p.y < target.min.y || p.y > target.max.y;
+    if outside_x && outside_y {
+        let corner = vector_2f(
+            if p.x < target.min.x { target.min.x } else { target.max.x },
+            if p.y < target.min.y { target.min.y } else { target.max.y },
+        );
+        let corner_hit = ray_cast_circle(&r, &circle(corner, moving.radius), 1.0)?;
+        return Some(RayHit { point: corner, ..corner_hit });
+    }
+
+    let point = vector_2f_sub(hit.point, vector_2f_scale(hit.normal, moving.radius));
+    Some(RayHit { point, ..hit })
+}
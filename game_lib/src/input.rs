@@ -0,0 +1,124 @@
+use crate::camera::*;
+use crate::render::*;
+use crate::state::*;
+
+// Button state over one frame.
+//
+// The platform keeps one GameButton per button across frames, clears the transition counts
+// at the start of every frame and reports every press and release as it comes in.
+// A tap that starts and ends between two frames still shows up as two half transitions.
+
+pub fn game_button(ended_down: bool, half_transition_count: u32) -> GameButton {
+    GameButton { ended_down, half_transition_count, value: if ended_down { 1.0 } else { 0.0 } }
+}
+
+// Call once per frame before processing any platform input
+pub fn input_begin_frame(input: &mut GameInput) {
+    for button in input_buttons_mut(input) {
+        button.half_transition_count = 0;
+    }
+    input.mouse_wheel = 0.0;
+    input.focus_gained = false;
+    input.focus_lost = false;
+    input.resized = false;
+}
+
+// Call once per frame after processing platform input. The camera is the one from the last
+// frame the player saw, so the world point is whatever was drawn under the mouse.
+pub fn input_update_mouse_world_pos(input: &mut GameInput, ctx: &GameState) {
+    input.mouse_world_pos = render_screen_to_world(input.screen_width, input.screen_height, &camera_view(&ctx.camera), input.mouse_pos);
+}
+
+// Key repeats report a down button as down again, those aren't transitions
+pub fn button_process(button: &mut GameButton, is_down: bool) {
+    if button.ended_down != is_down {
+        button.ended_down = is_down;
+        button.half_transition_count += 1;
+    }
+}
+
+// Went down at least once this frame
+pub fn button_pressed(button: &GameButton) -> bool {
+    button.half_transition_count > 1 || (button.half_transition_count == 1 && button.ended_down)
+}
+
+// Came up at least once this frame
+pub fn button_released(button: &GameButton) -> bool {
+    button.half_transition_count > 1 || (button.half_transition_count == 1 && !button.ended_down)
+}
+
+// Down at any point this frame, so a tap between frames still counts as held for a frame
+pub fn button_held(button: &GameButton) -> bool {
+    button.ended_down || button.half_transition_count > 0
+}
+
+// How hard the button is pushed. Analog values count even under the down threshold and a
+// tap between frames counts as fully pushed for a frame.
+pub fn button_value(button: &GameButton) -> f32 {
+    if button.value > 0.0 {
+        button.value
+    }
+    else if button.ended_down || button_pressed(button) {
+        1.0
+    }
+    else {
+        0.0
+    }
+}
+
+// Every button in a fixed order, for code that treats them all the same
+pub fn input_buttons(input: &GameInput) -> [&GameButton; 23] {
+    [
+        &input.turn_left,
+        &input.turn_right,
+        &input.accelerate,
+        &input.decelerate,
+        &input.launch,
+        &input.pause,
+        &input.mouse_left,
+        &input.mouse_right,
+        &input.mouse_middle,
+        &input.controller.a,
+        &input.controller.b,
+        &input.controller.x,
+        &input.controller.y,
+        &input.controller.start,
+        &input.controller.back,
+        &input.controller.left_shoulder,
+        &input.controller.right_shoulder,
+        &input.controller.left_thumb,
+        &input.controller.right_thumb,
+        &input.controller.dpad_up,
+        &input.controller.dpad_down,
+        &input.controller.dpad_left,
+        &input.controller.dpad_right,
+    ]
+}
+
+pub fn input_buttons_mut(input: &mut GameInput) -> [&mut GameButton; 23] {
+    [
+        &mut input.turn_left,
+        &mut input.turn_right,
+        &mut input.accelerate,
+        &mut input.decelerate,
+        &mut input.launch,
+        &mut input.pause,
+        &mut input.mouse_left,
+        &mut input.mouse_right,
+        &mut input.mouse_middle,
+        &mut input.controller.a,
+        &mut input.controller.b,
+        &mut input.controller.x,
+        &mut input.controller.y,
+        &mut input.controller.start,
+        &mut input.controller.back,
+        &mut input.controller.left_shoulder,
+        &mut input.controller.right_shoulder,
+        &mut input.controller.left_thumb,
+        &mut input.controller.right_thumb,
+        &mut input.controller.dpad_up,
+        &mut input.controller.dpad_down,
+        &mut input.controller.dpad_left,
+        &mut input.controller.dpad_right,
+    ]
+}
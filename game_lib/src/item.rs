@@ -0,0 +1,90 @@
+use crate::geometry::*;
+use crate::math::*;
+use crate::state::*;
+use crate::world::*;
+
+// Planet item pickups.
+//
+// Flying within ITEM_PICKUP_RADIUS of an item collects it, even if the ship only passed it
+// during a step. Fuel and repair kits are used straight away and are left where they are
+// while they would do nothing. Cargo and keys go into the ship inventory and stay on the
+// planet while the inventory is full. Gems only add to the score.
+
+pub const ITEM_PICKUP_RADIUS: f32 = 20.0;
+pub const SHIP_INVENTORY_CAPACITY: u32 = 4;
+
+const ITEM_FUEL_AMOUNT: f32 = 0.5;   // of a full tank
+const ITEM_REPAIR_AMOUNT: f32 = 0.5; // of a full hull
+const ITEM_GEM_SCORE: u32 = 100;
+const ITEM_CARGO_SCORE: u32 = 10;
+
+pub fn item_color(itype: PlanetItemType) -> Vector4f {
+    match itype {
+        PlanetItemType::None => vector_4f(0.5, 0.5, 0.5, 1.0),
+        PlanetItemType::Fuel => vector_4f(0.1, 0.2, 1.0, 1.0),
+        PlanetItemType::Repair => vector_4f(0.9, 0.9, 0.9, 1.0),
+        PlanetItemType::Cargo => vector_4f(0.7, 0.45, 0.2, 1.0),
+        PlanetItemType::Key => vector_4f(1.0, 0.85, 0.1, 1.0),
+        PlanetItemType::Gem => vector_4f(0.9, 0.2, 0.9, 1.0),
+    }
+}
+
+// Applies the item to the ship, false when it can't be taken right now
+pub fn item_collect(ship: &mut Ship, score: &mut u32, itype: PlanetItemType) -> bool {
+    match itype {
+        PlanetItemType::None => false,
+        PlanetItemType::Fuel => {
+            if ship.fuel_level >= 1.0 {
+                return false;
+            }
+            ship.fuel_level = (ship.fuel_level + ITEM_FUEL_AMOUNT).min(1.0);
+            true
+        },
+        PlanetItemType::Repair => {
+            if ship.hull >= 1.0 {
+                return false;
+            }
+            ship.hull = (ship.hull + ITEM_REPAIR_AMOUNT).min(1.0);
+            true
+        },
+        PlanetItemType::Cargo | PlanetItemType::Key => {
+            if ship.inventory.len() as u32 >= ship.inventory_capacity {
+                return false;
+            }
+            ship.inventory.push(itype);
+            if itype == PlanetItemType::Cargo {
+                *score += ITEM_CARGO_SCORE;
+            }
+            true
+        },
+        PlanetItemType::Gem => {
+            *score += ITEM_GEM_SCORE;
+            true
+        },
+    }
+}
+
+// Collects every item the ship came within reach of this step and removes it from its planet.
+// The step from prev_pos to pos is swept so a fast ship can't pass over an item between two
+// steps. Items ride their planet, so like the surfaces each one is swept in its planet's frame
+// at the copy nearest the ship.
+pub fn item_pickup(ctx: &mut GameState) {
+    let planets = match ctx.planets.as_mut() {
+        Some(p) => p,
+        None => return,
+    };
+    let pos = ctx.player.pos;
+    let delta = vector_2f_sub(pos, ctx.player.prev_pos);
+    let world_size = ctx.world_size;
+    for planet in planets.iter_mut() {
+        let moved = world_delta(planet.prev_pos, planet.pos, world_size);
+        let ship = circle(vector_2f_sub(vector_2f_add(pos, moved), delta), 0.0);
+        let planet_pos = world_nearest(pos, planet.pos, world_size);
+        planet.items.retain(|item| {
+            let reach = circle(vector_2f_add(planet_pos, item.pos), ITEM_PICKUP_RADIUS);
+            let in_reach = circle_contains_point(&reach, pos)
+                || circle_sweep_circle(&ship, vector_2f_sub(delta, moved), &reach).is_some();
+            !(in_reach && item_collect(&mut ctx.ship, &mut ctx.score, item.itype))
+        });
+    }
+}
@@ -0,0 +1,410 @@
+use crate::math::*;
+use crate::orbit::*;
+use crate::physics::*;
+use crate::state::*;
+
+// Solar system definitions loaded from text files.
+//
+// One entity per line, written as a keyword followed by key=value pairs. Vectors are
+// comma separated and # starts a comment. Orbits and items belong to the planet above them.
+//
+//   gravity g=1000
+//   sun     pos=0,0 mass=1500 softening=150
+//   spawn   pos=0,0 rot=0
+//   planet  pos=-600,-400 radius=100 g_radius=250 mass=3000 color=0.2,0.5,0.5,1
+//   orbit   around=sun
+//   item    type=fuel pos=110,40
+//
+// Every level needs one sun and one spawn. The gravity line is optional and defaults to
+// PHYSICS_DEFAULT_G, as is the sun's softening.
+// Planet keys surface_radius (defaults to radius), lz_pos and lz_color are optional.
+// An orbit goes around the sun or an earlier planet by its number in the file, counting
+// from 1. Without a= it's the circle through the planet's pos, otherwise a= e= periapsis=
+// and phase= give the ellipse and pos is ignored. dir=cw orbits clockwise, ccw is the default.
+// Item types are fuel, repair, cargo, key and gem. Items inside the surface can't be reached.
+
+pub const DEFAULT_LEVEL: &str = include_str!("../levels/default.level");
+
+#[derive(Default)]
+pub struct Level {
+    pub gravity_g: f32,
+    pub sun: Sun,
+    pub spawn_pos: Vector2f,
+    pub spawn_rot: f32,
+    pub planets: Vec<Planet>,
+}
+
+#[derive(Debug)]
+pub struct LevelError {
+    // 1 based, 0 when the error isn't about a specific line
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for LevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line > 0 {
+            write!(f, "level line {}: {}", self.line, self.message)
+        }
+        else {
+            write!(f, "level: {}", self.message)
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+fn level_error(line: usize, message: String) -> LevelError {
+    LevelError { line, message }
+}
+
+struct LevelEntry<'a> {
+    line: usize,
+    keyword: &'a str,
+    values: Vec<(&'a str, &'a str)>,
+}
+
+fn level_entry_get<'a>(entry: &LevelEntry<'a>, key: &str) -> Option<&'a str> {
+    entry.values.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+fn level_parse_floats(entry: &LevelEntry, key: &str, count: usize) -> Result<Option<Vec<f32>>, LevelError> {
+    let value = match level_entry_get(entry, key) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let mut floats = Vec::with_capacity(count);
+    for part in value.split(',') {
+        let f: f32 = part.trim().parse().map_err(|_| {
+            level_error(entry.line, format!("'{}={}' is not a number", key, value))
+        })?;
+        if !f.is_finite() {
+            return Err(level_error(entry.line, format!("'{}={}' is not finite", key, value)));
+        }
+        floats.push(f);
+    }
+    if floats.len() != count {
+        return Err(level_error(entry.line, format!("'{}' expects {} values but has {}", key, count, floats.len())));
+    }
+    Ok(Some(floats))
+}
+
+fn level_f32(entry: &LevelEntry, key: &str, default: Option<f32>) -> Result<f32, LevelError> {
+    match (level_parse_floats(entry, key, 1)?, default) {
+        (Some(v), _) => Ok(v[0]),
+        (None, Some(d)) => Ok(d),
+        (None, None) => Err(level_error(entry.line, format!("{} is missing '{}'", entry.keyword, key))),
+    }
+}
+
+fn level_vector_2f(entry: &LevelEntry, key: &str, default: Option<Vector2f>) -> Result<Vector2f, LevelError> {
+    match (level_parse_floats(entry, key, 2)?, default) {
+        (Some(v), _) => Ok(vector_2f(v[0], v[1])),
+        (None, Some(d)) => Ok(d),
+        (None, None) => Err(level_error(entry.line, format!("{} is missing '{}'", entry.keyword, key))),
+    }
+}
+
+fn level_color(entry: &LevelEntry, key: &str, default: Option<Vector4f>) -> Result<Vector4f, LevelError> {
+    let color = match (level_parse_floats(entry, key, 4)?, default) {
+        (Some(v), _) => vector_4f(v[0], v[1], v[2], v[3]),
+        (None, Some(d)) => return Ok(d),
+        (None, None) => return Err(level_error(entry.line, format!("{} is missing '{}'", entry.keyword, key))),
+    };
+    let channels = [color.x, color.y, color.z, color.w];
+    if channels.iter().any(|c| !c.between(0.0, 1.0)) {
+        return Err(level_error(entry.line, format!("'{}' color channels must be between 0 and 1", key)));
+    }
+    Ok(color)
+}
+
+fn level_check_keys(entry: &LevelEntry, allowed: &[&str]) -> Result<(), LevelError> {
+    for (i, (key, _)) in entry.values.iter().enumerate() {
+        if !allowed.contains(key) {
+            return Err(level_error(entry.line, format!("unknown {} key '{}'", entry.keyword, key)));
+        }
+        if entry.values[..i].iter().any(|(k, _)| k == key) {
+            return Err(level_error(entry.line, format!("'{}' is set more than once", key)));
+        }
+    }
+    Ok(())
+}
+
+fn level_item_type(entry: &LevelEntry) -> Result<PlanetItemType, LevelError> {
+    match level_entry_get(entry, "type") {
+        Some("fuel") => Ok(PlanetItemType::Fuel),
+        Some("repair") => Ok(PlanetItemType::Repair),
+        Some("cargo") => Ok(PlanetItemType::Cargo),
+        Some("key") => Ok(PlanetItemType::Key),
+        Some("gem") => Ok(PlanetItemType::Gem),
+        Some(other) => Err(level_error(entry.line, format!("unknown item type '{}'", other))),
+        None => Err(level_error(entry.line, "item is missing 'type'".to_string())),
+    }
+}
+
+// Orbit of the planet at planet_index. Circles through the planet's pos come back with a
+// semi_major of 0, they can only be worked out once the whole level is read.
+fn level_orbit(entry: &LevelEntry, planet_index: usize) -> Result<Orbit, LevelError> {
+    let parent = match level_entry_get(entry, "around") {
+        None | Some("sun") => OrbitParent::Sun,
+        Some(n) => match n.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= planet_index => OrbitParent::Planet(n - 1),
+            _ => return Err(level_error(entry.line, format!("an orbit goes around the sun or an earlier planet, not '{}'", n))),
+        },
+    };
+    let clockwise = match level_entry_get(entry, "dir") {
+        None | Some("ccw") => false,
+        Some("cw") => true,
+        Some(other) => return Err(level_error(entry.line, format!("unknown orbit dir '{}'", other))),
+    };
+
+    if level_entry_get(entry, "a").is_none() {
+        if ["e", "periapsis", "phase"].iter().any(|k| level_entry_get(entry, k).is_some()) {
+            return Err(level_error(entry.line, "orbit needs 'a' for e, periapsis or phase".to_string()));
+        }
+        return Ok(Orbit { parent, semi_major: 0.0, eccentricity: 0.0, periapsis_rot: 0.0, phase: 0.0, clockwise });
+    }
+    let orbit = Orbit {
+        parent,
+        semi_major: level_f32(entry, "a", None)?,
+        eccentricity: level_f32(entry, "e", Some(0.0))?,
+        periapsis_rot: level_f32(entry, "periapsis", Some(0.0))?,
+        phase: level_f32(entry, "phase", Some(0.0))?,
+        clockwise,
+    };
+    if orbit.semi_major <= 0.0 {
+        return Err(level_error(entry.line, "orbit a must be greater than 0".to_string()));
+    }
+    if !orbit.eccentricity.between(0.0, ORBIT_MAX_ECCENTRICITY) {
+        return Err(level_error(entry.line, format!("orbit e must be between 0 and {}", ORBIT_MAX_ECCENTRICITY)));
+    }
+    Ok(orbit)
+}
+
+fn level_tokenize(line: usize, text: &str) -> Result<Option<LevelEntry<'_>>, LevelError> {
+    let text = match text.find('#') {
+        Some(comment) => &text[..comment],
+        None => text,
+    };
+    let mut tokens = text.split_whitespace();
+    let keyword = match tokens.next() {
+        Some(k) => k,
+        None => return Ok(None),
+    };
+    let mut values = Vec::new();
+    for token in tokens {
+        match token.split_once('=') {
+            Some((key, value)) if !key.is_empty() && !value.is_empty() => values.push((key, value)),
+            _ => return Err(level_error(line, format!("expected key=value but found '{}'", token))),
+        }
+    }
+    Ok(Some(LevelEntry { line, keyword, values }))
+}
+
+pub fn level_parse(text: &str) -> Result<Level, LevelError> {
+    let mut level = Level { gravity_g: PHYSICS_DEFAULT_G, ..Level::default() };
+    let mut gravity_line = None;
+    let mut sun_line = None;
+    let mut spawn_line = None;
+
+    for (i, line_text) in text.lines().enumerate() {
+        let entry = match level_tokenize(i + 1, line_text)? {
+            Some(e) => e,
+            None => continue,
+        };
+        let line = entry.line;
+
+        match entry.keyword {
+            "gravity" => {
+                if let Some(first) = gravity_line {
+                    return Err(level_error(line, format!("gravity can only be set once, the first is on line {}", first)));
+                }
+                gravity_line = Some(line);
+                level_check_keys(&entry, &["g"])?;
+                level.gravity_g = level_f32(&entry, "g", None)?;
+                if level.gravity_g <= 0.0 {
+                    return Err(level_error(line, "gravity g must be greater than 0".to_string()));
+                }
+            },
+            "sun" => {
+                if let Some(first) = sun_line {
+                    return Err(level_error(line, format!("only one sun is allowed, the first is on line {}", first)));
+                }
+                sun_line = Some(line);
+                level_check_keys(&entry, &["pos", "mass", "softening"])?;
+                level.sun = Sun {
+                    pos: level_vector_2f(&entry, "pos", Some(vector_2f_zero()))?,
+                    mass: level_f32(&entry, "mass", None)?,
+                    softening: level_f32(&entry, "softening", Some(PHYSICS_DEFAULT_SUN_SOFTENING))?,
+                };
+                if level.sun.mass < 0.0 || level.sun.softening < 0.0 {
+                    return Err(level_error(line, "sun mass and softening can't be negative".to_string()));
+                }
+            },
+            "spawn" => {
+                if let Some(first) = spawn_line {
+                    return Err(level_error(line, format!("only one spawn is allowed, the first is on line {}", first)));
+                }
+                spawn_line = Some(line);
+                level_check_keys(&entry, &["pos", "rot"])?;
+                level.spawn_pos = level_vector_2f(&entry, "pos", None)?;
+                level.spawn_rot = level_f32(&entry, "rot", Some(0.0))?;
+            },
+            "planet" => {
+                level_check_keys(&entry, &["pos", "radius", "surface_radius", "g_radius", "mass", "color", "lz_pos", "lz_color"])?;
+                let radius = level_f32(&entry, "radius", None)?;
+                let pos = level_vector_2f(&entry, "pos", None)?;
+                let planet = Planet {
+                    pos,
+                    prev_pos: pos,
+                    radius,
+                    surface_radius: level_f32(&entry, "surface_radius", Some(radius))?,
+                    g_radius: level_f32(&entry, "g_radius", None)?,
+                    mass: level_f32(&entry, "mass", None)?,
+                    color: level_color(&entry, "color", None)?,
+                    lz_rel_pos: level_vector_2f(&entry, "lz_pos", Some(vector_2f_zero()))?,
+                    lz_color: level_color(&entry, "lz_color", Some(vector_4f(0.2, 0.2, 0.2, 1.0)))?,
+                    ..Planet::default()
+                };
+                if planet.radius <= 0.0 {
+                    return Err(level_error(line, "planet radius must be greater than 0".to_string()));
+                }
+                if planet.surface_radius <= 0.0 || planet.surface_radius > planet.radius {
+                    return Err(level_error(line, "planet surface_radius must be greater than 0 and no bigger than radius".to_string()));
+                }
+                if planet.g_radius <= planet.radius {
+                    return Err(level_error(line, format!("planet g_radius {} must be greater than radius {}", planet.g_radius, planet.radius)));
+                }
+                if planet.mass < 0.0 {
+                    return Err(level_error(line, "planet mass can't be negative".to_string()));
+                }
+                level.planets.push(planet);
+            },
+            "orbit" => {
+                level_check_keys(&entry, &["around", "a", "e", "periapsis", "phase", "dir"])?;
+                let index = match level.planets.len() {
+                    0 => return Err(level_error(line, "orbit must come after the planet it belongs to".to_string())),
+                    n => n - 1,
+                };
+                if level.planets[index].orbit.is_some() {
+                    return Err(level_error(line, "a planet can only have one orbit".to_string()));
+                }
+                level.planets[index].orbit = Some(level_orbit(&entry, index)?);
+            },
+            "item" => {
+                level_check_keys(&entry, &["type", "pos"])?;
+                let planet = match level.planets.last_mut() {
+                    Some(p) => p,
+                    None => return Err(level_error(line, "item must come after the planet it belongs to".to_string())),
+                };
+                if planet.items.len() >= MAX_PLANET_ITEMS {
+                    return Err(level_error(line, format!("a planet can hold at most {} items", MAX_PLANET_ITEMS)));
+                }
+                planet.items.push(PlanetItem {
+                    itype: level_item_type(&entry)?,
+                    pos: level_vector_2f(&entry, "pos", None)?,
+                });
+            },
+            other => {
+                return Err(level_error(line, format!("unknown entity '{}'", other)));
+            },
+        }
+    }
+
+    if sun_line.is_none() {
+        return Err(level_error(0, "level has no sun".to_string()));
+    }
+    if spawn_line.is_none() {
+        return Err(level_error(0, "level has no spawn".to_string()));
+    }
+
+    // put every planet where its orbit has it at time 0, parents before their moons so a
+    // circle through pos goes round wherever the parent is then
+    for i in 0..level.planets.len() {
+        if let Some(orbit) = level.planets[i].orbit.filter(|o| o.semi_major <= 0.0) {
+            let parent_pos = match orbit.parent {
+                OrbitParent::Sun => level.sun.pos,
+                OrbitParent::Planet(j) => level.planets[j].pos,
+            };
+            let rel_pos = vector_2f_sub(level.planets[i].pos, parent_pos);
+            if vector_2f_length(rel_pos) <= 0.0 {
+                return Err(level_error(0, format!("planet {} sits at the center of its own orbit", i + 1)));
+            }
+            level.planets[i].orbit = Some(orbit_circular(orbit.parent, rel_pos, orbit.clockwise));
+        }
+        orbit_place_planets(level.gravity_g, &level.sun, &mut level.planets[..=i], 0.0, vector_2f_zero());
+    }
+
+    Ok(level)
+}
+
+pub fn level_load_file(path: &std::path::Path) -> Result<Level, LevelError> {
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        level_error(0, format!("unable to read {}: {}", path.display(), e))
+    })?;
+    level_parse(&text)
+}
+
+fn level_item_type_name(itype: PlanetItemType) -> Option<&'static str> {
+    match itype {
+        PlanetItemType::None => None,
+        PlanetItemType::Fuel => Some("fuel"),
+        PlanetItemType::Repair => Some("repair"),
+        PlanetItemType::Cargo => Some("cargo"),
+        PlanetItemType::Key => Some("key"),
+        PlanetItemType::Gem => Some("gem"),
+    }
+}
+
+// Text that level_parse reads back into the same level.
+// Items of type None have no name in the format and are left out.
+pub fn level_write(level: &Level) -> String {
+    use std::fmt::Write;
+    let v2 = |v: Vector2f| format!("{},{}", v.x, v.y);
+    let v4 = |v: Vector4f| format!("{},{},{},{}", v.x, v.y, v.z, v.w);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "gravity g={}", level.gravity_g);
+    let _ = writeln!(out, "sun     pos={} mass={} softening={}", v2(level.sun.pos), level.sun.mass, level.sun.softening);
+    let _ = writeln!(out, "spawn   pos={} rot={}", v2(level.spawn_pos), level.spawn_rot);
+    for p in level.planets.iter() {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "planet  pos={} radius={} surface_radius={} g_radius={} mass={} color={} lz_pos={} lz_color={}",
+            v2(p.pos), p.radius, p.surface_radius, p.g_radius, p.mass, v4(p.color), v2(p.lz_rel_pos), v4(p.lz_color));
+        if let Some(orbit) = &p.orbit {
+            let around = match orbit.parent {
+                OrbitParent::Sun => "sun".to_string(),
+                OrbitParent::Planet(j) => (j + 1).to_string(),
+            };
+            let _ = writeln!(out, "orbit   around={} a={} e={} periapsis={} phase={} dir={}",
+                around, orbit.semi_major, orbit.eccentricity, orbit.periapsis_rot, orbit.phase, if orbit.clockwise { "cw" } else { "ccw" });
+        }
+        for item in p.items.iter() {
+            if let Some(name) = level_item_type_name(item.itype) {
+                let _ = writeln!(out, "item    type={} pos={}", name, v2(item.pos));
+            }
+        }
+    }
+    out
+}
+
+// Replaces the sun and planets and puts the player on the spawn point
+pub fn level_apply(level: &Level, ctx: &mut GameState) {
+    ctx.gravity_g = level.gravity_g;
+    ctx.sun = level.sun.clone();
+    let mut planets = level.planets.clone();
+    orbit_place_planets(ctx.gravity_g, &ctx.sun, &mut planets, 0.0, ctx.world_size);
+    ctx.planets = Some(planets);
+    ctx.sim_time_sec = 0.0;
+    ctx.spawn_pos = level.spawn_pos;
+    ctx.spawn_rot = level.spawn_rot;
+    ctx.last_landing_planet = None;
+
+    ctx.player = Player {
+        pos: level.spawn_pos,
+        prev_pos: level.spawn_pos,
+        rot: level.spawn_rot,
+        prev_rot: level.spawn_rot,
+        ..Default::default()
+    };
+}
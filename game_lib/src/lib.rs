@@ -1,72 +1,163 @@
+pub mod action;
+pub mod bitmap;
+pub mod camera;
+pub mod controller;
+pub mod generate;
+pub mod geometry;
+pub mod input;
+pub mod item;
+pub mod level;
 pub mod math;
+pub mod nav;
+pub mod orbit;
+pub mod physics;
+pub mod projectile;
+pub mod random;
 pub mod render;
+pub mod render_software;
+pub mod replay;
+pub mod save;
+pub mod ship;
 pub mod state;
+pub mod stream;
+pub mod world;
 
+use camera::*;
+use generate::*;
+use input::*;
+use item::*;
+use level::*;
 use math::*;
+use nav::*;
+use orbit::*;
+use physics::*;
+use projectile::*;
+use random::*;
+use render::*;
+use ship::*;
 use state::*;
+use world::*;
 
-pub type GameUpdateAndRenderFunc = extern "C" fn (input: &GameInput, ctx: &mut GameState); 
+pub const GAME_TITLE: &str = "MCV Game Template";
+
+pub type GameUpdateAndRenderFunc = extern "C" fn (input: &GameInput, ctx: &mut GameState, render_buffer: &mut RenderBuffer); 
+
+// Simulation runs at a fixed step no matter how fast frames are rendered
+pub const SIM_DEFAULT_STEP_SEC: f32 = 1.0 / 60.0;
+// Shortest step allowed, so a long frame is at most a few hundred steps
+pub const SIM_MIN_STEP_SEC: f32 = 1.0 / 1000.0;
+// Clamp long frames (breakpoints, window drags) so the sim doesn't spiral trying to catch up
+pub const SIM_MAX_FRAME_SEC: f32 = 0.25;
+
+// Ship physics in per second units
+const SHIP_THRUST_ACCELERATION: f32 = 270.0; // units/s^2
+const SHIP_TURN_RATE: f32 = 3.0;             // radians/s
+const SHIP_FUEL_BURN_RATE: f32 = 0.03;       // fuel/s while burning
+const TITLE_FADE_RATE: f32 = 0.6;            // fade/s once the player moves
+const LANDING_OUTCOME_FADE_SEC: f32 = 3.0;   // how long the HUD shows a landing outcome
+const EXPLOSION_SEC: f32 = 1.0;              // how long a destroyed ship's explosion lasts
+const EXPLOSION_RADIUS: f32 = 60.0;          // units at the end of the explosion
+// Launch is ignored for a moment after a level ends so a held button doesn't skip the overlay
+const GAME_MODE_INPUT_DELAY_SEC: f32 = 0.5;
 
 #[no_mangle]
-pub extern "C" fn update_and_render(input: &GameInput, ctx: &mut GameState) {
+pub extern "C" fn update_and_render(input: &GameInput, ctx: &mut GameState, render_buffer: &mut RenderBuffer) {
 
     let screen_width = input.screen_width;
     let screen_height = input.screen_height;
-    let world_width  = 2.0 * screen_width as f32;
-    let world_height = 2.0 * screen_height as f32;
+    let world_size = vector_2f(2.0 * screen_width as f32, 2.0 * screen_height as f32);
 
-    // title fade based on movement
-    if ctx.player.pos.x != 0.0 || ctx.player.pos.y != 0.0 {
-        ctx.title_fade += 0.02;
-        ctx.title_fade = ctx.title_fade.min(1.0);
+    // zero, infinite or NaN steps would stop the sim or poison sim_alpha
+    if !(ctx.sim_step_sec.is_finite() && ctx.sim_step_sec > 0.0) {
+        ctx.sim_step_sec = SIM_DEFAULT_STEP_SEC;
+    }
+    ctx.sim_step_sec = ctx.sim_step_sec.max(SIM_MIN_STEP_SEC);
+
+    ctx.events.clear();
+
+    game_init(ctx, world_size);
+
+    // buttons that act once per press
+    let pause_pressed = button_pressed(&input.pause);
+    let launch_pressed = button_pressed(&input.launch);
+    ctx.mode_sec += input.frame_dt_sec.max(0.0);
+
+    match ctx.mode {
+        GameMode::Title => {
+            let flying = button_held(&input.accelerate) || button_held(&input.decelerate)
+                || button_held(&input.turn_left) || button_held(&input.turn_right);
+            if flying || launch_pressed {
+                game_set_mode(ctx, GameMode::Playing);
+            }
+        },
+        GameMode::Playing => {
+            // switching to another window pauses so the ship isn't lost while nobody's looking
+            if pause_pressed || input.focus_lost {
+                game_set_mode(ctx, GameMode::Paused);
+            } else {
+                if launch_pressed {
+                    ctx.launch_queued = true;
+                }
+                game_update_playing(input, ctx);
+            }
+        },
+        GameMode::Paused => {
+            if pause_pressed {
+                game_set_mode(ctx, GameMode::Playing);
+            }
+        },
+        GameMode::LevelComplete => {
+            if launch_pressed && ctx.mode_sec >= GAME_MODE_INPUT_DELAY_SEC {
+                game_next_level(ctx, world_size);
+            }
+        },
+        GameMode::GameOver => {
+            if launch_pressed && ctx.mode_sec >= GAME_MODE_INPUT_DELAY_SEC {
+                game_restart(ctx, world_size);
+            }
+        },
     }
 
+    camera_update(ctx, input.frame_dt_sec.max(0.0));
+    game_render(input, ctx, render_buffer);
+}
+
+// Fills in everything that hasn't been set up yet, a fresh GameState is built up completely
+fn game_init(ctx: &mut GameState, world_size: Vector2f) {
+    ctx.world_size = world_size;
     if ctx.ship.fuel_burn_rate == 0.0 {
         ctx.ship.fuel_level = 1.0;
-        ctx.ship.fuel_burn_rate = 0.001;
+        ctx.ship.fuel_burn_rate = SHIP_FUEL_BURN_RATE;
+        ctx.ship.hull = 1.0;
+        ctx.ship.inventory_capacity = SHIP_INVENTORY_CAPACITY;
+        ctx.lives = SHIP_START_LIVES;
     }
 
-    // init planets
-    if let None = ctx.planets {
-        ctx.sun = Sun {
-            pos: vector_2f(0.0, 0.0),
-            g_force: 0.002
-        };
+    if !ctx.initialized {
+        // everything procedural is generated from the seed so it can be reproduced
+        ctx.rng = random_seed(ctx.seed);
+        ctx.initialized = true;
+    }
 
-        let mut planets = Vec::new();
-        let pos = [
-            vector_2f(-600.0, -400.0),
-            vector_2f(-600.0,  400.0),
-            vector_2f( 600.0,  400.0),
-            vector_2f( 600.0, -400.0),
-        ]; 
-        for p in pos {
-            planets.push(Planet {
-                pos: p,
-                radius: 100.0,
-                surface_radius: 100.0,
-                g_radius: 250.0,
-                g_force: 9.8, // 8 meters/s
-                color: vector_4f(0.2, 0.5, 0.5, 1.0),
-                lz_rel_pos: vector_2f(0.0, 0.0),
-                lz_color: vector_4f(0.2, 0.2, 0.2, 1.0),
-                item: PlanetItem { 
-                    itype: PlanetItemType::Fuel , 
-                    pos: vector_2f(10.0, 10.0) 
-                },
-            });
-        } 
-        ctx.planets = Some(planets);
+    // init planets, unless the platform already loaded a level
+    if ctx.planets.is_none() {
+        let level = if ctx.generate_planets {
+            let params = generate_params(world_size);
+            generate_level(&params, &mut ctx.rng)
+        } else {
+            level_parse(DEFAULT_LEVEL).expect("Built in default level is invalid")
+        };
+        level_apply(&level, ctx);
     }
 
     // init stars
-    if let None = ctx.space_stars {
+    if ctx.space_stars.is_none() {
         let star_density = 150;
         let star_offset_dist = 10.0;
-        let star_x_step = world_width/star_density as f32;
-        let star_y_step = world_height/star_density as f32;
-        let star_start_x = -world_width/2.0;
-        let star_start_y = -world_height/2.0;
+        let star_x_step = world_size.x/star_density as f32;
+        let star_y_step = world_size.y/star_density as f32;
+        let star_start_x = -world_size.x/2.0;
+        let star_start_y = -world_size.y/2.0;
         let mut stars = Vec::new();
         for y in 0..star_density {
             for x in 0..star_density {
@@ -74,8 +165,8 @@ pub extern "C" fn update_and_render(input: &GameInput, ctx: &mut GameState) {
                     star_start_x + (x as f32 * star_x_step),
                     star_start_y + (y as f32 * star_y_step),
                 );
-                let ox = -star_offset_dist + (2.0 * star_offset_dist * rand::random::<f32>());
-                let oy = -star_offset_dist + (2.0 * star_offset_dist * rand::random::<f32>());
+                let ox = star_offset_dist * random_bilateral(&mut ctx.rng);
+                let oy = star_offset_dist * random_bilateral(&mut ctx.rng);
                 let offset = vector_2f(ox, oy);
                 let point = vector_2f_add(point, offset);
                 let size = 1.0;
@@ -83,21 +174,141 @@ pub extern "C" fn update_and_render(input: &GameInput, ctx: &mut GameState) {
             }
         }
         ctx.space_stars = Some(stars);
+    }
+}
+
+pub fn game_set_mode(ctx: &mut GameState, mode: GameMode) {
+    ctx.mode = mode;
+    ctx.mode_sec = 0.0;
+}
+
+fn game_update_playing(input: &GameInput, ctx: &mut GameState) {
+    // Run as many fixed steps as the real frame time covers and carry the remainder
+    ctx.sim_accumulator_sec += input.frame_dt_sec.clamp(0.0, SIM_MAX_FRAME_SEC);
+    while ctx.sim_accumulator_sec >= ctx.sim_step_sec {
+        let dt = ctx.sim_step_sec;
+        game_simulate(input, ctx, dt);
+        ctx.sim_accumulator_sec -= dt;
+    }
+    // how far we are between the last two sim steps
+    ctx.sim_alpha = ctx.sim_accumulator_sec / ctx.sim_step_sec;
+
+    // Show where the ship will drift if it stops burning, nothing to show when landed
+    ctx.nav_path = if ctx.player.landed || ctx.ship.destroyed {
+        None
+    } else {
+        Some(nav_predict(ctx, ctx.world_size, NAV_PREDICT_SEC))
     };
-    
+
+    if ctx.ship.destroyed && ctx.lives == 0 && ctx.respawn_sec >= SHIP_RESPAWN_DELAY_SEC {
+        game_set_mode(ctx, GameMode::GameOver);
+    } else if game_level_complete(ctx) {
+        game_set_mode(ctx, GameMode::LevelComplete);
+    }
+}
+
+// Every gem picked up and sitting safely on a pad.
+// A level without gems is complete on the first safe landing.
+pub fn game_level_complete(ctx: &GameState) -> bool {
+    let gems_left = ctx.planets.iter().flatten()
+        .flat_map(|p| p.items.iter())
+        .any(|item| item.itype == PlanetItemType::Gem);
+    !gems_left && ctx.player.landed && ctx.landing_outcome == LandingOutcome::Landed
+}
+
+// Levels after the first are generated from the seed, score, lives and cargo carry over
+fn game_next_level(ctx: &mut GameState, world_size: Vector2f) {
+    let level = generate_level(&generate_params(world_size), &mut ctx.rng);
+    level_apply(&level, ctx);
+    ctx.level_number += 1;
+    ctx.ship.fuel_level = 1.0;
+    ctx.landing_outcome = LandingOutcome::None;
+    ctx.nav_path = None;
+    ctx.debug_player_forces = None;
+    ctx.projectiles.clear();
+    game_set_mode(ctx, GameMode::Playing);
+}
+
+// Starts over exactly like a fresh launch with the same seed
+fn game_restart(ctx: &mut GameState, world_size: Vector2f) {
+    *ctx = GameState {
+        seed: ctx.seed,
+        generate_planets: ctx.generate_planets,
+        sim_step_sec: ctx.sim_step_sec,
+        mode: GameMode::Playing,
+        ..Default::default()
+    };
+    game_init(ctx, world_size);
+}
+
+fn game_simulate(input: &GameInput, ctx: &mut GameState, dt: f32) {
+    let world_size = ctx.world_size;
+
+    ctx.player.prev_pos = ctx.player.pos;
+    ctx.player.prev_rot = ctx.player.rot;
+
+    // Planets move first and everything else takes its step around them.
+    // A landed ship rides along with the planet it's sitting on.
+    let carrier = match &ctx.planets {
+        Some(planets) if ctx.player.landed && !ctx.ship.destroyed => {
+            physics_surface_planet(planets, ctx.player.pos, world_size).map(|i| (i, planets[i].d_pos))
+        },
+        _ => None,
+    };
+    ctx.sim_time_sec += dt;
+    if let Some(planets) = ctx.planets.as_mut() {
+        orbit_update_planets(ctx.gravity_g, &ctx.sun, planets, ctx.sim_time_sec, world_size);
+    }
+    let mut carry = vector_2f_zero();
+    if let (Some((i, old_d_pos)), Some(planets)) = (carrier, &ctx.planets) {
+        let planet = &planets[i];
+        carry = world_delta(planet.prev_pos, planet.pos, world_size);
+        ctx.player.pos = vector_2f_add(ctx.player.pos, carry);
+        ctx.player.d_pos = vector_2f_add(ctx.player.d_pos, vector_2f_sub(planet.d_pos, old_d_pos));
+    }
+
+    projectile_update(ctx, world_size, dt);
+
+    // nothing to fly until the ship respawns
+    if ctx.ship.destroyed {
+        ctx.launch_queued = false;
+        ship_update_destroyed(ctx, dt);
+        return;
+    }
+
+    // launch takes off from a planet when landed and fires when flying
+    if ctx.launch_queued {
+        ctx.launch_queued = false;
+        if ctx.player.landed {
+            ship_launch(ctx);
+        } else {
+            projectile_fire(ctx);
+        }
+    }
+
+    // title fade based on movement
+    if ctx.player.pos.x != 0.0 || ctx.player.pos.y != 0.0 {
+        ctx.title_fade += TITLE_FADE_RATE * dt;
+        ctx.title_fade = ctx.title_fade.min(1.0);
+    }
+
+    // analog inputs give partial thrust and turn rates, keys are all or nothing
+    let thrust = button_value(&input.accelerate);
+    let brake = button_value(&input.decelerate);
     let mut acceleration = 0.0;
-    if input.accelerate {
-        acceleration = 0.3;
+    if thrust > 0.0 {
+        acceleration = SHIP_THRUST_ACCELERATION * thrust;
     }
-    if input.decelerate {
-        acceleration = -0.3;
+    if brake > 0.0 {
+        acceleration = -SHIP_THRUST_ACCELERATION * brake;
     }
 
     // Handle space flight burn
     if !ctx.player.landed {
         // acceleration is only allowed if we have fuel
         ctx.ship.fuel_level = if acceleration != 0.0 {
-            (ctx.ship.fuel_level - ctx.ship.fuel_burn_rate).max(0.0)
+            let throttle = acceleration.abs() / SHIP_THRUST_ACCELERATION;
+            (ctx.ship.fuel_level - ctx.ship.fuel_burn_rate * throttle * dt).max(0.0)
         } else {
             ctx.ship.fuel_level
         };
@@ -106,15 +317,17 @@ pub extern "C" fn update_and_render(input: &GameInput, ctx: &mut GameState) {
         }
     }
 
+    let turn_left = button_value(&input.turn_left);
+    let turn_right = button_value(&input.turn_right);
     let mut rotation_speed: f32 = 0.0;
-    if input.turn_left {
-        rotation_speed = 0.1;
+    if turn_left > 0.0 {
+        rotation_speed = SHIP_TURN_RATE * turn_left;
     }
-    if input.turn_right {
-        rotation_speed = -0.1;
+    if turn_right > 0.0 {
+        rotation_speed = -SHIP_TURN_RATE * turn_right;
     }
 
-    ctx.player.rot += rotation_speed;
+    ctx.player.rot += rotation_speed * dt;
     let direction = vector_2f(ctx.player.rot.cos(), ctx.player.rot.sin());
     ctx.player.dd_pos = acceleration;
 
@@ -124,55 +337,329 @@ pub extern "C" fn update_and_render(input: &GameInput, ctx: &mut GameState) {
     };
     forces.clear();
 
-    let player_velocity = vector_2f_scale(direction, acceleration);
-    forces.push(player_velocity);
+    let thrust_accel = vector_2f_scale(direction, acceleration);
+    forces.push(thrust_accel);
 
     // Accumulate gravity from all space bodies
-    let mut planet_g_accum = vector_2f(0.0, 0.0);
+    let planets = ctx.planets.as_deref().unwrap_or(&[]);
+    let gravity = physics_gravity(ctx.gravity_g, &ctx.sun, planets, ctx.player.pos, ctx.player.d_pos, world_size, Some(&mut forces));
+    let took_off = ctx.player.landed && !gravity.landed;
+    let carried = carrier.filter(|_| ctx.player.landed);
+    ctx.player.landed = gravity.landed;
 
-    // then the planets
-    if let Some(planets) = &ctx.planets {
-        let mut player = &mut ctx.player;
-
-        for p in planets.iter() {
-            let p_dir = vector_2f_sub(p.pos, player.pos);
-            let p_dist = vector_2f_length(p_dir);
-            if p_dist < p.surface_radius {
-                player.landed = true;
-                let planet_surface_friction = vector_2f_scale(player.d_pos, -0.1);
-                forces.push(planet_surface_friction);
-                planet_g_accum = vector_2f_add(planet_g_accum, planet_surface_friction);
+    ctx.debug_player_forces = Some(forces);
+
+    // Leapfrog, half the acceleration before moving and half after, see physics.rs
+    let player_accel = vector_2f_add(thrust_accel, gravity.accel);
+    ctx.player.d_pos = physics_apply_acceleration(ctx.player.d_pos, player_accel, 0.5 * dt);
+
+    // a carried ship only slides over the surface on top of the planet's own move,
+    // swept from where it was before the planet took it along
+    let (start, delta) = match carried {
+        Some((i, _)) => {
+            let slide = vector_2f_sub(ctx.player.d_pos, planets[i].d_pos);
+            (vector_2f_sub(ctx.player.pos, carry), vector_2f_add(carry, vector_2f_scale(slide, dt)))
+        },
+        None => (ctx.player.pos, vector_2f_scale(ctx.player.d_pos, dt)),
+    };
+    let mut unwrapped_pos = vector_2f_add(start, delta);
+    let mut crash_damage = 0.0;
+    if let Some((i, hit)) = physics_sweep_planets(planets, start, delta, world_size) {
+        let planet = &planets[i];
+        let outcome = physics_landing_outcome(planet, hit.point, hit.normal, ctx.player.d_pos, ctx.player.rot, world_size);
+        match outcome {
+            LandingOutcome::Crashed => {
+                // take damage by impact speed and bounce back off the surface
+                let impact_d_pos = vector_2f_sub(ctx.player.d_pos, planet.d_pos);
+                crash_damage = vector_2f_length(impact_d_pos) * LANDING_CRASH_DAMAGE_PER_SPEED;
+                unwrapped_pos = vector_2f_add(hit.point, vector_2f_scale(hit.normal, PHYSICS_CONTACT_SKIN));
+                let bounce = vector_2f_scale(vector_2f_reflect(impact_d_pos, hit.normal), LANDING_CRASH_RESTITUTION);
+                ctx.player.d_pos = vector_2f_add(planet.d_pos, bounce);
             }
-            else if p_dist.between(p.radius, p.g_radius) {
-                player.landed = false;
-                let planet_g_force = p.g_force * input.frame_dt_sec;
-                let planet_g_velocity = vector_2f_scale(vector_2f_normalize(p_dir), planet_g_force);
-                forces.push(planet_g_velocity);
-                planet_g_accum = vector_2f_add(planet_g_accum, planet_g_velocity);
+            LandingOutcome::Landed => {
+                // snap onto the pad pointing straight up, on the copy of the planet that was hit
+                let pad_dir = vector_2f_normalize_or(planet.lz_rel_pos, hit.normal);
+                let planet_pos = world_nearest(ctx.player.pos, planet.pos, world_size);
+                unwrapped_pos = vector_2f_add(planet_pos, vector_2f_scale(pad_dir, planet.surface_radius - PHYSICS_CONTACT_SKIN));
+                ctx.player.rot += vector_2f_signed_angle(direction, pad_dir);
+                ctx.player.d_pos = planet.d_pos;
+                ctx.player.landed = true;
+                ctx.last_landing_planet = Some(i);
             }
+            _ => {
+                unwrapped_pos = vector_2f_sub(hit.point, vector_2f_scale(hit.normal, PHYSICS_CONTACT_SKIN));
+                ctx.player.d_pos = planet.d_pos;
+                ctx.player.landed = true;
+            }
+        }
+        ctx.landing_outcome = outcome;
+        ctx.landing_outcome_sec = 0.0;
+    }
+    else {
+        let gravity = physics_gravity(ctx.gravity_g, &ctx.sun, planets, unwrapped_pos, ctx.player.d_pos, world_size, None);
+        let player_accel = vector_2f_add(thrust_accel, gravity.accel);
+        ctx.player.d_pos = physics_apply_acceleration(ctx.player.d_pos, player_accel, 0.5 * dt);
+        if took_off && ctx.landing_outcome != LandingOutcome::Crashed {
+            ctx.landing_outcome = LandingOutcome::None;
         }
     }
+    ctx.landing_outcome_sec += dt;
 
-    // Sun gravity only felt when in space
-    if ctx.player.landed == false {
-        let sun_dir = vector_2f_sub(ctx.sun.pos, ctx.player.pos);
-        let sun_g_velocity = vector_2f_scale(sun_dir, ctx.sun.g_force * input.frame_dt_sec);
-        planet_g_accum = vector_2f_add(planet_g_accum, sun_g_velocity);
+    // sitting on a landing zone refuels the ship
+    if ctx.player.landed && ctx.landing_outcome == LandingOutcome::Landed {
+        ctx.ship.fuel_level = (ctx.ship.fuel_level + LANDING_REFUEL_RATE * dt).min(1.0);
     }
 
-    ctx.debug_player_forces = Some(forces);
+    ctx.player.pos = world_wrap(unwrapped_pos, world_size);
+
+    // keep the previous position on the same side of the wrap so interpolation doesn't sweep the world
+    let wrap_offset = vector_2f_sub(ctx.player.pos, unwrapped_pos);
+    ctx.player.prev_pos = vector_2f_add(ctx.player.prev_pos, wrap_offset);
+
+    if crash_damage > 0.0 {
+        ship_damage(ctx, crash_damage);
+    }
+    if ctx.ship.destroyed {
+        return;
+    }
+
+    item_pickup(ctx);
+    ship_update_stranded(ctx, dt);
+}
 
-    // Apply the final player velocity after all forces have been calculated
-    let player_velocity = vector_2f_add(player_velocity, planet_g_accum);
-    // cap velocity
-    let max_velocity:f32 = 20.0;
-    let new_d_pos = vector_2f_add(ctx.player.d_pos, player_velocity);
-    let new_d_pos_len = vector_2f_length(new_d_pos); 
-    if new_d_pos_len <= max_velocity {
-        ctx.player.d_pos = new_d_pos;
+// Everything in one copy of the world
+fn game_render_world(ctx: &GameState, render_buffer: &mut RenderBuffer, player_pos: Vector2f, player_rot: f32) {
+    let alpha = ctx.sim_alpha;
+
+    // Render space background
+    if let Some(stars) = &ctx.space_stars {
+        let points = stars.iter().map(|s| s.pos).collect();
+        render_push_point_cloud(render_buffer, points, vector_4f(1.0, 1.0, 1.0, 1.0), 1.0);
+    }
+
+    // Render planets
+    if let Some(planets) = &ctx.planets {
+        for planet in planets {
+            let planet_pos = vector_2f_lerp(planet.prev_pos, planet.pos, alpha);
+            render_push_circle(render_buffer, planet_pos, planet.radius, planet.color, true);
+            render_push_circle(render_buffer, planet_pos, planet.g_radius, planet.color, false);
+
+            // Render planet stuff
+            for item in planet.items.iter() {
+                let item_pos = vector_2f_add(planet_pos, item.pos);
+                let item_dim = vector_2f(6.0, 6.0);
+                render_push_quad(render_buffer, item_pos, item_dim, item_color(item.itype));
+            }
+
+            // Render Landing Zone
+            let lz_pos = vector_2f_add(planet_pos, planet.lz_rel_pos);
+            let lz_dim = vector_2f(10.0, 10.0);
+            render_push_quad(render_buffer, lz_pos, lz_dim, planet.lz_color);
+        }
+    }
+
+    // Render projectiles
+    for p in ctx.projectiles.iter().filter(|p| p.alive) {
+        let pos = vector_2f_lerp(p.prev_pos, p.pos, alpha);
+        render_push_quad(render_buffer, pos, vector_2f(2.0, 2.0), vector_4f(1.0, 0.6, 0.2, 1.0));
+    }
+
+    // Render Player, or what's left of it
+    if ctx.ship.destroyed {
+        let t = ctx.respawn_sec / EXPLOSION_SEC;
+        if t < 1.0 {
+            let radius = 10.0 + EXPLOSION_RADIUS * t;
+            render_push_circle(render_buffer, player_pos, radius, vector_4f(1.0, 0.5, 0.1, 1.0 - t), true);
+            render_push_circle(render_buffer, player_pos, radius * 1.3, vector_4f(1.0, 0.9, 0.3, 1.0 - t), false);
+        }
+    } else {
+        render_push_transform(render_buffer, player_pos, player_rot, vector_2f(10.0, 10.0));
+        render_push_triangles(render_buffer, vec![
+            vector_2f( 1.0,  0.0),
+            vector_2f(-1.0,  1.0),
+            vector_2f(-0.5,  0.0),
+
+            vector_2f( 1.0,  0.0),
+            vector_2f(-0.5,  0.0),
+            vector_2f(-1.0, -1.0),
+        ], vector_4f(1.0, 1.0, 1.0, 1.0));
+        render_pop_transform(render_buffer);
+    }
+
+    // Render Navigation Path
+    if let Some(path) = &ctx.nav_path {
+        let points = path.points.iter().map(|p| RenderVertex {
+            p: p.p,
+            c: vector_4f(p.c.x, p.c.y, p.c.z, 1.0),
+        }).collect();
+        render_push_line_strip(render_buffer, points, 2.0);
+    }
+
+    // Render Forces
+    if let Some(forces) = &ctx.debug_player_forces {
+        for f in forces {
+            let force = vector_2f_add(player_pos, vector_2f_scale(*f, 0.1));
+            render_push_line(render_buffer, player_pos, force, vector_4f(1.0, 1.0, 0.0, 1.0), 2.0);
+        }
     }
-    ctx.player.pos = vector_2f_add(ctx.player.pos, ctx.player.d_pos);
-    ctx.player.pos.x = ctx.player.pos.x.wrap(-world_width as f32 / 2.0, world_width as f32 / 2.0);
-    ctx.player.pos.y = ctx.player.pos.y.wrap(-world_height as f32 / 2.0, world_height as f32 / 2.0);
 
+    // Render title text
+    let text_color = vector_4f(1.0, 1.0, 0.0, 1.0 - ctx.title_fade);
+    game_render_text(render_buffer, GAME_TITLE, vector_2f(0.0, 0.0), vector_2f(40.0, 40.0), text_color);
+}
+
+pub fn game_render(input: &GameInput, ctx: &GameState, render_buffer: &mut RenderBuffer) {
+    render_buffer_reset(render_buffer);
+
+    // Blend between the last two sim steps so motion is smooth at any render rate
+    let alpha = ctx.sim_alpha;
+    let player_pos = ship_drawn_pos(ctx);
+    let player_rot = ctx.player.prev_rot + (ctx.player.rot - ctx.player.prev_rot) * alpha;
+    let view = camera_view(&ctx.camera);
+    render_set_camera(render_buffer, view);
+
+    render_set_layer(render_buffer, RenderLayer::World);
+    render_push_clear(render_buffer, vector_4f(0.0, 0.05, 0.11, 1.0));
+
+    // The world is drawn again shifted by a whole world wherever the view reaches past an edge
+    let view_radius = 0.5 * vector_2f_length(vector_2f(input.screen_width as f32, input.screen_height as f32)) / view.zoom.max(0.01);
+    for offset in world_ghost_offsets(view.pos, view_radius, ctx.world_size) {
+        render_push_transform(render_buffer, offset, 0.0, vector_2f(1.0, 1.0));
+        game_render_world(ctx, render_buffer, player_pos, player_rot);
+        render_pop_transform(render_buffer);
+    }
+
+    //
+    // Render HUD
+    //
+    render_set_layer(render_buffer, RenderLayer::Hud);
+
+    // Render fuel bar
+    let screen_half_width = (input.screen_width / 2) as f32;
+    let screen_half_height = (input.screen_height / 2) as f32;
+    let rect_padding = 10.0;
+    let rect_width = screen_half_width - (2.0 * rect_padding);
+    let rect_height = 10.0;
+    let rect_center = vector_2f(
+        0.0, -(screen_half_height - (2.0 * rect_padding) - rect_height/2.0)
+    );
+    let fuel_width = rect_width * ctx.ship.fuel_level;
+    render_push_quad(render_buffer, rect_center, vector_2f(fuel_width, rect_height), vector_4f(0.0, 0.0, 1.0, 1.0));
+
+    // Render a thinner hull bar above it
+    let hull_center = vector_2f(rect_center.x, rect_center.y + 2.0 * rect_height);
+    let hull_width = rect_width * ctx.ship.hull;
+    render_push_quad(render_buffer, hull_center, vector_2f(hull_width, rect_height / 2.0), vector_4f(0.8, 0.8, 0.8, 1.0));
+
+    // Render the last landing outcome fading out next to the bars
+    let outcome_color = match ctx.landing_outcome {
+        LandingOutcome::Landed => Some(vector_3f(0.2, 1.0, 0.3)),
+        LandingOutcome::TouchedDown => Some(vector_3f(1.0, 0.9, 0.2)),
+        LandingOutcome::Crashed => Some(vector_3f(1.0, 0.15, 0.15)),
+        LandingOutcome::None => None,
+    };
+    if let Some(c) = outcome_color {
+        let fade = 1.0 - (ctx.landing_outcome_sec / LANDING_OUTCOME_FADE_SEC).min(1.0);
+        if fade > 0.0 {
+            let outcome_pos = vector_2f(-rect_width - 3.0 * rect_height, rect_center.y + rect_height);
+            let outcome_dim = vector_2f(2.0 * rect_height, 2.0 * rect_height);
+            render_push_quad(render_buffer, outcome_pos, outcome_dim, vector_4f_from_3f(c, fade));
+        }
+    }
+
+    // Render inventory slots above the left end of the hull bar
+    let slot_dim = vector_2f(rect_height / 2.0, rect_height / 2.0);
+    for slot in 0..ctx.ship.inventory_capacity as usize {
+        let slot_pos = vector_2f(
+            -rect_width + rect_height / 2.0 + slot as f32 * 1.5 * rect_height,
+            rect_center.y + 4.0 * rect_height,
+        );
+        let slot_color = match ctx.ship.inventory.get(slot) {
+            Some(itype) => item_color(*itype),
+            None => vector_4f(0.5, 0.5, 0.5, 0.3),
+        };
+        render_push_quad(render_buffer, slot_pos, slot_dim, slot_color);
+    }
+
+    // Render spare ships at the right end of the same row
+    for life in 0..ctx.lives {
+        let life_pos = vector_2f(
+            rect_width - rect_height / 2.0 - life as f32 * 1.5 * rect_height,
+            rect_center.y + 4.0 * rect_height,
+        );
+        render_push_transform(render_buffer, life_pos, std::f32::consts::FRAC_PI_2, slot_dim);
+        render_push_triangles(render_buffer, vec![
+            vector_2f( 1.0,  0.0),
+            vector_2f(-1.0,  1.0),
+            vector_2f(-1.0, -1.0),
+        ], vector_4f(1.0, 1.0, 1.0, 1.0));
+        render_pop_transform(render_buffer);
+    }
+
+    // Render score in the top left corner once the game is going
+    let text_half_dim = screen_half_width / 20.0;
+    let score_dim = vector_2f(0.35 * text_half_dim, 0.35 * text_half_dim);
+    if ctx.mode != GameMode::Title {
+        let score = format!("SCORE {}", ctx.score);
+        let score_pos = vector_2f(
+            -screen_half_width + rect_padding + score.len() as f32 * score_dim.x,
+            screen_half_height - rect_padding - score_dim.y,
+        );
+        game_render_text(render_buffer, &score, score_pos, score_dim, vector_4f(1.0, 1.0, 1.0, 1.0));
+    }
+
+    //
+    // Render mode overlays
+    //
+    let headline_dim = vector_2f(text_half_dim, text_half_dim);
+    let hint_dim = vector_2f(0.4 * text_half_dim, 0.4 * text_half_dim);
+    let headline_pos = vector_2f(0.0, 0.25 * screen_half_height);
+    let hint_pos = vector_2f(0.0, -0.25 * screen_half_height);
+    let headline_color = vector_4f(1.0, 1.0, 0.0, 1.0);
+    let hint_color = vector_4f(1.0, 1.0, 1.0, 1.0);
+    let screen_half_dim = vector_2f(screen_half_width, screen_half_height);
+    let dim_color = vector_4f(0.0, 0.0, 0.0, 0.5);
+    // hints blink so they read as a prompt
+    let hint_visible = ctx.mode_sec.fract() < 0.5;
+
+    match ctx.mode {
+        GameMode::Title => {
+            if hint_visible {
+                game_render_text(render_buffer, "PRESS SPACE TO START", hint_pos, hint_dim, hint_color);
+            }
+        },
+        GameMode::Playing => {},
+        GameMode::Paused => {
+            render_push_quad(render_buffer, vector_2f_zero(), screen_half_dim, dim_color);
+            game_render_text(render_buffer, "PAUSED", headline_pos, headline_dim, headline_color);
+            game_render_text(render_buffer, "PRESS P TO RESUME", hint_pos, hint_dim, hint_color);
+        },
+        GameMode::LevelComplete => {
+            render_push_quad(render_buffer, vector_2f_zero(), screen_half_dim, dim_color);
+            game_render_text(render_buffer, "SYSTEM CLEARED", headline_pos, headline_dim, headline_color);
+            if ctx.mode_sec >= GAME_MODE_INPUT_DELAY_SEC && hint_visible {
+                game_render_text(render_buffer, "PRESS SPACE FOR THE NEXT SYSTEM", hint_pos, hint_dim, hint_color);
+            }
+        },
+        GameMode::GameOver => {
+            render_push_quad(render_buffer, vector_2f_zero(), screen_half_dim, dim_color);
+            game_render_text(render_buffer, "GAME OVER", headline_pos, headline_dim, vector_4f(1.0, 0.2, 0.2, 1.0));
+            if ctx.mode_sec >= GAME_MODE_INPUT_DELAY_SEC && hint_visible {
+                game_render_text(render_buffer, "PRESS SPACE TO PLAY AGAIN", hint_pos, hint_dim, hint_color);
+            }
+        },
+    }
+}
+
+// One line of glyph quads centered on pos, every character is 2 * char_half_dim.x wide
+// todo use actual font metrics to properly size and position sentences
+fn game_render_text(render_buffer: &mut RenderBuffer, text: &str, pos: Vector2f, char_half_dim: Vector2f, color: Vector4f) {
+    let char_count = text.chars().count() as f32;
+    let mut char_pos = vector_2f(pos.x - (char_count - 1.0) * char_half_dim.x, pos.y);
+    for c in text.chars() {
+        if c != ' ' {
+            render_push_textured_quad(render_buffer, char_pos, char_half_dim, RenderTexture::Glyph(c), color);
+        }
+        char_pos.x += char_half_dim.x * 2.0;
+    }
 }
@@ -2,12 +2,16 @@ pub trait WrapScalar<T> {
     fn wrap(&self, a: T, b: T) -> T;
 }
 
+// Into [a, b), keeping however far it went past the edge
 impl WrapScalar<f32> for f32 {
     #[inline]
     fn wrap(&self, a: f32, b: f32) -> f32 {
-        if *self < a { b }
-        else if *self > b { a }
-        else { *self }
+        if b <= a {
+            return a;
+        }
+        let wrapped = a + (*self - a).rem_euclid(b - a);
+        // rem_euclid can round up to exactly b - a for tiny negative inputs
+        if wrapped >= b { a } else { wrapped }
     }
 }
 
@@ -22,7 +26,14 @@ impl BetweenScalar<f32> for f32 {
     }
 }
 
-#[derive(Default, Copy, Clone, Debug)]
+// Vectors shorter than this have no usable direction
+pub const VECTOR_NORMALIZE_EPSILON: f32 = 1.0e-6;
+
+//
+// Vector2f
+//
+
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
 #[repr(C)]
 pub struct Vector2f {
     pub x: f32,
@@ -50,62 +61,202 @@ pub fn vector_2f_unity() -> Vector2f {
 }
 
 #[inline]
-pub fn vector_2f_normalize(v: Vector2f) -> Vector2f {
-    let len = vector_2f_length(v);
-    let result = Vector2f {
-        x: v.x/len,
-        y: v.y/len,
-    };
-    result
+pub fn vector_2f_add(a: Vector2f, b: Vector2f) -> Vector2f {
+    Vector2f {
+        x: a.x + b.x,
+        y: a.y + b.y,
+    }
 }
 
 #[inline]
-pub fn vector_2f_length(v: Vector2f) -> f32 {
-    (v.x*v.x + v.y*v.y).sqrt()
+pub fn vector_2f_sub(a: Vector2f, b: Vector2f) -> Vector2f {
+    Vector2f {
+        x: a.x - b.x,
+        y: a.y - b.y,
+    }
 }
 
+#[inline]
+pub fn vector_2f_scale(v: Vector2f, s: f32) -> Vector2f {
+    Vector2f {
+        x: v.x*s,
+        y: v.y*s,
+    }
+}
 
 #[inline]
-pub fn vector_2f_length_squard(v: Vector2f) -> f32 {
-    v.x*v.x + v.y*v.y
+pub fn vector_2f_neg(v: Vector2f) -> Vector2f {
+    Vector2f {
+        x: -v.x,
+        y: -v.y,
+    }
 }
 
+// Component-wise multiply
 #[inline]
-pub fn vector_2f_scale(v: Vector2f, s: f32) -> Vector2f {
-    let result = Vector2f { 
-        x: v.x*s,
-        y: v.y*s
-    };
-    result
+pub fn vector_2f_hadamard(a: Vector2f, b: Vector2f) -> Vector2f {
+    Vector2f {
+        x: a.x*b.x,
+        y: a.y*b.y,
+    }
 }
 
+// Component-wise divide
 #[inline]
-pub fn vector_2f_add(a: Vector2f, b: Vector2f) -> Vector2f {
+pub fn vector_2f_div(a: Vector2f, b: Vector2f) -> Vector2f {
     Vector2f {
-        x: a.x + b.x, 
-        y: a.y + b.y, 
+        x: a.x/b.x,
+        y: a.y/b.y,
     }
 }
 
 #[inline]
-pub fn vector_2f_sub(a: Vector2f, b: Vector2f) -> Vector2f {
+pub fn vector_2f_min(a: Vector2f, b: Vector2f) -> Vector2f {
+    Vector2f {
+        x: a.x.min(b.x),
+        y: a.y.min(b.y),
+    }
+}
+
+#[inline]
+pub fn vector_2f_max(a: Vector2f, b: Vector2f) -> Vector2f {
+    Vector2f {
+        x: a.x.max(b.x),
+        y: a.y.max(b.y),
+    }
+}
+
+// Component-wise, lo wins if lo > hi
+#[inline]
+pub fn vector_2f_clamp(v: Vector2f, lo: Vector2f, hi: Vector2f) -> Vector2f {
     Vector2f {
-        x: a.x - b.x, 
-        y: a.y - b.y, 
+        x: v.x.max(lo.x).min(hi.x),
+        y: v.y.max(lo.y).min(hi.y),
+    }
+}
+
+#[inline]
+pub fn vector_2f_lerp(a: Vector2f, b: Vector2f, t: f32) -> Vector2f {
+    Vector2f {
+        x: a.x + (b.x - a.x)*t,
+        y: a.y + (b.y - a.y)*t,
+    }
+}
+
+#[inline]
+pub fn vector_2f_dot(a: Vector2f, b: Vector2f) -> f32 {
+    a.x*b.x + a.y*b.y
+}
+
+#[inline]
+pub fn vector_2f_length_squared(v: Vector2f) -> f32 {
+    vector_2f_dot(v, v)
+}
+
+#[inline]
+pub fn vector_2f_length(v: Vector2f) -> f32 {
+    vector_2f_length_squared(v).sqrt()
+}
+
+#[inline]
+pub fn vector_2f_distance(a: Vector2f, b: Vector2f) -> f32 {
+    vector_2f_length(vector_2f_sub(a, b))
+}
+
+#[inline]
+pub fn vector_2f_distance_squared(a: Vector2f, b: Vector2f) -> f32 {
+    vector_2f_length_squared(vector_2f_sub(a, b))
+}
+
+// Returns fallback when v is too short to have a direction
+#[inline]
+pub fn vector_2f_normalize_or(v: Vector2f, fallback: Vector2f) -> Vector2f {
+    let len = vector_2f_length(v);
+    if len > VECTOR_NORMALIZE_EPSILON {
+        vector_2f_scale(v, 1.0/len)
+    } else {
+        fallback
+    }
+}
+
+// Zero vector for a zero length v instead of NaNs
+#[inline]
+pub fn vector_2f_normalize(v: Vector2f) -> Vector2f {
+    vector_2f_normalize_or(v, vector_2f_zero())
+}
+
+// Reflects v off a surface with unit normal n
+#[inline]
+pub fn vector_2f_reflect(v: Vector2f, n: Vector2f) -> Vector2f {
+    vector_2f_sub(v, vector_2f_scale(n, 2.0*vector_2f_dot(v, n)))
+}
+
+// Projects a onto b, zero if b is zero
+#[inline]
+pub fn vector_2f_project(a: Vector2f, b: Vector2f) -> Vector2f {
+    let b_len_sq = vector_2f_length_squared(b);
+    if b_len_sq > VECTOR_NORMALIZE_EPSILON*VECTOR_NORMALIZE_EPSILON {
+        vector_2f_scale(b, vector_2f_dot(a, b)/b_len_sq)
+    } else {
+        vector_2f_zero()
     }
 }
 
+// Unsigned angle in radians [0, pi], zero if either vector is zero
+#[inline]
+pub fn vector_2f_angle_between(a: Vector2f, b: Vector2f) -> f32 {
+    let len = vector_2f_length(a)*vector_2f_length(b);
+    if len > 0.0 {
+        (vector_2f_dot(a, b)/len).clamp(-1.0, 1.0).acos()
+    } else {
+        0.0
+    }
+}
+
+// Perp-dot product, the z of the 3D cross product. Positive when b is counter clockwise from a
+#[inline]
+pub fn vector_2f_cross(a: Vector2f, b: Vector2f) -> f32 {
+    a.x*b.y - a.y*b.x
+}
+
+// Rotated 90 degrees counter clockwise
+#[inline]
+pub fn vector_2f_perp(v: Vector2f) -> Vector2f {
+    Vector2f { x: -v.y, y: v.x }
+}
+
 #[inline]
-pub fn vector_2f_dot() {
-    todo!()
+pub fn vector_2f_from_angle(angle: f32) -> Vector2f {
+    Vector2f { x: angle.cos(), y: angle.sin() }
 }
 
+// Angle from the x axis in radians [-pi, pi]
 #[inline]
-pub fn vector_2f_cross() {
-    todo!()
+pub fn vector_2f_angle(v: Vector2f) -> f32 {
+    v.y.atan2(v.x)
 }
 
-#[derive(Default, Copy, Clone, Debug)]
+// Counter clockwise angle from a to b in radians [-pi, pi]
+#[inline]
+pub fn vector_2f_signed_angle(a: Vector2f, b: Vector2f) -> f32 {
+    vector_2f_cross(a, b).atan2(vector_2f_dot(a, b))
+}
+
+// Counter clockwise by angle radians
+#[inline]
+pub fn vector_2f_rotate(v: Vector2f, angle: f32) -> Vector2f {
+    let (sin, cos) = angle.sin_cos();
+    Vector2f {
+        x: v.x*cos - v.y*sin,
+        y: v.x*sin + v.y*cos,
+    }
+}
+
+//
+// Vector3f
+//
+
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
 #[repr(C)]
 pub struct Vector3f {
     pub x: f32,
@@ -113,11 +264,214 @@ pub struct Vector3f {
     pub z: f32,
 }
 
+#[inline]
 pub fn vector_3f(x: f32, y: f32, z: f32) -> Vector3f {
     Vector3f { x, y, z }
 }
 
-#[derive(Default, Copy, Clone, Debug)]
+#[inline]
+pub fn vector_3f_zero() -> Vector3f {
+    Vector3f { x: 0.0, y: 0.0, z: 0.0 }
+}
+
+#[inline]
+pub fn vector_3f_add(a: Vector3f, b: Vector3f) -> Vector3f {
+    Vector3f {
+        x: a.x + b.x,
+        y: a.y + b.y,
+        z: a.z + b.z,
+    }
+}
+
+#[inline]
+pub fn vector_3f_sub(a: Vector3f, b: Vector3f) -> Vector3f {
+    Vector3f {
+        x: a.x - b.x,
+        y: a.y - b.y,
+        z: a.z - b.z,
+    }
+}
+
+#[inline]
+pub fn vector_3f_scale(v: Vector3f, s: f32) -> Vector3f {
+    Vector3f {
+        x: v.x*s,
+        y: v.y*s,
+        z: v.z*s,
+    }
+}
+
+#[inline]
+pub fn vector_3f_neg(v: Vector3f) -> Vector3f {
+    Vector3f {
+        x: -v.x,
+        y: -v.y,
+        z: -v.z,
+    }
+}
+
+// Component-wise multiply
+#[inline]
+pub fn vector_3f_hadamard(a: Vector3f, b: Vector3f) -> Vector3f {
+    Vector3f {
+        x: a.x*b.x,
+        y: a.y*b.y,
+        z: a.z*b.z,
+    }
+}
+
+// Component-wise divide
+#[inline]
+pub fn vector_3f_div(a: Vector3f, b: Vector3f) -> Vector3f {
+    Vector3f {
+        x: a.x/b.x,
+        y: a.y/b.y,
+        z: a.z/b.z,
+    }
+}
+
+#[inline]
+pub fn vector_3f_min(a: Vector3f, b: Vector3f) -> Vector3f {
+    Vector3f {
+        x: a.x.min(b.x),
+        y: a.y.min(b.y),
+        z: a.z.min(b.z),
+    }
+}
+
+#[inline]
+pub fn vector_3f_max(a: Vector3f, b: Vector3f) -> Vector3f {
+    Vector3f {
+        x: a.x.max(b.x),
+        y: a.y.max(b.y),
+        z: a.z.max(b.z),
+    }
+}
+
+// Component-wise, lo wins if lo > hi
+#[inline]
+pub fn vector_3f_clamp(v: Vector3f, lo: Vector3f, hi: Vector3f) -> Vector3f {
+    Vector3f {
+        x: v.x.max(lo.x).min(hi.x),
+        y: v.y.max(lo.y).min(hi.y),
+        z: v.z.max(lo.z).min(hi.z),
+    }
+}
+
+#[inline]
+pub fn vector_3f_lerp(a: Vector3f, b: Vector3f, t: f32) -> Vector3f {
+    Vector3f {
+        x: a.x + (b.x - a.x)*t,
+        y: a.y + (b.y - a.y)*t,
+        z: a.z + (b.z - a.z)*t,
+    }
+}
+
+#[inline]
+pub fn vector_3f_dot(a: Vector3f, b: Vector3f) -> f32 {
+    a.x*b.x + a.y*b.y + a.z*b.z
+}
+
+#[inline]
+pub fn vector_3f_length_squared(v: Vector3f) -> f32 {
+    vector_3f_dot(v, v)
+}
+
+#[inline]
+pub fn vector_3f_length(v: Vector3f) -> f32 {
+    vector_3f_length_squared(v).sqrt()
+}
+
+#[inline]
+pub fn vector_3f_distance(a: Vector3f, b: Vector3f) -> f32 {
+    vector_3f_length(vector_3f_sub(a, b))
+}
+
+#[inline]
+pub fn vector_3f_distance_squared(a: Vector3f, b: Vector3f) -> f32 {
+    vector_3f_length_squared(vector_3f_sub(a, b))
+}
+
+// Returns fallback when v is too short to have a direction
+#[inline]
+pub fn vector_3f_normalize_or(v: Vector3f, fallback: Vector3f) -> Vector3f {
+    let len = vector_3f_length(v);
+    if len > VECTOR_NORMALIZE_EPSILON {
+        vector_3f_scale(v, 1.0/len)
+    } else {
+        fallback
+    }
+}
+
+// Zero vector for a zero length v instead of NaNs
+#[inline]
+pub fn vector_3f_normalize(v: Vector3f) -> Vector3f {
+    vector_3f_normalize_or(v, vector_3f_zero())
+}
+
+// Reflects v off a surface with unit normal n
+#[inline]
+pub fn vector_3f_reflect(v: Vector3f, n: Vector3f) -> Vector3f {
+    vector_3f_sub(v, vector_3f_scale(n, 2.0*vector_3f_dot(v, n)))
+}
+
+// Projects a onto b, zero if b is zero
+#[inline]
+pub fn vector_3f_project(a: Vector3f, b: Vector3f) -> Vector3f {
+    let b_len_sq = vector_3f_length_squared(b);
+    if b_len_sq > VECTOR_NORMALIZE_EPSILON*VECTOR_NORMALIZE_EPSILON {
+        vector_3f_scale(b, vector_3f_dot(a, b)/b_len_sq)
+    } else {
+        vector_3f_zero()
+    }
+}
+
+// Unsigned angle in radians [0, pi], zero if either vector is zero
+#[inline]
+pub fn vector_3f_angle_between(a: Vector3f, b: Vector3f) -> f32 {
+    let len = vector_3f_length(a)*vector_3f_length(b);
+    if len > 0.0 {
+        (vector_3f_dot(a, b)/len).clamp(-1.0, 1.0).acos()
+    } else {
+        0.0
+    }
+}
+
+#[inline]
+pub fn vector_3f_cross(a: Vector3f, b: Vector3f) -> Vector3f {
+    Vector3f {
+        x: a.y*b.z - a.z*b.y,
+        y: a.z*b.x - a.x*b.z,
+        z: a.x*b.y - a.y*b.x,
+    }
+}
+
+// Rotates around axis by angle radians, counter clockwise looking down the axis (Rodrigues)
+#[inline]
+pub fn vector_3f_rotate(v: Vector3f, axis: Vector3f, angle: f32) -> Vector3f {
+    let k = vector_3f_normalize(axis);
+    let (sin, cos) = angle.sin_cos();
+    let a = vector_3f_scale(v, cos);
+    let b = vector_3f_scale(vector_3f_cross(k, v), sin);
+    let c = vector_3f_scale(k, vector_3f_dot(k, v)*(1.0 - cos));
+    vector_3f_add(vector_3f_add(a, b), c)
+}
+
+#[inline]
+pub fn vector_3f_from_2f(v: Vector2f, z: f32) -> Vector3f {
+    Vector3f { x: v.x, y: v.y, z }
+}
+
+#[inline]
+pub fn vector_3f_xy(v: Vector3f) -> Vector2f {
+    Vector2f { x: v.x, y: v.y }
+}
+
+//
+// Vector4f
+//
+
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
 #[repr(C)]
 pub struct Vector4f {
     pub x: f32,
@@ -126,11 +480,504 @@ pub struct Vector4f {
     pub w: f32,
 }
 
+#[inline]
 pub fn vector_4f(x: f32, y: f32, z: f32, w: f32) -> Vector4f {
     Vector4f { x, y, z, w }
 }
 
+#[inline]
+pub fn vector_4f_zero() -> Vector4f {
+    Vector4f { x: 0.0, y: 0.0, z: 0.0, w: 0.0 }
+}
+
+#[inline]
+pub fn vector_4f_add(a: Vector4f, b: Vector4f) -> Vector4f {
+    Vector4f {
+        x: a.x + b.x,
+        y: a.y + b.y,
+        z: a.z + b.z,
+        w: a.w + b.w,
+    }
+}
+
+#[inline]
+pub fn vector_4f_sub(a: Vector4f, b: Vector4f) -> Vector4f {
+    Vector4f {
+        x: a.x - b.x,
+        y: a.y - b.y,
+        z: a.z - b.z,
+        w: a.w - b.w,
+    }
+}
+
+#[inline]
+pub fn vector_4f_scale(v: Vector4f, s: f32) -> Vector4f {
+    Vector4f {
+        x: v.x*s,
+        y: v.y*s,
+        z: v.z*s,
+        w: v.w*s,
+    }
+}
+
+#[inline]
+pub fn vector_4f_neg(v: Vector4f) -> Vector4f {
+    Vector4f {
+        x: -v.x,
+        y: -v.y,
+        z: -v.z,
+        w: -v.w,
+    }
+}
+
+// Component-wise multiply
+#[inline]
+pub fn vector_4f_hadamard(a: Vector4f, b: Vector4f) -> Vector4f {
+    Vector4f {
+        x: a.x*b.x,
+        y: a.y*b.y,
+        z: a.z*b.z,
+        w: a.w*b.w,
+    }
+}
+
+// Component-wise divide
+#[inline]
+pub fn vector_4f_div(a: Vector4f, b: Vector4f) -> Vector4f {
+    Vector4f {
+        x: a.x/b.x,
+        y: a.y/b.y,
+        z: a.z/b.z,
+        w: a.w/b.w,
+    }
+}
+
+#[inline]
+pub fn vector_4f_min(a: Vector4f, b: Vector4f) -> Vector4f {
+    Vector4f {
+        x: a.x.min(b.x),
+        y: a.y.min(b.y),
+        z: a.z.min(b.z),
+        w: a.w.min(b.w),
+    }
+}
+
+#[inline]
+pub fn vector_4f_max(a: Vector4f, b: Vector4f) -> Vector4f {
+    Vector4f {
+        x: a.x.max(b.x),
+        y: a.y.max(b.y),
+        z: a.z.max(b.z),
+        w: a.w.max(b.w),
+    }
+}
+
+// Component-wise, lo wins if lo > hi
+#[inline]
+pub fn vector_4f_clamp(v: Vector4f, lo: Vector4f, hi: Vector4f) -> Vector4f {
+    Vector4f {
+        x: v.x.max(lo.x).min(hi.x),
+        y: v.y.max(lo.y).min(hi.y),
+        z: v.z.max(lo.z).min(hi.z),
+        w: v.w.max(lo.w).min(hi.w),
+    }
+}
+
+#[inline]
+pub fn vector_4f_lerp(a: Vector4f, b: Vector4f, t: f32) -> Vector4f {
+    Vector4f {
+        x: a.x + (b.x - a.x)*t,
+        y: a.y + (b.y - a.y)*t,
+        z: a.z + (b.z - a.z)*t,
+        w: a.w + (b.w - a.w)*t,
+    }
+}
+
+#[inline]
+pub fn vector_4f_dot(a: Vector4f, b: Vector4f) -> f32 {
+    a.x*b.x + a.y*b.y + a.z*b.z + a.w*b.w
+}
+
+#[inline]
+pub fn vector_4f_length_squared(v: Vector4f) -> f32 {
+    vector_4f_dot(v, v)
+}
+
+#[inline]
+pub fn vector_4f_length(v: Vector4f) -> f32 {
+    vector_4f_length_squared(v).sqrt()
+}
+
+#[inline]
+pub fn vector_4f_distance(a: Vector4f, b: Vector4f) -> f32 {
+    vector_4f_length(vector_4f_sub(a, b))
+}
+
+#[inline]
+pub fn vector_4f_distance_squared(a: Vector4f, b: Vector4f) -> f32 {
+    vector_4f_length_squared(vector_4f_sub(a, b))
+}
+
+// Returns fallback when v is too short to have a direction
+#[inline]
+pub fn vector_4f_normalize_or(v: Vector4f, fallback: Vector4f) -> Vector4f {
+    let len = vector_4f_length(v);
+    if len > VECTOR_NORMALIZE_EPSILON {
+        vector_4f_scale(v, 1.0/len)
+    } else {
+        fallback
+    }
+}
+
+// Zero vector for a zero length v instead of NaNs
+#[inline]
+pub fn vector_4f_normalize(v: Vector4f) -> Vector4f {
+    vector_4f_normalize_or(v, vector_4f_zero())
+}
+
+// Reflects v off a surface with unit normal n
+#[inline]
+pub fn vector_4f_reflect(v: Vector4f, n: Vector4f) -> Vector4f {
+    vector_4f_sub(v, vector_4f_scale(n, 2.0*vector_4f_dot(v, n)))
+}
+
+// Projects a onto b, zero if b is zero
+#[inline]
+pub fn vector_4f_project(a: Vector4f, b: Vector4f) -> Vector4f {
+    let b_len_sq = vector_4f_length_squared(b);
+    if b_len_sq > VECTOR_NORMALIZE_EPSILON*VECTOR_NORMALIZE_EPSILON {
+        vector_4f_scale(b, vector_4f_dot(a, b)/b_len_sq)
+    } else {
+        vector_4f_zero()
+    }
+}
 
+// Unsigned angle in radians [0, pi], zero if either vector is zero
+#[inline]
+pub fn vector_4f_angle_between(a: Vector4f, b: Vector4f) -> f32 {
+    let len = vector_4f_length(a)*vector_4f_length(b);
+    if len > 0.0 {
+        (vector_4f_dot(a, b)/len).clamp(-1.0, 1.0).acos()
+    } else {
+        0.0
+    }
+}
 
+#[inline]
+pub fn vector_4f_from_3f(v: Vector3f, w: f32) -> Vector4f {
+    Vector4f { x: v.x, y: v.y, z: v.z, w }
+}
 
+#[inline]
+pub fn vector_4f_xyz(v: Vector4f) -> Vector3f {
+    Vector3f { x: v.x, y: v.y, z: v.z }
+}
 
+//
+// Matrix3f
+//
+
+// 2D affine transform. Column major, e[column*3 + row], so a point is
+// transformed as M * [x, y, 1]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Matrix3f {
+    pub e: [f32; 9],
+}
+
+impl Default for Matrix3f {
+    fn default() -> Self {
+        matrix_3f_identity()
+    }
+}
+
+#[inline]
+pub fn matrix_3f_identity() -> Matrix3f {
+    Matrix3f { e: [
+        1.0, 0.0, 0.0,
+        0.0, 1.0, 0.0,
+        0.0, 0.0, 1.0,
+    ] }
+}
+
+#[inline]
+pub fn matrix_3f_translate(t: Vector2f) -> Matrix3f {
+    Matrix3f { e: [
+        1.0, 0.0, 0.0,
+        0.0, 1.0, 0.0,
+        t.x, t.y, 1.0,
+    ] }
+}
+
+#[inline]
+pub fn matrix_3f_scale(s: Vector2f) -> Matrix3f {
+    Matrix3f { e: [
+        s.x, 0.0, 0.0,
+        0.0, s.y, 0.0,
+        0.0, 0.0, 1.0,
+    ] }
+}
+
+// Counter clockwise by angle radians
+#[inline]
+pub fn matrix_3f_rotate_z(angle: f32) -> Matrix3f {
+    let (sin, cos) = angle.sin_cos();
+    Matrix3f { e: [
+        cos, sin, 0.0,
+       -sin, cos, 0.0,
+        0.0, 0.0, 1.0,
+    ] }
+}
+
+// translate * rotate * scale, built directly instead of with two multiplies
+#[inline]
+pub fn matrix_3f_trs(t: Vector2f, angle: f32, s: Vector2f) -> Matrix3f {
+    let (sin, cos) = angle.sin_cos();
+    Matrix3f { e: [
+        cos*s.x,  sin*s.x, 0.0,
+       -sin*s.y,  cos*s.y, 0.0,
+        t.x,      t.y,     1.0,
+    ] }
+}
+
+// Maps [left, right] x [bottom, top] to [-1, 1] x [-1, 1]
+pub fn matrix_3f_orthographic(left: f32, right: f32, bottom: f32, top: f32) -> Matrix3f {
+    Matrix3f { e: [
+        2.0/(right - left), 0.0, 0.0,
+        0.0, 2.0/(top - bottom), 0.0,
+        -(right + left)/(right - left), -(top + bottom)/(top - bottom), 1.0,
+    ] }
+}
+
+// a * b, so b is applied first
+pub fn matrix_3f_multiply(a: &Matrix3f, b: &Matrix3f) -> Matrix3f {
+    let mut e = [0.0; 9];
+    for col in 0..3 {
+        for row in 0..3 {
+            e[col*3 + row] = (0..3).map(|k| a.e[k*3 + row]*b.e[col*3 + k]).sum();
+        }
+    }
+    Matrix3f { e }
+}
+
+// None when the matrix can't be inverted, like a zero scale
+pub fn matrix_3f_inverse(m: &Matrix3f) -> Option<Matrix3f> {
+    let e = &m.e;
+    // cofactors, transposed into the adjugate
+    let c00 = e[4]*e[8] - e[7]*e[5];
+    let c01 = e[7]*e[2] - e[1]*e[8];
+    let c02 = e[1]*e[5] - e[4]*e[2];
+    let det = e[0]*c00 + e[3]*c01 + e[6]*c02;
+    // Only a zero determinant means there's no inverse, small ones come from valid small
+    // scales like the world projection
+    let inv_det = 1.0/det;
+    if !det.is_finite() || !inv_det.is_finite() {
+        return None;
+    }
+    Some(Matrix3f { e: [
+        c00*inv_det,
+        c01*inv_det,
+        c02*inv_det,
+        (e[6]*e[5] - e[3]*e[8])*inv_det,
+        (e[0]*e[8] - e[6]*e[2])*inv_det,
+        (e[3]*e[2] - e[0]*e[5])*inv_det,
+        (e[3]*e[7] - e[6]*e[4])*inv_det,
+        (e[6]*e[1] - e[0]*e[7])*inv_det,
+        (e[0]*e[4] - e[3]*e[1])*inv_det,
+    ] })
+}
+
+// Includes translation
+#[inline]
+pub fn matrix_3f_transform_point(m: &Matrix3f, p: Vector2f) -> Vector2f {
+    Vector2f {
+        x: m.e[0]*p.x + m.e[3]*p.y + m.e[6],
+        y: m.e[1]*p.x + m.e[4]*p.y + m.e[7],
+    }
+}
+
+// Ignores translation
+#[inline]
+pub fn matrix_3f_transform_vector(m: &Matrix3f, v: Vector2f) -> Vector2f {
+    Vector2f {
+        x: m.e[0]*v.x + m.e[3]*v.y,
+        y: m.e[1]*v.x + m.e[4]*v.y,
+    }
+}
+
+//
+// Matrix4f
+//
+
+// Column major, e[column*4 + row], the layout glLoadMatrixf/glMultMatrixf expect
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Matrix4f {
+    pub e: [f32; 16],
+}
+
+impl Default for Matrix4f {
+    fn default() -> Self {
+        matrix_4f_identity()
+    }
+}
+
+#[inline]
+pub fn matrix_4f_identity() -> Matrix4f {
+    Matrix4f { e: [
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    ] }
+}
+
+#[inline]
+pub fn matrix_4f_translate(t: Vector3f) -> Matrix4f {
+    Matrix4f { e: [
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        t.x, t.y, t.z, 1.0,
+    ] }
+}
+
+#[inline]
+pub fn matrix_4f_scale(s: Vector3f) -> Matrix4f {
+    Matrix4f { e: [
+        s.x, 0.0, 0.0, 0.0,
+        0.0, s.y, 0.0, 0.0,
+        0.0, 0.0, s.z, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    ] }
+}
+
+// Counter clockwise around z by angle radians
+#[inline]
+pub fn matrix_4f_rotate_z(angle: f32) -> Matrix4f {
+    let (sin, cos) = angle.sin_cos();
+    Matrix4f { e: [
+        cos, sin, 0.0, 0.0,
+       -sin, cos, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    ] }
+}
+
+// translate * rotate_z * scale
+#[inline]
+pub fn matrix_4f_trs(t: Vector3f, angle: f32, s: Vector3f) -> Matrix4f {
+    let (sin, cos) = angle.sin_cos();
+    Matrix4f { e: [
+        cos*s.x,  sin*s.x, 0.0, 0.0,
+       -sin*s.y,  cos*s.y, 0.0, 0.0,
+        0.0,      0.0,     s.z, 0.0,
+        t.x,      t.y,     t.z, 1.0,
+    ] }
+}
+
+// Same matrix glOrtho builds
+pub fn matrix_4f_orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Matrix4f {
+    Matrix4f { e: [
+        2.0/(right - left), 0.0, 0.0, 0.0,
+        0.0, 2.0/(top - bottom), 0.0, 0.0,
+        0.0, 0.0, -2.0/(far - near), 0.0,
+        -(right + left)/(right - left), -(top + bottom)/(top - bottom), -(far + near)/(far - near), 1.0,
+    ] }
+}
+
+// Embeds a 2D transform in the xy plane, z passes through untouched
+pub fn matrix_4f_from_3f(m: &Matrix3f) -> Matrix4f {
+    Matrix4f { e: [
+        m.e[0], m.e[1], 0.0, m.e[2],
+        m.e[3], m.e[4], 0.0, m.e[5],
+        0.0,    0.0,    1.0, 0.0,
+        m.e[6], m.e[7], 0.0, m.e[8],
+    ] }
+}
+
+// a * b, so b is applied first
+pub fn matrix_4f_multiply(a: &Matrix4f, b: &Matrix4f) -> Matrix4f {
+    let mut e = [0.0; 16];
+    for col in 0..4 {
+        for row in 0..4 {
+            e[col*4 + row] = (0..4).map(|k| a.e[k*4 + row]*b.e[col*4 + k]).sum();
+        }
+    }
+    Matrix4f { e }
+}
+
+// None when the matrix can't be inverted
+pub fn matrix_4f_inverse(m: &Matrix4f) -> Option<Matrix4f> {
+    // Laplace expansion with 2x2 sub determinants. a(i, j) indexes the storage as
+    // e[i*4 + j], which is the transpose, and the inverse is written back the same way
+    // since inverse(transpose(M)) == transpose(inverse(M)).
+    let a = |i: usize, j: usize| m.e[i*4 + j];
+    let s0 = a(0, 0)*a(1, 1) - a(1, 0)*a(0, 1);
+    let s1 = a(0, 0)*a(1, 2) - a(1, 0)*a(0, 2);
+    let s2 = a(0, 0)*a(1, 3) - a(1, 0)*a(0, 3);
+    let s3 = a(0, 1)*a(1, 2) - a(1, 1)*a(0, 2);
+    let s4 = a(0, 1)*a(1, 3) - a(1, 1)*a(0, 3);
+    let s5 = a(0, 2)*a(1, 3) - a(1, 2)*a(0, 3);
+    let c5 = a(2, 2)*a(3, 3) - a(3, 2)*a(2, 3);
+    let c4 = a(2, 1)*a(3, 3) - a(3, 1)*a(2, 3);
+    let c3 = a(2, 1)*a(3, 2) - a(3, 1)*a(2, 2);
+    let c2 = a(2, 0)*a(3, 3) - a(3, 0)*a(2, 3);
+    let c1 = a(2, 0)*a(3, 2) - a(3, 0)*a(2, 2);
+    let c0 = a(2, 0)*a(3, 1) - a(3, 0)*a(2, 1);
+
+    let det = s0*c5 - s1*c4 + s2*c3 + s3*c2 - s4*c1 + s5*c0;
+    let inv_det = 1.0/det;
+    if !det.is_finite() || !inv_det.is_finite() {
+        return None;
+    }
+
+    let inv = [
+         a(1, 1)*c5 - a(1, 2)*c4 + a(1, 3)*c3,
+        -a(0, 1)*c5 + a(0, 2)*c4 - a(0, 3)*c3,
+         a(3, 1)*s5 - a(3, 2)*s4 + a(3, 3)*s3,
+        -a(2, 1)*s5 + a(2, 2)*s4 - a(2, 3)*s3,
+
+        -a(1, 0)*c5 + a(1, 2)*c2 - a(1, 3)*c1,
+         a(0, 0)*c5 - a(0, 2)*c2 + a(0, 3)*c1,
+        -a(3, 0)*s5 + a(3, 2)*s2 - a(3, 3)*s1,
+         a(2, 0)*s5 - a(2, 2)*s2 + a(2, 3)*s1,
+
+         a(1, 0)*c4 - a(1, 1)*c2 + a(1, 3)*c0,
+        -a(0, 0)*c4 + a(0, 1)*c2 - a(0, 3)*c0,
+         a(3, 0)*s4 - a(3, 1)*s2 + a(3, 3)*s0,
+        -a(2, 0)*s4 + a(2, 1)*s2 - a(2, 3)*s0,
+
+        -a(1, 0)*c3 + a(1, 1)*c1 - a(1, 2)*c0,
+         a(0, 0)*c3 - a(0, 1)*c1 + a(0, 2)*c0,
+        -a(3, 0)*s3 + a(3, 1)*s1 - a(3, 2)*s0,
+         a(2, 0)*s3 - a(2, 1)*s1 + a(2, 2)*s0,
+    ];
+    Some(Matrix4f { e: inv.map(|v| v*inv_det) })
+}
+
+// Includes translation, assumes an affine matrix (no perspective divide)
+#[inline]
+pub fn matrix_4f_transform_point(m: &Matrix4f, p: Vector3f) -> Vector3f {
+    Vector3f {
+        x: m.e[0]*p.x + m.e[4]*p.y + m.e[8]*p.z + m.e[12],
+        y: m.e[1]*p.x + m.e[5]*p.y + m.e[9]*p.z + m.e[13],
+        z: m.e[2]*p.x + m.e[6]*p.y + m.e[10]*p.z + m.e[14],
+    }
+}
+
+// Ignores translation
+#[inline]
+pub fn matrix_4f_transform_vector(m: &Matrix4f, v: Vector3f) -> Vector3f {
+    Vector3f {
+        x: m.e[0]*v.x + m.e[4]*v.y + m.e[8]*v.z,
+        y: m.e[1]*v.x + m.e[5]*v.y + m.e[9]*v.z,
+        z: m.e[2]*v.x + m.e[6]*v.y + m.e[10]*v.z,
+    }
+}
+
+// For glLoadMatrixf and glMultMatrixf
+#[inline]
+pub fn matrix_4f_as_ptr(m: &Matrix4f) -> *const f32 {
+    m.e.as_ptr()
+}
@@ -0,0 +1,90 @@
+use crate::math::*;
+use crate::orbit::*;
+use crate::physics::*;
+use crate::state::*;
+use crate::world::*;
+
+// Trajectory prediction.
+//
+// Forward simulates the ship drifting with no thrust, at the same fixed step and under the
+// same gravity as the simulation, with the planets going round their orbits meanwhile.
+// Points are colored from cool to hot by speed and the path ends where it runs into a
+// planet, colored by how that contact would be judged.
+
+pub const NAV_PREDICT_SEC: f32 = 4.0;
+// Sim steps between recorded points
+const NAV_STEPS_PER_POINT: u32 = 4;
+
+const NAV_SLOW_COLOR: Vector3f = Vector3f { x: 0.2, y: 0.6, z: 1.0 };
+const NAV_FAST_COLOR: Vector3f = Vector3f { x: 1.0, y: 0.8, z: 0.2 };
+const NAV_CRASH_COLOR: Vector3f = Vector3f { x: 1.0, y: 0.15, z: 0.15 };
+const NAV_LANDED_COLOR: Vector3f = Vector3f { x: 0.2, y: 1.0, z: 0.3 };
+const NAV_TOUCHDOWN_COLOR: Vector3f = Vector3f { x: 1.0, y: 0.9, z: 0.2 };
+
+fn nav_speed_color(speed: f32) -> Vector3f {
+    let t = (speed / SHIP_MAX_SPEED).clamp(0.0, 1.0);
+    vector_3f_lerp(NAV_SLOW_COLOR, NAV_FAST_COLOR, t)
+}
+
+// Copy of a planet with only what its orbit, gravity and landing need, the items and colors
+// are left behind so predicting every frame doesn't copy them too
+fn nav_planet(p: &Planet) -> Planet {
+    Planet {
+        radius: p.radius,
+        pos: p.pos,
+        prev_pos: p.prev_pos,
+        d_pos: p.d_pos,
+        g_radius: p.g_radius,
+        mass: p.mass,
+        orbit: p.orbit,
+        surface_radius: p.surface_radius,
+        lz_rel_pos: p.lz_rel_pos,
+        ..Planet::default()
+    }
+}
+
+// Points are not wrapped so the path stays one continuous line off the edge of the world,
+// gravity is still sampled at the wrapped position the ship would really be at.
+pub fn nav_predict(ctx: &GameState, world_size: Vector2f, seconds: f32) -> NavPath {
+    let dt = ctx.sim_step_sec;
+    // the planets keep going round while the ship drifts
+    let mut planets: Vec<Planet> = ctx.planets.iter().flatten().map(nav_planet).collect();
+    let mut time = ctx.sim_time_sec;
+
+    let mut pos = ctx.player.pos;
+    let mut d_pos = ctx.player.d_pos;
+    let mut unwrapped_pos = pos;
+
+    let mut points = vec![NavPoint::new(unwrapped_pos, nav_speed_color(vector_2f_length(d_pos)))];
+    let step_count = if dt > 0.0 { (seconds / dt) as u32 } else { 0 };
+    for step in 1..=step_count {
+        time += dt;
+        orbit_update_planets(ctx.gravity_g, &ctx.sun, &mut planets, time, world_size);
+
+        let gravity = physics_gravity(ctx.gravity_g, &ctx.sun, &planets, pos, d_pos, world_size, None);
+        d_pos = physics_apply_acceleration(d_pos, gravity.accel, 0.5 * dt);
+
+        let step_d_pos = vector_2f_scale(d_pos, dt);
+        if let Some((i, hit)) = physics_sweep_planets(&planets, pos, step_d_pos, world_size) {
+            let impact = vector_2f_add(unwrapped_pos, vector_2f_sub(hit.point, pos));
+            let color = match physics_landing_outcome(&planets[i], hit.point, hit.normal, d_pos, ctx.player.rot, world_size) {
+                LandingOutcome::Landed => NAV_LANDED_COLOR,
+                LandingOutcome::TouchedDown => NAV_TOUCHDOWN_COLOR,
+                _ => NAV_CRASH_COLOR,
+            };
+            points.push(NavPoint::new(impact, color));
+            break;
+        }
+        unwrapped_pos = vector_2f_add(unwrapped_pos, step_d_pos);
+        pos = world_wrap(vector_2f_add(pos, step_d_pos), world_size);
+
+        let gravity = physics_gravity(ctx.gravity_g, &ctx.sun, &planets, pos, d_pos, world_size, None);
+        d_pos = physics_apply_acceleration(d_pos, gravity.accel, 0.5 * dt);
+
+        if step % NAV_STEPS_PER_POINT == 0 {
+            points.push(NavPoint::new(unwrapped_pos, nav_speed_color(vector_2f_length(d_pos))));
+        }
+    }
+
+    NavPath { points }
+}
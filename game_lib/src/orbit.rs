@@ -0,0 +1,103 @@
+use std::f32::consts::{PI, TAU};
+
+use crate::math::*;
+use crate::state::*;
+use crate::world::*;
+
+// Planets and moons on rails.
+//
+// An orbit is a Kepler ellipse around the sun or another planet, so where a planet is comes
+// from the time alone: orbits never decay or drift however long the game runs and the
+// trajectory predictor can ask where the planets will be. Planets pull on the ship and
+// projectiles but not on each other or the sun. A moon's parent comes before it in the
+// planet list so the parent has already moved when the moon is placed around it.
+
+pub const ORBIT_MAX_ECCENTRICITY: f32 = 0.9;
+// Newton steps for Kepler's equation, enough up to ORBIT_MAX_ECCENTRICITY
+const ORBIT_KEPLER_ITERATIONS: u32 = 8;
+
+// Circle through rel_pos, which is relative to the parent
+pub fn orbit_circular(parent: OrbitParent, rel_pos: Vector2f, clockwise: bool) -> Orbit {
+    Orbit {
+        parent,
+        semi_major: vector_2f_length(rel_pos),
+        eccentricity: 0.0,
+        periapsis_rot: vector_2f_angle(rel_pos),
+        phase: 0.0,
+        clockwise,
+    }
+}
+
+// Gravitational parameter of the body the orbit goes around, G times its mass
+pub fn orbit_parent_mu(gravity_g: f32, sun: &Sun, planets: &[Planet], parent: OrbitParent) -> f32 {
+    match parent {
+        OrbitParent::Sun => gravity_g * sun.mass,
+        OrbitParent::Planet(i) => planets.get(i).map_or(0.0, |p| gravity_g * p.mass),
+    }
+}
+
+pub fn orbit_period(orbit: &Orbit, mu: f32) -> f32 {
+    if mu <= 0.0 {
+        return f32::INFINITY;
+    }
+    TAU * (orbit.semi_major.powi(3) / mu).sqrt()
+}
+
+// Position and velocity relative to the parent at time
+pub fn orbit_state(orbit: &Orbit, mu: f32, time: f32) -> (Vector2f, Vector2f) {
+    let a = orbit.semi_major;
+    let e = orbit.eccentricity.clamp(0.0, ORBIT_MAX_ECCENTRICITY);
+    let mean_motion = if mu > 0.0 && a > 0.0 { (mu / a.powi(3)).sqrt() } else { 0.0 };
+    let mean_anomaly = (orbit.phase + mean_motion * time).rem_euclid(TAU);
+
+    // Kepler's equation, M = E - e sin E
+    let mut ecc_anomaly = if e < 0.8 { mean_anomaly } else { PI };
+    for _ in 0..ORBIT_KEPLER_ITERATIONS {
+        ecc_anomaly -= (ecc_anomaly - e * ecc_anomaly.sin() - mean_anomaly) / (1.0 - e * ecc_anomaly.cos());
+    }
+
+    let b = a * (1.0 - e * e).sqrt();
+    let (sin_e, cos_e) = ecc_anomaly.sin_cos();
+    let d_ecc_anomaly = mean_motion / (1.0 - e * cos_e);
+    let mut pos = vector_2f(a * (cos_e - e), b * sin_e);
+    let mut d_pos = vector_2f(-a * sin_e * d_ecc_anomaly, b * cos_e * d_ecc_anomaly);
+    if orbit.clockwise {
+        pos.y = -pos.y;
+        d_pos.y = -d_pos.y;
+    }
+    (vector_2f_rotate(pos, orbit.periapsis_rot), vector_2f_rotate(d_pos, orbit.periapsis_rot))
+}
+
+// Moves every planet with an orbit to where it is at time. prev_pos is left where the planet
+// was, on the same side of the wrap, and planets without an orbit keep still.
+pub fn orbit_update_planets(gravity_g: f32, sun: &Sun, planets: &mut [Planet], time: f32, world_size: Vector2f) {
+    for i in 0..planets.len() {
+        planets[i].prev_pos = planets[i].pos;
+        let orbit = match planets[i].orbit {
+            Some(o) => o,
+            None => continue,
+        };
+        let (parent_pos, parent_d_pos) = match orbit.parent {
+            OrbitParent::Sun => (sun.pos, vector_2f_zero()),
+            OrbitParent::Planet(j) if j < i => (planets[j].pos, planets[j].d_pos),
+            // levels with a moon before its parent don't load
+            _ => continue,
+        };
+        let mu = orbit_parent_mu(gravity_g, sun, planets, orbit.parent);
+        let (rel_pos, rel_d_pos) = orbit_state(&orbit, mu, time);
+
+        let p = &mut planets[i];
+        let pos = world_nearest(p.pos, vector_2f_add(parent_pos, rel_pos), world_size);
+        p.pos = world_wrap(pos, world_size);
+        p.prev_pos = vector_2f_add(p.prev_pos, vector_2f_sub(p.pos, pos));
+        p.d_pos = vector_2f_add(parent_d_pos, rel_d_pos);
+    }
+}
+
+// Puts the planets on their orbits with nothing to interpolate from, for a fresh level
+pub fn orbit_place_planets(gravity_g: f32, sun: &Sun, planets: &mut [Planet], time: f32, world_size: Vector2f) {
+    orbit_update_planets(gravity_g, sun, planets, time, world_size);
+    for p in planets.iter_mut() {
+        p.prev_pos = p.pos;
+    }
+}
@@ -0,0 +1,123 @@
+use crate::geometry::*;
+use crate::math::*;
+use crate::state::*;
+use crate::world::*;
+
+// Ship physics shared by the simulation and the trajectory predictor, so a predicted
+// path follows exactly the rules the ship will.
+//
+// Everything that flies is stepped with leapfrog: half the velocity change, the move, then
+// the other half with gravity at the new position. Leapfrog is symplectic, so a ship coasting
+// around a planet keeps its orbit instead of spiralling in or out from integration error.
+// The planets have already moved for the step by then and hold still while it's taken.
+
+pub const SHIP_MAX_SPEED: f32 = 600.0;          // units/s
+pub const PLANET_SURFACE_FRICTION: f32 = 3.0;   // fraction of velocity lost/s
+// Landing rules
+pub const LANDING_MAX_SPEED: f32 = 80.0;              // units/s at contact
+pub const LANDING_MAX_TILT: f32 = 0.5;                // radians between the nose and straight up
+pub const LANDING_ZONE_RADIUS: f32 = 30.0;            // contact this close to the pad counts as on it
+pub const LANDING_CRASH_DAMAGE_PER_SPEED: f32 = 0.002; // hull lost per unit/s of impact speed
+pub const LANDING_CRASH_RESTITUTION: f32 = 0.4;       // fraction of speed kept when bouncing off
+pub const LANDING_REFUEL_RATE: f32 = 0.25;            // fuel/s while sitting on a pad
+
+// Gravitational constant levels get unless they set their own
+pub const PHYSICS_DEFAULT_G: f32 = 1000.0;
+pub const PHYSICS_DEFAULT_SUN_MASS: f32 = 1500.0;
+pub const PHYSICS_DEFAULT_SUN_SOFTENING: f32 = 150.0;
+
+// How far inside the surface a ship is put when a sweep stops it, so it counts as landed
+pub const PHYSICS_CONTACT_SKIN: f32 = 0.01;
+
+pub struct GravitySample {
+    // Sum of gravity, or surface friction when landed
+    pub accel: Vector2f,
+    pub landed: bool,
+}
+
+// Plummer softened inverse square pull toward a body delta away. With no softening the pull
+// at the center itself is zero rather than infinite.
+pub fn physics_pull(gravity_g: f32, mass: f32, softening: f32, delta: Vector2f) -> Vector2f {
+    let dist_sq = vector_2f_dot(delta, delta) + softening * softening;
+    if dist_sq <= 0.0 {
+        return vector_2f_zero();
+    }
+    vector_2f_scale(delta, gravity_g * mass / (dist_sq * dist_sq.sqrt()))
+}
+
+// Planet whose surface pos is inside
+pub fn physics_surface_planet(planets: &[Planet], pos: Vector2f, world_size: Vector2f) -> Option<usize> {
+    planets.iter().position(|p| world_distance(pos, p.pos, world_size) < p.surface_radius)
+}
+
+// Every planet and the sun pull, the shortest way round the world. On a surface the ship
+// is held by friction against the planet's motion instead.
+// Each individual planet force is pushed to forces for debug drawing.
+pub fn physics_gravity(gravity_g: f32, sun: &Sun, planets: &[Planet], pos: Vector2f, d_pos: Vector2f, world_size: Vector2f, mut forces: Option<&mut Vec<Vector2f>>) -> GravitySample {
+    if let Some(i) = physics_surface_planet(planets, pos, world_size) {
+        let friction = vector_2f_scale(vector_2f_sub(planets[i].d_pos, d_pos), PLANET_SURFACE_FRICTION);
+        if let Some(forces) = forces {
+            forces.push(friction);
+        }
+        return GravitySample { accel: friction, landed: true };
+    }
+
+    let mut accel = vector_2f_zero();
+    for p in planets.iter() {
+        let force = physics_pull(gravity_g, p.mass, 0.0, world_delta(pos, p.pos, world_size));
+        if let Some(forces) = forces.as_deref_mut() {
+            forces.push(force);
+        }
+        accel = vector_2f_add(accel, force);
+    }
+    let sun_pull = physics_pull(gravity_g, sun.mass, sun.softening, world_delta(pos, sun.pos, world_size));
+    accel = vector_2f_add(accel, sun_pull);
+
+    GravitySample { accel, landed: false }
+}
+
+// Velocity changes that would push past SHIP_MAX_SPEED are dropped
+pub fn physics_apply_acceleration(d_pos: Vector2f, accel: Vector2f, dt: f32) -> Vector2f {
+    let new_d_pos = vector_2f_add(d_pos, vector_2f_scale(accel, dt));
+    if vector_2f_length(new_d_pos) <= SHIP_MAX_SPEED {
+        new_d_pos
+    } else {
+        d_pos
+    }
+}
+
+// First planet surface the ship enters while moving by delta this step. Sweeping instead of
+// checking the end position keeps fast ships from stepping over a planet between two steps.
+// The planets have already taken the step from prev_pos to pos, so each one is swept in its
+// own frame and the hit is where the planet is now. Each planet is swept at its copy nearest
+// pos, so the hit is on pos's side of the seam.
+pub fn physics_sweep_planets(planets: &[Planet], pos: Vector2f, delta: Vector2f, world_size: Vector2f) -> Option<(usize, RayHit)> {
+    let mut first: Option<(usize, RayHit)> = None;
+    for (i, p) in planets.iter().enumerate() {
+        let moved = world_delta(p.prev_pos, p.pos, world_size);
+        let ship = circle(vector_2f_add(pos, moved), 0.0);
+        let planet_pos = world_nearest(pos, p.pos, world_size);
+        if let Some(hit) = circle_sweep_circle(&ship, vector_2f_sub(delta, moved), &circle(planet_pos, p.surface_radius)) {
+            if first.is_none_or(|(_, f)| hit.t < f.t) {
+                first = Some((i, hit));
+            }
+        }
+    }
+    first
+}
+
+// Judges a contact with the surface of planet at point, where normal points out of the planet.
+// The speed that counts is the one relative to the planet.
+pub fn physics_landing_outcome(planet: &Planet, point: Vector2f, normal: Vector2f, d_pos: Vector2f, rot: f32, world_size: Vector2f) -> LandingOutcome {
+    let slow = vector_2f_distance(d_pos, planet.d_pos) <= LANDING_MAX_SPEED;
+    let upright = vector_2f_angle_between(vector_2f_from_angle(rot), normal) <= LANDING_MAX_TILT;
+    if !slow || !upright {
+        return LandingOutcome::Crashed;
+    }
+    let pad = vector_2f_add(planet.pos, planet.lz_rel_pos);
+    if world_distance(point, pad, world_size) <= LANDING_ZONE_RADIUS {
+        LandingOutcome::Landed
+    } else {
+        LandingOutcome::TouchedDown
+    }
+}
@@ -0,0 +1,70 @@
+use crate::math::*;
+use crate::physics::*;
+use crate::state::*;
+use crate::world::*;
+
+// Projectiles fired from the ship.
+//
+// They live in a fixed size pool on GameState: a dead slot is reused first, and when every
+// slot is alive the oldest projectile is replaced. Projectiles fall under the same gravity
+// as the ship, wrap around the world and die when they hit something or run out of time.
+
+pub const MAX_PROJECTILES: usize = 32;
+pub const PROJECTILE_SPEED: f32 = 400.0;        // units/s on top of the ship's velocity
+pub const PROJECTILE_LIFETIME_SEC: f32 = 2.5;
+// How far in front of the ship's center projectiles appear
+const PROJECTILE_MUZZLE_DIST: f32 = 12.0;
+
+pub fn projectile_fire(ctx: &mut GameState) {
+    let nose = vector_2f_from_angle(ctx.player.rot);
+    let pos = vector_2f_add(ctx.player.pos, vector_2f_scale(nose, PROJECTILE_MUZZLE_DIST));
+    let projectile = Projectile {
+        pos,
+        prev_pos: pos,
+        d_pos: vector_2f_add(ctx.player.d_pos, vector_2f_scale(nose, PROJECTILE_SPEED)),
+        age_sec: 0.0,
+        alive: true,
+    };
+
+    let pool = &mut ctx.projectiles;
+    if let Some(slot) = pool.iter_mut().find(|p| !p.alive) {
+        *slot = projectile;
+    } else if pool.len() < MAX_PROJECTILES {
+        pool.push(projectile);
+    } else if let Some(oldest) = pool.iter_mut().max_by(|a, b| a.age_sec.total_cmp(&b.age_sec)) {
+        *oldest = projectile;
+    }
+}
+
+pub fn projectile_update(ctx: &mut GameState, world_size: Vector2f, dt: f32) {
+    let planets = ctx.planets.as_deref().unwrap_or(&[]);
+
+    for p in ctx.projectiles.iter_mut().filter(|p| p.alive) {
+        p.prev_pos = p.pos;
+        p.age_sec += dt;
+        if p.age_sec >= PROJECTILE_LIFETIME_SEC {
+            p.alive = false;
+            continue;
+        }
+
+        // leapfrog like the ship, projectiles are never landed, they die on the surface instead
+        let gravity = physics_gravity(ctx.gravity_g, &ctx.sun, planets, p.pos, p.d_pos, world_size, None);
+        p.d_pos = vector_2f_add(p.d_pos, vector_2f_scale(gravity.accel, 0.5 * dt));
+
+        let delta = vector_2f_scale(p.d_pos, dt);
+        if let Some((i, hit)) = physics_sweep_planets(planets, p.pos, delta, world_size) {
+            p.pos = hit.point;
+            p.alive = false;
+            ctx.events.push(GameEvent::ProjectileHit { pos: hit.point, target: ProjectileTarget::Planet(i) });
+            continue;
+        }
+
+        let unwrapped_pos = vector_2f_add(p.pos, delta);
+        p.pos = world_wrap(unwrapped_pos, world_size);
+        // keep the previous position on the same side of the wrap for interpolation
+        p.prev_pos = vector_2f_add(p.prev_pos, vector_2f_sub(p.pos, unwrapped_pos));
+
+        let gravity = physics_gravity(ctx.gravity_g, &ctx.sun, planets, p.pos, p.d_pos, world_size, None);
+        p.d_pos = vector_2f_add(p.d_pos, vector_2f_scale(gravity.accel, 0.5 * dt));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::math::*;
+
+// PCG32 random number series (https://www.pcg-random.org).
+// All procedural generation should pull from a RandomSeries so a seed always
+// reproduces the same universe.
+
+const RANDOM_PCG_MULTIPLIER: u64 = 6364136223846793005;
+const RANDOM_PCG_INCREMENT: u64 = 1442695040888963407;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RandomSeries {
+    pub state: u64,
+}
+impl Default for RandomSeries {
+    fn default() -> Self {
+        random_seed(0)
+    }
+}
+
+pub fn random_seed(seed: u64) -> RandomSeries {
+    let mut series = RandomSeries { state: 0 };
+    random_next_u32(&mut series);
+    series.state = series.state.wrapping_add(seed);
+    random_next_u32(&mut series);
+    series
+}
+
+#[inline]
+pub fn random_next_u32(series: &mut RandomSeries) -> u32 {
+    let old = series.state;
+    series.state = old.wrapping_mul(RANDOM_PCG_MULTIPLIER).wrapping_add(RANDOM_PCG_INCREMENT);
+    let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+    let rot = (old >> 59) as u32;
+    xorshifted.rotate_right(rot)
+}
+
+// [0, 1)
+#[inline]
+pub fn random_unilateral(series: &mut RandomSeries) -> f32 {
+    // 24 bits is all the precision an f32 mantissa can hold
+    (random_next_u32(series) >> 8) as f32 / (1u32 << 24) as f32
+}
+
+// [-1, 1)
+#[inline]
+pub fn random_bilateral(series: &mut RandomSeries) -> f32 {
+    2.0 * random_unilateral(series) - 1.0
+}
+
+// [min, max)
+#[inline]
+pub fn random_between(series: &mut RandomSeries, min: f32, max: f32) -> f32 {
+    min + (max - min) * random_unilateral(series)
+}
+
+// [0, count)
+#[inline]
+pub fn random_choice(series: &mut RandomSeries, count: u32) -> u32 {
+    debug_assert!(count > 0);
+    ((random_next_u32(series) as u64 * count as u64) >> 32) as u32
+}
+
+pub fn random_unit_vector_2f(series: &mut RandomSeries) -> Vector2f {
+    let angle = random_between(series, 0.0, std::f32::consts::TAU);
+    vector_2f(angle.cos(), angle.sin())
+}
+
+// Box-Muller transform
+pub fn random_gaussian(series: &mut RandomSeries, mean: f32, std_dev: f32) -> f32 {
+    // keep u1 away from zero so ln() stays finite
+    let u1 = random_unilateral(series).max(f32::MIN_POSITIVE);
+    let u2 = random_unilateral(series);
+    let z = (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos();
+    mean + z * std_dev
+}
@@ -1,5 +1,223 @@
-use crate::state::*;
+use crate::math::*;
+
+// The game never talks to a graphics API directly. Instead update_and_render pushes
+// a list of render commands into a RenderBuffer and the platform layer replays them
+// with whatever backend it has (OpenGL on win32, software, etc).
+
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RenderLayer {
+    // World space centered on the buffer camera
+    #[default]
+    World,
+    // Screen space with the origin at the center of the viewport
+    Hud,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RenderTexture {
+    Debug,
+    Glyph(char),
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct RenderVertex {
+    pub p: Vector2f,
+    pub c: Vector4f,
+}
+
+pub enum RenderCommand {
+    Clear {
+        color: Vector4f,
+    },
+    // Axis aligned quad covering pos +/- half_dim
+    Quad {
+        pos: Vector2f,
+        half_dim: Vector2f,
+        color: Vector4f,
+    },
+    Circle {
+        pos: Vector2f,
+        radius: f32,
+        color: Vector4f,
+        filled: bool,
+    },
+    LineStrip {
+        points: Vec<RenderVertex>,
+        width: f32,
+    },
+    PointCloud {
+        points: Vec<Vector2f>,
+        color: Vector4f,
+        size: f32,
+    },
+    Triangles {
+        vertices: Vec<Vector2f>,
+        color: Vector4f,
+    },
+    TexturedQuad {
+        pos: Vector2f,
+        half_dim: Vector2f,
+        texture: RenderTexture,
+        color: Vector4f,
+    },
+    // Applied on top of the current transform as translate * rotate * scale
+    PushTransform {
+        pos: Vector2f,
+        rot: f32,
+        scale: Vector2f,
+    },
+    PopTransform,
+    // Resets the transform stack and switches projection
+    SetLayer(RenderLayer),
+}
+
+// What the World layer is looked at through, filled from the game's camera every frame
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RenderCamera {
+    pub pos: Vector2f,
+    // Above 1 zooms in, below 1 shows more of the world
+    pub zoom: f32,
+    // Counter clockwise, the world appears turned the other way
+    pub rot: f32,
+}
+
+impl Default for RenderCamera {
+    fn default() -> Self {
+        render_camera(vector_2f_zero(), 1.0, 0.0)
+    }
+}
+
+#[derive(Default)]
+pub struct RenderBuffer {
+    pub camera: RenderCamera,
+    pub commands: Vec<RenderCommand>,
+}
 
 pub trait IRenderer {
-    fn render(x: i32, y: i32, width: i32, height: i32, ctx: &GameState);
-}
\ No newline at end of file
+    fn render(&mut self, x: i32, y: i32, width: i32, height: i32, buffer: &RenderBuffer);
+}
+
+pub fn render_camera(pos: Vector2f, zoom: f32, rot: f32) -> RenderCamera {
+    RenderCamera { pos, zoom, rot }
+}
+
+pub fn render_buffer_reset(buffer: &mut RenderBuffer) {
+    buffer.camera = RenderCamera::default();
+    buffer.commands.clear();
+}
+
+pub fn render_set_camera(buffer: &mut RenderBuffer, camera: RenderCamera) {
+    buffer.camera = camera;
+}
+
+pub fn render_push_clear(buffer: &mut RenderBuffer, color: Vector4f) {
+    buffer.commands.push(RenderCommand::Clear { color });
+}
+
+pub fn render_push_quad(buffer: &mut RenderBuffer, pos: Vector2f, half_dim: Vector2f, color: Vector4f) {
+    buffer.commands.push(RenderCommand::Quad { pos, half_dim, color });
+}
+
+pub fn render_push_circle(buffer: &mut RenderBuffer, pos: Vector2f, radius: f32, color: Vector4f, filled: bool) {
+    buffer.commands.push(RenderCommand::Circle { pos, radius, color, filled });
+}
+
+pub fn render_push_line_strip(buffer: &mut RenderBuffer, points: Vec<RenderVertex>, width: f32) {
+    buffer.commands.push(RenderCommand::LineStrip { points, width });
+}
+
+pub fn render_push_line(buffer: &mut RenderBuffer, a: Vector2f, b: Vector2f, color: Vector4f, width: f32) {
+    let points = vec![
+        RenderVertex { p: a, c: color },
+        RenderVertex { p: b, c: color },
+    ];
+    render_push_line_strip(buffer, points, width);
+}
+
+pub fn render_push_point_cloud(buffer: &mut RenderBuffer, points: Vec<Vector2f>, color: Vector4f, size: f32) {
+    buffer.commands.push(RenderCommand::PointCloud { points, color, size });
+}
+
+pub fn render_push_triangles(buffer: &mut RenderBuffer, vertices: Vec<Vector2f>, color: Vector4f) {
+    debug_assert!(vertices.len().is_multiple_of(3), "triangle vertex count {} is not a multiple of 3", vertices.len());
+    buffer.commands.push(RenderCommand::Triangles { vertices, color });
+}
+
+pub fn render_push_textured_quad(buffer: &mut RenderBuffer, pos: Vector2f, half_dim: Vector2f, texture: RenderTexture, color: Vector4f) {
+    buffer.commands.push(RenderCommand::TexturedQuad { pos, half_dim, texture, color });
+}
+
+pub fn render_push_transform(buffer: &mut RenderBuffer, pos: Vector2f, rot: f32, scale: Vector2f) {
+    buffer.commands.push(RenderCommand::PushTransform { pos, rot, scale });
+}
+
+pub fn render_pop_transform(buffer: &mut RenderBuffer) {
+    buffer.commands.push(RenderCommand::PopTransform);
+}
+
+pub fn render_set_layer(buffer: &mut RenderBuffer, layer: RenderLayer) {
+    buffer.commands.push(RenderCommand::SetLayer(layer));
+}
+
+//
+// Transforms shared by the renderers
+//
+
+// Orthographic projection with the origin at the center of the viewport. Half sizes are
+// whole units so odd sized viewports line up the same in every backend. The World layer
+// is also moved, turned and zoomed so the camera is at the center.
+pub fn render_layer_projection(width: i32, height: i32, camera: &RenderCamera, layer: RenderLayer) -> Matrix4f {
+    let half_width = (width / 2) as f32;
+    let half_height = (height / 2) as f32;
+    let projection = matrix_4f_orthographic(-half_width, half_width, -half_height, half_height, 0.0, 100.0);
+    match layer {
+        RenderLayer::World => {
+            let zoom = render_camera_zoom(camera);
+            let view = matrix_4f_multiply(
+                &matrix_4f_multiply(&matrix_4f_scale(vector_3f(zoom, zoom, 1.0)), &matrix_4f_rotate_z(-camera.rot)),
+                &matrix_4f_translate(vector_3f(-camera.pos.x, -camera.pos.y, 0.0)));
+            matrix_4f_multiply(&projection, &view)
+        },
+        RenderLayer::Hud => projection,
+    }
+}
+
+// A zero zoom would divide by zero, treat it as unzoomed
+fn render_camera_zoom(camera: &RenderCamera) -> f32 {
+    if camera.zoom > 0.0 { camera.zoom } else { 1.0 }
+}
+
+// Window pixels (origin top left, y down) to the World layer under the given camera.
+// Passing the default camera gives Hud layer coordinates instead.
+pub fn render_screen_to_world(width: i32, height: i32, camera: &RenderCamera, screen_pos: Vector2f) -> Vector2f {
+    if width <= 0 || height <= 0 {
+        return camera.pos;
+    }
+    let half_width = (width / 2) as f32;
+    let half_height = (height / 2) as f32;
+    let ndc_x = 2.0 * screen_pos.x / width as f32 - 1.0;
+    let ndc_y = 1.0 - 2.0 * screen_pos.y / height as f32;
+    let view = vector_2f_scale(vector_2f(ndc_x * half_width, ndc_y * half_height), 1.0 / render_camera_zoom(camera));
+    vector_2f_add(camera.pos, vector_2f_rotate(view, camera.rot))
+}
+
+// Inverse of render_screen_to_world
+pub fn render_world_to_screen(width: i32, height: i32, camera: &RenderCamera, world_pos: Vector2f) -> Vector2f {
+    let half_width = ((width / 2) as f32).max(1.0);
+    let half_height = ((height / 2) as f32).max(1.0);
+    let view = vector_2f_rotate(vector_2f_sub(world_pos, camera.pos), -camera.rot);
+    let view = vector_2f_scale(view, render_camera_zoom(camera));
+    let ndc_x = view.x / half_width;
+    let ndc_y = view.y / half_height;
+    vector_2f(0.5 * (ndc_x + 1.0) * width as f32, 0.5 * (1.0 - ndc_y) * height as f32)
+}
+
+// Model transform that maps the [-1, 1] unit square onto pos +/- half_dim
+pub fn render_quad_transform(pos: Vector2f, half_dim: Vector2f) -> Matrix3f {
+    matrix_3f_trs(pos, 0.0, half_dim)
+}
+
+// Model transform for a PushTransform command
+pub fn render_push_transform_matrix(pos: Vector2f, rot: f32, scale: Vector2f) -> Matrix3f {
+    matrix_3f_trs(pos, rot, scale)
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use crate::bitmap::*;
+use crate::math::*;
+use crate::render::*;
+
+// CPU rasterizer that replays a RenderBuffer into an RGBA framebuffer.
+// It matches what the win32 OpenGL backend draws closely enough for screenshots
+// and tests on machines without a GPU. The framebuffer is stored top row first.
+
+const SOFTWARE_CIRCLE_POINTS: i32 = 360;
+
+#[derive(Copy, Clone)]
+struct SoftwareViewport {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+pub struct SoftwareRenderer {
+    pub framebuffer: LoadedBitmap,
+    // Texture bitmaps are stored bottom row first, the same as they are uploaded to OpenGL
+    pub textures: HashMap<RenderTexture, LoadedBitmap>,
+}
+
+impl IRenderer for SoftwareRenderer {
+    fn render(&mut self, x: i32, y: i32, width: i32, height: i32, buffer: &RenderBuffer) {
+        software_render(self, x, y, width, height, buffer);
+    }
+}
+
+pub fn software_renderer_create(width: i32, height: i32) -> SoftwareRenderer {
+    SoftwareRenderer {
+        framebuffer: bitmap_create(width, height),
+        textures: HashMap::new(),
+    }
+}
+
+pub fn software_renderer_set_texture(renderer: &mut SoftwareRenderer, texture: RenderTexture, bitmap: LoadedBitmap) {
+    renderer.textures.insert(texture, bitmap);
+}
+
+pub fn software_render(renderer: &mut SoftwareRenderer, x: i32, y: i32, width: i32, height: i32, buffer: &RenderBuffer) {
+    let viewport = SoftwareViewport { x, y, width, height };
+    let mut projection = render_layer_projection(width, height, &buffer.camera, RenderLayer::World);
+    let mut transforms = vec![matrix_3f_identity()];
+
+    for command in buffer.commands.iter() {
+        let model = *transforms.last().unwrap();
+        match command {
+            RenderCommand::Clear { color } => {
+                software_clear(&mut renderer.framebuffer, *color);
+            },
+            RenderCommand::Quad { pos, half_dim, color } => {
+                let corners = software_quad_corners(*pos, *half_dim);
+                let corners = corners.map(|c| software_project(&viewport, &projection, &model, c));
+                software_fill_triangle(&mut renderer.framebuffer, &viewport, corners[0], corners[1], corners[2], |_, _, _| *color);
+                software_fill_triangle(&mut renderer.framebuffer, &viewport, corners[0], corners[2], corners[3], |_, _, _| *color);
+            },
+            RenderCommand::Circle { pos, radius, color, filled } => {
+                let step = (360.0/SOFTWARE_CIRCLE_POINTS as f32).to_radians();
+                let points: Vec<Vector2f> = (0..SOFTWARE_CIRCLE_POINTS).map(|p| {
+                    let angle = p as f32 * step;
+                    let p = vector_2f(pos.x + radius * angle.cos(), pos.y + radius * angle.sin());
+                    software_project(&viewport, &projection, &model, p)
+                }).collect();
+                if *filled {
+                    for i in 1..points.len() - 1 {
+                        software_fill_triangle(&mut renderer.framebuffer, &viewport, points[0], points[i], points[i + 1], |_, _, _| *color);
+                    }
+                }
+                else {
+                    for i in 0..points.len() - 1 {
+                        software_draw_line(&mut renderer.framebuffer, &viewport, points[i], points[i + 1], *color, *color, 1.0);
+                    }
+                }
+            },
+            RenderCommand::LineStrip { points, width } => {
+                for pair in points.windows(2) {
+                    let a = software_project(&viewport, &projection, &model, pair[0].p);
+                    let b = software_project(&viewport, &projection, &model, pair[1].p);
+                    software_draw_line(&mut renderer.framebuffer, &viewport, a, b, pair[0].c, pair[1].c, *width);
+                }
+            },
+            RenderCommand::PointCloud { points, color, size } => {
+                for p in points.iter() {
+                    let p = software_project(&viewport, &projection, &model, *p);
+                    software_plot(&mut renderer.framebuffer, &viewport, p, *color, *size);
+                }
+            },
+            RenderCommand::Triangles { vertices, color } => {
+                for tri in vertices.chunks_exact(3) {
+                    let a = software_project(&viewport, &projection, &model, tri[0]);
+                    let b = software_project(&viewport, &projection, &model, tri[1]);
+                    let c = software_project(&viewport, &projection, &model, tri[2]);
+                    software_fill_triangle(&mut renderer.framebuffer, &viewport, a, b, c, |_, _, _| *color);
+                }
+            },
+            RenderCommand::TexturedQuad { pos, half_dim, texture, color } => {
+                let corners = software_quad_corners(*pos, *half_dim);
+                let corners = corners.map(|c| software_project(&viewport, &projection, &model, c));
+                // matches the texture coordinates the OpenGL backend uses for each corner
+                let uvs = [vector_2f(0.0, 1.0), vector_2f(0.0, 0.0), vector_2f(1.0, 0.0), vector_2f(1.0, 1.0)];
+                let texture = renderer.textures.get(texture);
+                let shade = |uv: Vector2f| match texture {
+                    Some(bitmap) => {
+                        let texel = bitmap_sample(bitmap, uv);
+                        vector_4f(texel.x * color.x, texel.y * color.y, texel.z * color.z, texel.w * color.w)
+                    },
+                    None => *color,
+                };
+                let fb = &mut renderer.framebuffer;
+                software_fill_triangle(fb, &viewport, corners[0], corners[1], corners[2], |w0, w1, w2| {
+                    shade(software_barycentric(uvs[0], uvs[1], uvs[2], w0, w1, w2))
+                });
+                software_fill_triangle(fb, &viewport, corners[0], corners[2], corners[3], |w0, w1, w2| {
+                    shade(software_barycentric(uvs[0], uvs[2], uvs[3], w0, w1, w2))
+                });
+            },
+            RenderCommand::PushTransform { pos, rot, scale } => {
+                let local = render_push_transform_matrix(*pos, *rot, *scale);
+                transforms.push(matrix_3f_multiply(&model, &local));
+            },
+            RenderCommand::PopTransform => {
+                debug_assert!(transforms.len() > 1, "PopTransform without a matching PushTransform");
+                if transforms.len() > 1 {
+                    transforms.pop();
+                }
+            },
+            RenderCommand::SetLayer(layer) => {
+                projection = render_layer_projection(width, height, &buffer.camera, *layer);
+                transforms.truncate(1);
+            },
+        }
+    }
+}
+
+fn software_quad_corners(pos: Vector2f, half_dim: Vector2f) -> [Vector2f; 4] {
+    [
+        vector_2f(pos.x - half_dim.x, pos.y + half_dim.y),
+        vector_2f(pos.x - half_dim.x, pos.y - half_dim.y),
+        vector_2f(pos.x + half_dim.x, pos.y - half_dim.y),
+        vector_2f(pos.x + half_dim.x, pos.y + half_dim.y),
+    ]
+}
+
+fn software_barycentric(a: Vector2f, b: Vector2f, c: Vector2f, w0: f32, w1: f32, w2: f32) -> Vector2f {
+    vector_2f(
+        a.x*w0 + b.x*w1 + c.x*w2,
+        a.y*w0 + b.y*w1 + c.y*w2,
+    )
+}
+
+// Model space point to framebuffer pixel space, y up from the bottom
+fn software_project(viewport: &SoftwareViewport, projection: &Matrix4f, model: &Matrix3f, p: Vector2f) -> Vector2f {
+    let p = matrix_3f_transform_point(model, p);
+    let ndc = matrix_4f_transform_point(projection, vector_3f(p.x, p.y, 0.0));
+    let screen_x = viewport.x as f32 + (ndc.x + 1.0) * 0.5 * viewport.width as f32;
+    let screen_y = viewport.y as f32 + (ndc.y + 1.0) * 0.5 * viewport.height as f32;
+    vector_2f(screen_x, screen_y)
+}
+
+fn software_clear(fb: &mut LoadedBitmap, color: Vector4f) {
+    let pixel = bitmap_pack_color(color);
+    if let Some(data) = fb.data.as_mut() {
+        for p in data.chunks_exact_mut(BITMAP_BYTES_PER_PIXEL as usize) {
+            p.copy_from_slice(&pixel);
+        }
+    }
+}
+
+// Blends a color into the pixel at x, y where y is measured up from the bottom of the framebuffer
+fn software_blend_pixel(fb: &mut LoadedBitmap, viewport: &SoftwareViewport, x: i32, y: i32, color: Vector4f) {
+    if x < viewport.x || x >= viewport.x + viewport.width || y < viewport.y || y >= viewport.y + viewport.height {
+        return;
+    }
+    if x < 0 || x >= fb.width || y < 0 || y >= fb.height {
+        return;
+    }
+    let row = fb.height - 1 - y;
+    let offset = (row * fb.pitch + x * BITMAP_BYTES_PER_PIXEL) as usize;
+    let data = match fb.data.as_mut() {
+        Some(data) => data,
+        None => return,
+    };
+    let alpha = color.w.clamp(0.0, 1.0);
+    let src = [color.x, color.y, color.z];
+    for (i, s) in src.iter().enumerate() {
+        let dst = data[offset + i] as f32 / 255.0;
+        let blended = s.clamp(0.0, 1.0) * alpha + dst * (1.0 - alpha);
+        data[offset + i] = (blended * 255.0 + 0.5) as u8;
+    }
+    data[offset + 3] = 0xFF;
+}
+
+fn software_plot(fb: &mut LoadedBitmap, viewport: &SoftwareViewport, p: Vector2f, color: Vector4f, size: f32) {
+    let size = size.max(1.0) as i32;
+    let min_x = (p.x - size as f32 * 0.5).round() as i32;
+    let min_y = (p.y - size as f32 * 0.5).round() as i32;
+    for y in min_y..min_y + size {
+        for x in min_x..min_x + size {
+            software_blend_pixel(fb, viewport, x, y, color);
+        }
+    }
+}
+
+fn software_draw_line(fb: &mut LoadedBitmap, viewport: &SoftwareViewport, a: Vector2f, b: Vector2f, ca: Vector4f, cb: Vector4f, width: f32) {
+    let delta = vector_2f_sub(b, a);
+    let steps = delta.x.abs().max(delta.y.abs()).ceil().max(1.0) as i32;
+    // don't spend forever on lines that are way off screen
+    let steps = steps.min(4 * (viewport.width + viewport.height));
+    for i in 0..=steps {
+        let t = i as f32 / steps as f32;
+        let p = vector_2f_add(a, vector_2f_scale(delta, t));
+        let c = vector_4f(
+            ca.x + (cb.x - ca.x) * t,
+            ca.y + (cb.y - ca.y) * t,
+            ca.z + (cb.z - ca.z) * t,
+            ca.w + (cb.w - ca.w) * t,
+        );
+        software_plot(fb, viewport, p, c, width);
+    }
+}
+
+fn software_edge(a: Vector2f, b: Vector2f, p: Vector2f) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+// Fills the triangle sampling at pixel centers. shade receives the barycentric weights of a, b and c.
+fn software_fill_triangle<F>(fb: &mut LoadedBitmap, viewport: &SoftwareViewport, a: Vector2f, b: Vector2f, c: Vector2f, shade: F)
+where F: Fn(f32, f32, f32) -> Vector4f {
+    let area = software_edge(a, b, c);
+    if area == 0.0 {
+        return;
+    }
+
+    let min_x = a.x.min(b.x).min(c.x).floor().max(viewport.x as f32) as i32;
+    let max_x = a.x.max(b.x).max(c.x).ceil().min((viewport.x + viewport.width) as f32) as i32;
+    let min_y = a.y.min(b.y).min(c.y).floor().max(viewport.y as f32) as i32;
+    let max_y = a.y.max(b.y).max(c.y).ceil().min((viewport.y + viewport.height) as f32) as i32;
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let p = vector_2f(x as f32 + 0.5, y as f32 + 0.5);
+            let w0 = software_edge(b, c, p) / area;
+            let w1 = software_edge(c, a, p) / area;
+            let w2 = software_edge(a, b, p) / area;
+            if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
+                software_blend_pixel(fb, viewport, x, y, shade(w0, w1, w2));
+            }
+        }
+    }
+}
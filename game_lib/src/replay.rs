@@ -0,0 +1,131 @@
+use crate::input::*;
+use crate::save::*;
+use crate::state::*;
+use crate::stream::*;
+
+// Input recording for looped live editing.
+//
+// A replay file is a snapshot of the GameState when recording started followed by
+// every GameInput the game saw, one record per frame:
+//
+//   magic "MCVR" | version u32 | snapshot size u32 | snapshot bytes | input records ...
+//
+// An input record is
+//
+//   screen width i32 | screen height i32 | frame dt f32 | mouse pos 2 x f32 |
+//   mouse world pos 2 x f32 | mouse wheel f32 | flags u8 | left stick 2 x f32 |
+//   right stick 2 x f32 | left trigger f32 | right trigger f32 | buttons ...
+//
+// with packed state u8 | value f32 per button in input_buttons order. The packed state's
+// high bit is ended_down and the low 7 bits the half transition count. Flags are
+// has_focus, focus_gained, focus_lost, resized and controller connected from the lowest
+// bit up.
+//
+// The snapshot is a save file (see save.rs). Playing it back means restoring the
+// snapshot and feeding the inputs in order.
+
+pub const REPLAY_MAGIC: [u8; 4] = *b"MCVR";
+pub const REPLAY_VERSION: u32 = 6;
+
+const REPLAY_BUTTON_ENDED_DOWN: u8 = 0x80;
+const REPLAY_BUTTON_MAX_TRANSITIONS: u32 = 0x7F;
+
+const REPLAY_WINDOW_HAS_FOCUS: u8 = 0x1;
+const REPLAY_WINDOW_FOCUS_GAINED: u8 = 0x2;
+const REPLAY_WINDOW_FOCUS_LOST: u8 = 0x4;
+const REPLAY_WINDOW_RESIZED: u8 = 0x8;
+const REPLAY_CONTROLLER_CONNECTED: u8 = 0x10;
+
+pub struct Replay {
+    pub snapshot: Vec<u8>,
+    pub inputs: Vec<GameInput>,
+}
+
+pub fn replay_write_header(out: &mut Vec<u8>, ctx: &GameState) {
+    let snapshot = save_write(ctx);
+    stream_write_bytes(out, &REPLAY_MAGIC);
+    stream_write_u32(out, REPLAY_VERSION);
+    stream_write_u32(out, snapshot.len() as u32);
+    stream_write_bytes(out, &snapshot);
+}
+
+pub fn replay_write_input(out: &mut Vec<u8>, input: &GameInput) {
+    stream_write_i32(out, input.screen_width);
+    stream_write_i32(out, input.screen_height);
+    stream_write_f32(out, input.frame_dt_sec);
+    stream_write_vector_2f(out, input.mouse_pos);
+    stream_write_vector_2f(out, input.mouse_world_pos);
+    stream_write_f32(out, input.mouse_wheel);
+    let flags = [
+        (input.has_focus, REPLAY_WINDOW_HAS_FOCUS),
+        (input.focus_gained, REPLAY_WINDOW_FOCUS_GAINED),
+        (input.focus_lost, REPLAY_WINDOW_FOCUS_LOST),
+        (input.resized, REPLAY_WINDOW_RESIZED),
+        (input.controller.connected, REPLAY_CONTROLLER_CONNECTED),
+    ];
+    stream_write_u8(out, flags.iter().filter(|(set, _)| *set).fold(0, |packed, (_, bit)| packed | bit));
+    stream_write_vector_2f(out, input.controller.left_stick);
+    stream_write_vector_2f(out, input.controller.right_stick);
+    stream_write_f32(out, input.controller.left_trigger);
+    stream_write_f32(out, input.controller.right_trigger);
+    for button in input_buttons(input) {
+        let mut packed = button.half_transition_count.min(REPLAY_BUTTON_MAX_TRANSITIONS) as u8;
+        if button.ended_down {
+            packed |= REPLAY_BUTTON_ENDED_DOWN;
+        }
+        stream_write_u8(out, packed);
+        stream_write_f32(out, button.value);
+    }
+}
+
+fn replay_read_input(reader: &mut StreamReader) -> Option<GameInput> {
+    let mut input = GameInput {
+        screen_width: stream_read_i32(reader)?,
+        screen_height: stream_read_i32(reader)?,
+        frame_dt_sec: stream_read_f32(reader)?,
+        mouse_pos: stream_read_vector_2f(reader)?,
+        mouse_world_pos: stream_read_vector_2f(reader)?,
+        mouse_wheel: stream_read_f32(reader)?,
+        ..Default::default()
+    };
+    let flags = stream_read_u8(reader)?;
+    input.has_focus = flags & REPLAY_WINDOW_HAS_FOCUS != 0;
+    input.focus_gained = flags & REPLAY_WINDOW_FOCUS_GAINED != 0;
+    input.focus_lost = flags & REPLAY_WINDOW_FOCUS_LOST != 0;
+    input.resized = flags & REPLAY_WINDOW_RESIZED != 0;
+    input.controller.connected = flags & REPLAY_CONTROLLER_CONNECTED != 0;
+    input.controller.left_stick = stream_read_vector_2f(reader)?;
+    input.controller.right_stick = stream_read_vector_2f(reader)?;
+    input.controller.left_trigger = stream_read_f32(reader)?;
+    input.controller.right_trigger = stream_read_f32(reader)?;
+    for button in input_buttons_mut(&mut input) {
+        let packed = stream_read_u8(reader)?;
+        *button = GameButton {
+            ended_down: packed & REPLAY_BUTTON_ENDED_DOWN != 0,
+            half_transition_count: (packed & !REPLAY_BUTTON_ENDED_DOWN) as u32,
+            value: stream_read_f32(reader)?,
+        };
+    }
+    Some(input)
+}
+
+// Returns None if the bytes aren't a replay this version understands.
+// A partially written last input record (crash while recording) is dropped.
+pub fn replay_read(bytes: &[u8]) -> Option<Replay> {
+    let mut reader = stream_reader(bytes);
+    if stream_read_bytes(&mut reader, 4)? != REPLAY_MAGIC {
+        return None;
+    }
+    if stream_read_u32(&mut reader)? != REPLAY_VERSION {
+        return None;
+    }
+    let snapshot_size = stream_read_u32(&mut reader)? as usize;
+    let snapshot = stream_read_bytes(&mut reader, snapshot_size)?.to_vec();
+
+    let mut inputs = Vec::new();
+    while let Some(input) = replay_read_input(&mut reader) {
+        inputs.push(input);
+    }
+
+    Some(Replay { snapshot, inputs })
+}
@@ -0,0 +1,657 @@
+use crate::math::*;
+use crate::projectile::*;
+use crate::random::*;
+use crate::state::*;
+use crate::stream::*;
+
+// Versioned save files for the full GameState.
+//
+//   magic "MCVS" | version u32 | fields ...
+//
+// Every value is written as a tagged field:
+//
+//   field id u16 | size u32 | bytes
+//
+// Structs are fields whose bytes are more fields. Readers skip field ids they don't know
+// and leave the default value for fields that are missing, so adding a field never needs
+// a new version. SAVE_VERSION only goes up when an existing field changes meaning, and
+// files from any other version are rejected instead of being misread.
+//
+// The last field is an empty SAVE_GAME_END, so a file cut off between two fields is
+// caught instead of loading with the rest missing.
+
+pub const SAVE_MAGIC: [u8; 4] = *b"MCVS";
+pub const SAVE_VERSION: u32 = 3;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    // The bytes don't start with SAVE_MAGIC
+    NotASaveFile,
+    UnsupportedVersion { found: u32, supported: u32 },
+    // What was being read when the data ran out or made no sense
+    Corrupt(&'static str),
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "save file io error: {}", e),
+            SaveError::NotASaveFile => write!(f, "not a save file"),
+            SaveError::UnsupportedVersion { found, supported } => {
+                write!(f, "save file version {} is not the supported version {}", found, supported)
+            },
+            SaveError::Corrupt(what) => write!(f, "save file is corrupt: bad {}", what),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+// Field ids, never reuse or renumber one that has shipped
+const SAVE_GAME_END: u16            = 0;
+const SAVE_GAME_SEED: u16           = 1;
+const SAVE_GAME_RNG_STATE: u16      = 2;
+const SAVE_GAME_SIM_STEP: u16       = 3;
+const SAVE_GAME_SIM_ACCUMULATOR: u16 = 4;
+const SAVE_GAME_SIM_ALPHA: u16      = 5;
+const SAVE_GAME_PLAYER: u16         = 6;
+const SAVE_GAME_SHIP: u16           = 7;
+const SAVE_GAME_SUN: u16            = 8;
+const SAVE_GAME_PLANETS: u16        = 9;
+const SAVE_GAME_STARS: u16          = 10;
+const SAVE_GAME_TITLE_FADE: u16     = 11;
+const SAVE_GAME_NAV_PATH: u16       = 12;
+const SAVE_GAME_DEBUG_FORCES: u16   = 13;
+const SAVE_GAME_INITIALIZED: u16    = 14;
+const SAVE_GAME_GENERATE_PLANETS: u16 = 15;
+const SAVE_GAME_LANDING_OUTCOME: u16 = 16;
+const SAVE_GAME_LANDING_OUTCOME_SEC: u16 = 17;
+const SAVE_GAME_SCORE: u16          = 18;
+const SAVE_GAME_LIVES: u16          = 19;
+const SAVE_GAME_SPAWN_POS: u16      = 20;
+const SAVE_GAME_SPAWN_ROT: u16      = 21;
+const SAVE_GAME_LAST_LANDING_PLANET: u16 = 22;
+const SAVE_GAME_RESPAWN_SEC: u16    = 23;
+const SAVE_GAME_STRANDED_SEC: u16   = 24;
+const SAVE_GAME_MODE: u16           = 25;
+const SAVE_GAME_MODE_SEC: u16       = 26;
+const SAVE_GAME_LEVEL_NUMBER: u16   = 27;
+const SAVE_GAME_PROJECTILES: u16    = 28;
+const SAVE_GAME_LAUNCH_QUEUED: u16  = 29;
+const SAVE_GAME_SIM_TIME: u16       = 30;
+const SAVE_GAME_GRAVITY_G: u16      = 31;
+
+const SAVE_PLAYER_ROT: u16      = 1;
+const SAVE_PLAYER_POS: u16      = 2;
+const SAVE_PLAYER_PREV_ROT: u16 = 3;
+const SAVE_PLAYER_PREV_POS: u16 = 4;
+const SAVE_PLAYER_D_POS: u16    = 5;
+const SAVE_PLAYER_DD_POS: u16   = 6;
+const SAVE_PLAYER_LANDED: u16   = 7;
+
+const SAVE_SHIP_FUEL_LEVEL: u16     = 1;
+const SAVE_SHIP_FUEL_BURN_RATE: u16 = 2;
+const SAVE_SHIP_HULL: u16           = 3;
+const SAVE_SHIP_INVENTORY_CAPACITY: u16 = 4;
+// written once per carried item
+const SAVE_SHIP_INVENTORY_ITEM: u16 = 5;
+const SAVE_SHIP_DESTROYED: u16      = 6;
+
+const SAVE_SUN_POS: u16       = 1;
+const SAVE_SUN_MASS: u16      = 2;
+const SAVE_SUN_SOFTENING: u16 = 3;
+
+// Lists are a field holding one SAVE_LIST_ITEM field per element
+const SAVE_LIST_ITEM: u16 = 1;
+
+const SAVE_PLANET_RADIUS: u16         = 1;
+const SAVE_PLANET_POS: u16            = 2;
+const SAVE_PLANET_COLOR: u16          = 3;
+const SAVE_PLANET_G_RADIUS: u16       = 4;
+const SAVE_PLANET_SURFACE_RADIUS: u16 = 5;
+const SAVE_PLANET_LZ_REL_POS: u16     = 6;
+const SAVE_PLANET_LZ_COLOR: u16       = 7;
+// written once per item
+const SAVE_PLANET_ITEM: u16           = 8;
+const SAVE_PLANET_MASS: u16           = 9;
+const SAVE_PLANET_PREV_POS: u16       = 10;
+const SAVE_PLANET_D_POS: u16          = 11;
+// left out for planets that keep still
+const SAVE_PLANET_ORBIT: u16          = 12;
+
+// parent is 0 for the sun and the planet index plus 1 otherwise
+const SAVE_ORBIT_PARENT: u16        = 1;
+const SAVE_ORBIT_SEMI_MAJOR: u16    = 2;
+const SAVE_ORBIT_ECCENTRICITY: u16  = 3;
+const SAVE_ORBIT_PERIAPSIS_ROT: u16 = 4;
+const SAVE_ORBIT_PHASE: u16         = 5;
+const SAVE_ORBIT_CLOCKWISE: u16     = 6;
+
+const SAVE_ITEM_TYPE: u16 = 1;
+const SAVE_ITEM_POS: u16  = 2;
+
+// Stars and forces are big flat arrays so they're packed as count | stride | elements.
+// The stride lets an older reader skip members appended to each element.
+const SAVE_STAR_STRIDE: u32   = 12;
+const SAVE_VECTOR_STRIDE: u32 = 8;
+
+const SAVE_PROJECTILE_POS: u16      = 1;
+const SAVE_PROJECTILE_PREV_POS: u16 = 2;
+const SAVE_PROJECTILE_D_POS: u16    = 3;
+const SAVE_PROJECTILE_AGE: u16      = 4;
+
+const SAVE_NAV_POINT_P: u16 = 1;
+const SAVE_NAV_POINT_C: u16 = 2;
+
+//
+// Writing
+//
+
+fn save_field<F: FnOnce(&mut Vec<u8>)>(out: &mut Vec<u8>, id: u16, write: F) {
+    stream_write_u16(out, id);
+    let size_at = out.len();
+    stream_write_u32(out, 0);
+    write(out);
+    let size = (out.len() - size_at - 4) as u32;
+    out[size_at..size_at + 4].copy_from_slice(&size.to_le_bytes());
+}
+
+fn save_field_f32(out: &mut Vec<u8>, id: u16, v: f32) {
+    save_field(out, id, |out| stream_write_f32(out, v));
+}
+
+fn save_field_vector_2f(out: &mut Vec<u8>, id: u16, v: Vector2f) {
+    save_field(out, id, |out| stream_write_vector_2f(out, v));
+}
+
+fn save_field_vector_4f(out: &mut Vec<u8>, id: u16, v: Vector4f) {
+    save_field(out, id, |out| stream_write_vector_4f(out, v));
+}
+
+fn save_field_list<T>(out: &mut Vec<u8>, id: u16, items: &[T], write: fn(&mut Vec<u8>, &T)) {
+    save_field(out, id, |out| {
+        for item in items {
+            save_field(out, SAVE_LIST_ITEM, |out| write(out, item));
+        }
+    });
+}
+
+fn save_write_planet(out: &mut Vec<u8>, p: &Planet) {
+    save_field_f32(out, SAVE_PLANET_RADIUS, p.radius);
+    save_field_vector_2f(out, SAVE_PLANET_POS, p.pos);
+    save_field_vector_4f(out, SAVE_PLANET_COLOR, p.color);
+    save_field_f32(out, SAVE_PLANET_G_RADIUS, p.g_radius);
+    save_field_f32(out, SAVE_PLANET_MASS, p.mass);
+    save_field_f32(out, SAVE_PLANET_SURFACE_RADIUS, p.surface_radius);
+    save_field_vector_2f(out, SAVE_PLANET_LZ_REL_POS, p.lz_rel_pos);
+    save_field_vector_4f(out, SAVE_PLANET_LZ_COLOR, p.lz_color);
+    for item in p.items.iter() {
+        save_field(out, SAVE_PLANET_ITEM, |out| save_write_item(out, item));
+    }
+    save_field_vector_2f(out, SAVE_PLANET_PREV_POS, p.prev_pos);
+    save_field_vector_2f(out, SAVE_PLANET_D_POS, p.d_pos);
+    if let Some(orbit) = &p.orbit {
+        save_field(out, SAVE_PLANET_ORBIT, |out| save_write_orbit(out, orbit));
+    }
+}
+
+fn save_write_orbit(out: &mut Vec<u8>, orbit: &Orbit) {
+    let parent = match orbit.parent {
+        OrbitParent::Sun => 0,
+        OrbitParent::Planet(i) => i as u32 + 1,
+    };
+    save_field(out, SAVE_ORBIT_PARENT, |out| stream_write_u32(out, parent));
+    save_field_f32(out, SAVE_ORBIT_SEMI_MAJOR, orbit.semi_major);
+    save_field_f32(out, SAVE_ORBIT_ECCENTRICITY, orbit.eccentricity);
+    save_field_f32(out, SAVE_ORBIT_PERIAPSIS_ROT, orbit.periapsis_rot);
+    save_field_f32(out, SAVE_ORBIT_PHASE, orbit.phase);
+    save_field(out, SAVE_ORBIT_CLOCKWISE, |out| stream_write_bool(out, orbit.clockwise));
+}
+
+fn save_write_item(out: &mut Vec<u8>, item: &PlanetItem) {
+    save_field(out, SAVE_ITEM_TYPE, |out| stream_write_u8(out, planet_item_type_to_u8(&item.itype)));
+    save_field_vector_2f(out, SAVE_ITEM_POS, item.pos);
+}
+
+// only live projectiles are written, the pool refills as they're fired
+fn save_write_projectile(out: &mut Vec<u8>, p: &Projectile) {
+    save_field_vector_2f(out, SAVE_PROJECTILE_POS, p.pos);
+    save_field_vector_2f(out, SAVE_PROJECTILE_PREV_POS, p.prev_pos);
+    save_field_vector_2f(out, SAVE_PROJECTILE_D_POS, p.d_pos);
+    save_field_f32(out, SAVE_PROJECTILE_AGE, p.age_sec);
+}
+
+fn save_write_nav_point(out: &mut Vec<u8>, p: &NavPoint) {
+    save_field_vector_2f(out, SAVE_NAV_POINT_P, p.p);
+    save_field(out, SAVE_NAV_POINT_C, |out| stream_write_vector_3f(out, p.c));
+}
+
+fn planet_item_type_to_u8(itype: &PlanetItemType) -> u8 {
+    match itype {
+        PlanetItemType::None => 0,
+        PlanetItemType::Fuel => 1,
+        PlanetItemType::Repair => 2,
+        PlanetItemType::Cargo => 3,
+        PlanetItemType::Key => 4,
+        PlanetItemType::Gem => 5,
+    }
+}
+
+fn planet_item_type_from_u8(v: u8) -> PlanetItemType {
+    match v {
+        1 => PlanetItemType::Fuel,
+        2 => PlanetItemType::Repair,
+        3 => PlanetItemType::Cargo,
+        4 => PlanetItemType::Key,
+        5 => PlanetItemType::Gem,
+        // item types from a newer version are dropped rather than failing the whole load
+        _ => PlanetItemType::None,
+    }
+}
+
+fn game_mode_to_u8(mode: GameMode) -> u8 {
+    match mode {
+        GameMode::Title => 0,
+        GameMode::Playing => 1,
+        GameMode::Paused => 2,
+        GameMode::LevelComplete => 3,
+        GameMode::GameOver => 4,
+    }
+}
+
+fn game_mode_from_u8(v: u8) -> GameMode {
+    match v {
+        1 => GameMode::Playing,
+        2 => GameMode::Paused,
+        3 => GameMode::LevelComplete,
+        4 => GameMode::GameOver,
+        _ => GameMode::Title,
+    }
+}
+
+fn landing_outcome_to_u8(outcome: LandingOutcome) -> u8 {
+    match outcome {
+        LandingOutcome::None => 0,
+        LandingOutcome::Landed => 1,
+        LandingOutcome::TouchedDown => 2,
+        LandingOutcome::Crashed => 3,
+    }
+}
+
+fn landing_outcome_from_u8(v: u8) -> LandingOutcome {
+    match v {
+        1 => LandingOutcome::Landed,
+        2 => LandingOutcome::TouchedDown,
+        3 => LandingOutcome::Crashed,
+        _ => LandingOutcome::None,
+    }
+}
+
+pub fn save_write(ctx: &GameState) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let out = &mut bytes;
+    stream_write_bytes(out, &SAVE_MAGIC);
+    stream_write_u32(out, SAVE_VERSION);
+
+    save_field(out, SAVE_GAME_INITIALIZED, |out| stream_write_bool(out, ctx.initialized));
+    save_field(out, SAVE_GAME_MODE, |out| stream_write_u8(out, game_mode_to_u8(ctx.mode)));
+    save_field_f32(out, SAVE_GAME_MODE_SEC, ctx.mode_sec);
+    save_field(out, SAVE_GAME_LEVEL_NUMBER, |out| stream_write_u32(out, ctx.level_number));
+    save_field(out, SAVE_GAME_SEED, |out| stream_write_u64(out, ctx.seed));
+    save_field(out, SAVE_GAME_GENERATE_PLANETS, |out| stream_write_bool(out, ctx.generate_planets));
+    save_field(out, SAVE_GAME_RNG_STATE, |out| stream_write_u64(out, ctx.rng.state));
+    save_field_f32(out, SAVE_GAME_SIM_STEP, ctx.sim_step_sec);
+    save_field_f32(out, SAVE_GAME_SIM_ACCUMULATOR, ctx.sim_accumulator_sec);
+    save_field_f32(out, SAVE_GAME_SIM_ALPHA, ctx.sim_alpha);
+    save_field_f32(out, SAVE_GAME_SIM_TIME, ctx.sim_time_sec);
+    save_field_f32(out, SAVE_GAME_GRAVITY_G, ctx.gravity_g);
+
+    let p = &ctx.player;
+    save_field(out, SAVE_GAME_PLAYER, |out| {
+        save_field_f32(out, SAVE_PLAYER_ROT, p.rot);
+        save_field_vector_2f(out, SAVE_PLAYER_POS, p.pos);
+        save_field_f32(out, SAVE_PLAYER_PREV_ROT, p.prev_rot);
+        save_field_vector_2f(out, SAVE_PLAYER_PREV_POS, p.prev_pos);
+        save_field_vector_2f(out, SAVE_PLAYER_D_POS, p.d_pos);
+        save_field_f32(out, SAVE_PLAYER_DD_POS, p.dd_pos);
+        save_field(out, SAVE_PLAYER_LANDED, |out| stream_write_bool(out, p.landed));
+    });
+
+    save_field(out, SAVE_GAME_SHIP, |out| {
+        save_field_f32(out, SAVE_SHIP_FUEL_LEVEL, ctx.ship.fuel_level);
+        save_field_f32(out, SAVE_SHIP_FUEL_BURN_RATE, ctx.ship.fuel_burn_rate);
+        save_field_f32(out, SAVE_SHIP_HULL, ctx.ship.hull);
+        save_field(out, SAVE_SHIP_INVENTORY_CAPACITY, |out| stream_write_u32(out, ctx.ship.inventory_capacity));
+        for itype in ctx.ship.inventory.iter() {
+            save_field(out, SAVE_SHIP_INVENTORY_ITEM, |out| stream_write_u8(out, planet_item_type_to_u8(itype)));
+        }
+        save_field(out, SAVE_SHIP_DESTROYED, |out| stream_write_bool(out, ctx.ship.destroyed));
+    });
+
+    save_field(out, SAVE_GAME_SUN, |out| {
+        save_field_vector_2f(out, SAVE_SUN_POS, ctx.sun.pos);
+        save_field_f32(out, SAVE_SUN_MASS, ctx.sun.mass);
+        save_field_f32(out, SAVE_SUN_SOFTENING, ctx.sun.softening);
+    });
+
+    if let Some(planets) = &ctx.planets {
+        save_field_list(out, SAVE_GAME_PLANETS, planets, save_write_planet);
+    }
+
+    if let Some(stars) = &ctx.space_stars {
+        save_field(out, SAVE_GAME_STARS, |out| {
+            stream_write_u32(out, stars.len() as u32);
+            stream_write_u32(out, SAVE_STAR_STRIDE);
+            for s in stars {
+                stream_write_vector_2f(out, s.pos);
+                stream_write_f32(out, s.size);
+            }
+        });
+    }
+
+    save_field(out, SAVE_GAME_LANDING_OUTCOME, |out| stream_write_u8(out, landing_outcome_to_u8(ctx.landing_outcome)));
+    save_field_f32(out, SAVE_GAME_LANDING_OUTCOME_SEC, ctx.landing_outcome_sec);
+    save_field(out, SAVE_GAME_SCORE, |out| stream_write_u32(out, ctx.score));
+    save_field(out, SAVE_GAME_LIVES, |out| stream_write_u32(out, ctx.lives));
+    save_field_vector_2f(out, SAVE_GAME_SPAWN_POS, ctx.spawn_pos);
+    save_field_f32(out, SAVE_GAME_SPAWN_ROT, ctx.spawn_rot);
+    // left out when the ship hasn't landed anywhere yet
+    if let Some(i) = ctx.last_landing_planet {
+        save_field(out, SAVE_GAME_LAST_LANDING_PLANET, |out| stream_write_u32(out, i as u32));
+    }
+    save_field_f32(out, SAVE_GAME_RESPAWN_SEC, ctx.respawn_sec);
+    save_field_f32(out, SAVE_GAME_STRANDED_SEC, ctx.stranded_sec);
+    let projectiles: Vec<Projectile> = ctx.projectiles.iter().filter(|p| p.alive).copied().collect();
+    save_field_list(out, SAVE_GAME_PROJECTILES, &projectiles, save_write_projectile);
+    save_field(out, SAVE_GAME_LAUNCH_QUEUED, |out| stream_write_bool(out, ctx.launch_queued));
+    save_field_f32(out, SAVE_GAME_TITLE_FADE, ctx.title_fade);
+
+    if let Some(path) = &ctx.nav_path {
+        save_field_list(out, SAVE_GAME_NAV_PATH, &path.points, save_write_nav_point);
+    }
+
+    if let Some(forces) = &ctx.debug_player_forces {
+        save_field(out, SAVE_GAME_DEBUG_FORCES, |out| {
+            stream_write_u32(out, forces.len() as u32);
+            stream_write_u32(out, SAVE_VECTOR_STRIDE);
+            for f in forces {
+                stream_write_vector_2f(out, *f);
+            }
+        });
+    }
+
+    save_field(out, SAVE_GAME_END, |_| {});
+    bytes
+}
+
+pub fn save_write_file(ctx: &GameState, path: &std::path::Path) -> Result<(), SaveError> {
+    std::fs::write(path, save_write(ctx))?;
+    Ok(())
+}
+
+//
+// Reading
+//
+
+struct SaveField<'a> {
+    id: u16,
+    bytes: &'a [u8],
+}
+
+fn save_read_fields<'a>(bytes: &'a [u8], what: &'static str) -> Result<Vec<SaveField<'a>>, SaveError> {
+    let mut reader = stream_reader(bytes);
+    let mut fields = Vec::new();
+    while stream_remaining(&reader) > 0 {
+        let id = stream_read_u16(&mut reader).ok_or(SaveError::Corrupt(what))?;
+        let size = stream_read_u32(&mut reader).ok_or(SaveError::Corrupt(what))?;
+        let bytes = stream_read_bytes(&mut reader, size as usize).ok_or(SaveError::Corrupt(what))?;
+        fields.push(SaveField { id, bytes });
+    }
+    Ok(fields)
+}
+
+fn save_value<T>(field: &SaveField, what: &'static str, read: fn(&mut StreamReader) -> Option<T>) -> Result<T, SaveError> {
+    read(&mut stream_reader(field.bytes)).ok_or(SaveError::Corrupt(what))
+}
+
+fn save_read_list<T>(field: &SaveField, what: &'static str, read: fn(&[u8]) -> Result<T, SaveError>) -> Result<Vec<T>, SaveError> {
+    let mut items = Vec::new();
+    for item in save_read_fields(field.bytes, what)? {
+        if item.id == SAVE_LIST_ITEM {
+            items.push(read(item.bytes)?);
+        }
+    }
+    Ok(items)
+}
+
+// count | stride | elements, where each element is at least min_stride bytes
+fn save_read_packed<T>(field: &SaveField, what: &'static str, min_stride: u32, read: fn(&mut StreamReader) -> Option<T>) -> Result<Vec<T>, SaveError> {
+    let reader = &mut stream_reader(field.bytes);
+    let count = stream_read_u32(reader).ok_or(SaveError::Corrupt(what))?;
+    let stride = stream_read_u32(reader).ok_or(SaveError::Corrupt(what))?;
+    let total = (count as u64) * (stride as u64);
+    if stride < min_stride || total > stream_remaining(reader) as u64 {
+        return Err(SaveError::Corrupt(what));
+    }
+    let mut items = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let element = stream_read_bytes(reader, stride as usize).ok_or(SaveError::Corrupt(what))?;
+        items.push(read(&mut stream_reader(element)).ok_or(SaveError::Corrupt(what))?);
+    }
+    Ok(items)
+}
+
+fn save_read_player(bytes: &[u8]) -> Result<Player, SaveError> {
+    let mut p = Player::default();
+    for field in save_read_fields(bytes, "player")? {
+        match field.id {
+            SAVE_PLAYER_ROT      => p.rot = save_value(&field, "player rotation", stream_read_f32)?,
+            SAVE_PLAYER_POS      => p.pos = save_value(&field, "player position", stream_read_vector_2f)?,
+            SAVE_PLAYER_PREV_ROT => p.prev_rot = save_value(&field, "player rotation", stream_read_f32)?,
+            SAVE_PLAYER_PREV_POS => p.prev_pos = save_value(&field, "player position", stream_read_vector_2f)?,
+            SAVE_PLAYER_D_POS    => p.d_pos = save_value(&field, "player velocity", stream_read_vector_2f)?,
+            SAVE_PLAYER_DD_POS   => p.dd_pos = save_value(&field, "player acceleration", stream_read_f32)?,
+            SAVE_PLAYER_LANDED   => p.landed = save_value(&field, "player landed", stream_read_bool)?,
+            _ => {},
+        }
+    }
+    Ok(p)
+}
+
+fn save_read_ship(bytes: &[u8]) -> Result<Ship, SaveError> {
+    let mut ship = Ship::default();
+    for field in save_read_fields(bytes, "ship")? {
+        match field.id {
+            SAVE_SHIP_FUEL_LEVEL     => ship.fuel_level = save_value(&field, "ship fuel", stream_read_f32)?,
+            SAVE_SHIP_FUEL_BURN_RATE => ship.fuel_burn_rate = save_value(&field, "ship fuel", stream_read_f32)?,
+            SAVE_SHIP_HULL           => ship.hull = save_value(&field, "ship hull", stream_read_f32)?,
+            SAVE_SHIP_INVENTORY_CAPACITY => ship.inventory_capacity = save_value(&field, "ship inventory capacity", stream_read_u32)?,
+            SAVE_SHIP_INVENTORY_ITEM => ship.inventory.push(planet_item_type_from_u8(save_value(&field, "ship inventory item", stream_read_u8)?)),
+            SAVE_SHIP_DESTROYED      => ship.destroyed = save_value(&field, "ship destroyed", stream_read_bool)?,
+            _ => {},
+        }
+    }
+    if ship.inventory.len() > ship.inventory_capacity as usize {
+        return Err(SaveError::Corrupt("ship inventory count"));
+    }
+    Ok(ship)
+}
+
+fn save_read_sun(bytes: &[u8]) -> Result<Sun, SaveError> {
+    let mut sun = Sun::default();
+    for field in save_read_fields(bytes, "sun")? {
+        match field.id {
+            SAVE_SUN_POS       => sun.pos = save_value(&field, "sun position", stream_read_vector_2f)?,
+            SAVE_SUN_MASS      => sun.mass = save_value(&field, "sun mass", stream_read_f32)?,
+            SAVE_SUN_SOFTENING => sun.softening = save_value(&field, "sun softening", stream_read_f32)?,
+            _ => {},
+        }
+    }
+    Ok(sun)
+}
+
+fn save_read_item(bytes: &[u8]) -> Result<PlanetItem, SaveError> {
+    let mut item = PlanetItem::default();
+    for field in save_read_fields(bytes, "planet item")? {
+        match field.id {
+            SAVE_ITEM_TYPE => item.itype = planet_item_type_from_u8(save_value(&field, "planet item type", stream_read_u8)?),
+            SAVE_ITEM_POS  => item.pos = save_value(&field, "planet item position", stream_read_vector_2f)?,
+            _ => {},
+        }
+    }
+    Ok(item)
+}
+
+fn save_read_orbit(bytes: &[u8]) -> Result<Orbit, SaveError> {
+    let mut orbit = Orbit { parent: OrbitParent::Sun, semi_major: 0.0, eccentricity: 0.0, periapsis_rot: 0.0, phase: 0.0, clockwise: false };
+    for field in save_read_fields(bytes, "planet orbit")? {
+        match field.id {
+            SAVE_ORBIT_PARENT => {
+                orbit.parent = match save_value(&field, "planet orbit parent", stream_read_u32)? {
+                    0 => OrbitParent::Sun,
+                    i => OrbitParent::Planet(i as usize - 1),
+                };
+            },
+            SAVE_ORBIT_SEMI_MAJOR    => orbit.semi_major = save_value(&field, "planet orbit size", stream_read_f32)?,
+            SAVE_ORBIT_ECCENTRICITY  => orbit.eccentricity = save_value(&field, "planet orbit eccentricity", stream_read_f32)?,
+            SAVE_ORBIT_PERIAPSIS_ROT => orbit.periapsis_rot = save_value(&field, "planet orbit rotation", stream_read_f32)?,
+            SAVE_ORBIT_PHASE         => orbit.phase = save_value(&field, "planet orbit phase", stream_read_f32)?,
+            SAVE_ORBIT_CLOCKWISE     => orbit.clockwise = save_value(&field, "planet orbit direction", stream_read_bool)?,
+            _ => {},
+        }
+    }
+    Ok(orbit)
+}
+
+fn save_read_planet(bytes: &[u8]) -> Result<Planet, SaveError> {
+    let mut p = Planet::default();
+    for field in save_read_fields(bytes, "planet")? {
+        match field.id {
+            SAVE_PLANET_RADIUS         => p.radius = save_value(&field, "planet radius", stream_read_f32)?,
+            SAVE_PLANET_POS            => p.pos = save_value(&field, "planet position", stream_read_vector_2f)?,
+            SAVE_PLANET_COLOR          => p.color = save_value(&field, "planet color", stream_read_vector_4f)?,
+            SAVE_PLANET_G_RADIUS       => p.g_radius = save_value(&field, "planet gravity radius", stream_read_f32)?,
+            SAVE_PLANET_MASS           => p.mass = save_value(&field, "planet mass", stream_read_f32)?,
+            SAVE_PLANET_PREV_POS       => p.prev_pos = save_value(&field, "planet position", stream_read_vector_2f)?,
+            SAVE_PLANET_D_POS          => p.d_pos = save_value(&field, "planet velocity", stream_read_vector_2f)?,
+            SAVE_PLANET_ORBIT          => p.orbit = Some(save_read_orbit(field.bytes)?),
+            SAVE_PLANET_SURFACE_RADIUS => p.surface_radius = save_value(&field, "planet surface radius", stream_read_f32)?,
+            SAVE_PLANET_LZ_REL_POS     => p.lz_rel_pos = save_value(&field, "planet landing zone", stream_read_vector_2f)?,
+            SAVE_PLANET_LZ_COLOR       => p.lz_color = save_value(&field, "planet landing zone color", stream_read_vector_4f)?,
+            SAVE_PLANET_ITEM           => {
+                if p.items.len() >= MAX_PLANET_ITEMS {
+                    return Err(SaveError::Corrupt("planet item count"));
+                }
+                p.items.push(save_read_item(field.bytes)?);
+            },
+            _ => {},
+        }
+    }
+    Ok(p)
+}
+
+fn save_read_projectile(bytes: &[u8]) -> Result<Projectile, SaveError> {
+    let mut p = Projectile { alive: true, ..Projectile::default() };
+    for field in save_read_fields(bytes, "projectile")? {
+        match field.id {
+            SAVE_PROJECTILE_POS      => p.pos = save_value(&field, "projectile position", stream_read_vector_2f)?,
+            SAVE_PROJECTILE_PREV_POS => p.prev_pos = save_value(&field, "projectile position", stream_read_vector_2f)?,
+            SAVE_PROJECTILE_D_POS    => p.d_pos = save_value(&field, "projectile velocity", stream_read_vector_2f)?,
+            SAVE_PROJECTILE_AGE      => p.age_sec = save_value(&field, "projectile age", stream_read_f32)?,
+            _ => {},
+        }
+    }
+    Ok(p)
+}
+
+fn save_read_nav_point(bytes: &[u8]) -> Result<NavPoint, SaveError> {
+    let mut point = NavPoint::new(vector_2f_zero(), vector_3f(0.0, 0.0, 0.0));
+    for field in save_read_fields(bytes, "nav point")? {
+        match field.id {
+            SAVE_NAV_POINT_P => point.p = save_value(&field, "nav point position", stream_read_vector_2f)?,
+            SAVE_NAV_POINT_C => point.c = save_value(&field, "nav point color", stream_read_vector_3f)?,
+            _ => {},
+        }
+    }
+    Ok(point)
+}
+
+fn save_read_star(reader: &mut StreamReader) -> Option<Star> {
+    Some(Star {
+        pos: stream_read_vector_2f(reader)?,
+        size: stream_read_f32(reader)?,
+    })
+}
+
+pub fn save_read(bytes: &[u8]) -> Result<GameState, SaveError> {
+    let mut reader = stream_reader(bytes);
+    match stream_read_bytes(&mut reader, 4) {
+        Some(magic) if magic == SAVE_MAGIC => {},
+        _ => return Err(SaveError::NotASaveFile),
+    }
+    let version = stream_read_u32(&mut reader).ok_or(SaveError::Corrupt("version"))?;
+    if version != SAVE_VERSION {
+        return Err(SaveError::UnsupportedVersion { found: version, supported: SAVE_VERSION });
+    }
+
+    let mut ctx = GameState::default();
+    let mut ended = false;
+    for field in save_read_fields(&bytes[reader.at..], "game state")? {
+        match field.id {
+            SAVE_GAME_END             => ended = true,
+            SAVE_GAME_INITIALIZED     => ctx.initialized = save_value(&field, "initialized", stream_read_bool)?,
+            SAVE_GAME_MODE            => ctx.mode = game_mode_from_u8(save_value(&field, "game mode", stream_read_u8)?),
+            SAVE_GAME_MODE_SEC        => ctx.mode_sec = save_value(&field, "game mode time", stream_read_f32)?,
+            SAVE_GAME_LEVEL_NUMBER    => ctx.level_number = save_value(&field, "level number", stream_read_u32)?,
+            SAVE_GAME_GENERATE_PLANETS => ctx.generate_planets = save_value(&field, "generate planets", stream_read_bool)?,
+            SAVE_GAME_SEED            => ctx.seed = save_value(&field, "seed", stream_read_u64)?,
+            SAVE_GAME_RNG_STATE       => ctx.rng = RandomSeries { state: save_value(&field, "random state", stream_read_u64)? },
+            SAVE_GAME_SIM_STEP        => ctx.sim_step_sec = save_value(&field, "sim step", stream_read_f32)?,
+            SAVE_GAME_SIM_ACCUMULATOR => ctx.sim_accumulator_sec = save_value(&field, "sim accumulator", stream_read_f32)?,
+            SAVE_GAME_SIM_ALPHA       => ctx.sim_alpha = save_value(&field, "sim alpha", stream_read_f32)?,
+            SAVE_GAME_SIM_TIME        => ctx.sim_time_sec = save_value(&field, "sim time", stream_read_f32)?,
+            SAVE_GAME_GRAVITY_G       => ctx.gravity_g = save_value(&field, "gravity constant", stream_read_f32)?,
+            SAVE_GAME_PLAYER          => ctx.player = save_read_player(field.bytes)?,
+            SAVE_GAME_SHIP            => ctx.ship = save_read_ship(field.bytes)?,
+            SAVE_GAME_SUN             => ctx.sun = save_read_sun(field.bytes)?,
+            SAVE_GAME_PLANETS         => ctx.planets = Some(save_read_list(&field, "planets", save_read_planet)?),
+            SAVE_GAME_STARS           => ctx.space_stars = Some(save_read_packed(&field, "stars", SAVE_STAR_STRIDE, save_read_star)?),
+            SAVE_GAME_LANDING_OUTCOME => ctx.landing_outcome = landing_outcome_from_u8(save_value(&field, "landing outcome", stream_read_u8)?),
+            SAVE_GAME_LANDING_OUTCOME_SEC => ctx.landing_outcome_sec = save_value(&field, "landing outcome time", stream_read_f32)?,
+            SAVE_GAME_SCORE           => ctx.score = save_value(&field, "score", stream_read_u32)?,
+            SAVE_GAME_LIVES           => ctx.lives = save_value(&field, "lives", stream_read_u32)?,
+            SAVE_GAME_SPAWN_POS       => ctx.spawn_pos = save_value(&field, "spawn position", stream_read_vector_2f)?,
+            SAVE_GAME_SPAWN_ROT       => ctx.spawn_rot = save_value(&field, "spawn rotation", stream_read_f32)?,
+            SAVE_GAME_LAST_LANDING_PLANET => ctx.last_landing_planet = Some(save_value(&field, "last landing planet", stream_read_u32)? as usize),
+            SAVE_GAME_RESPAWN_SEC     => ctx.respawn_sec = save_value(&field, "respawn time", stream_read_f32)?,
+            SAVE_GAME_STRANDED_SEC    => ctx.stranded_sec = save_value(&field, "stranded time", stream_read_f32)?,
+            SAVE_GAME_PROJECTILES     => {
+                ctx.projectiles = save_read_list(&field, "projectiles", save_read_projectile)?;
+                if ctx.projectiles.len() > MAX_PROJECTILES {
+                    return Err(SaveError::Corrupt("projectile count"));
+                }
+            },
+            SAVE_GAME_LAUNCH_QUEUED   => ctx.launch_queued = save_value(&field, "launch queued", stream_read_bool)?,
+            SAVE_GAME_TITLE_FADE      => ctx.title_fade = save_value(&field, "title fade", stream_read_f32)?,
+            SAVE_GAME_NAV_PATH        => ctx.nav_path = Some(NavPath { points: save_read_list(&field, "nav path", save_read_nav_point)? }),
+            SAVE_GAME_DEBUG_FORCES    => ctx.debug_player_forces = Some(save_read_packed(&field, "debug forces", SAVE_VECTOR_STRIDE, stream_read_vector_2f)?),
+            _ => {},
+        }
+    }
+    if !ended {
+        return Err(SaveError::Corrupt("end of file"));
+    }
+    Ok(ctx)
+}
+
+pub fn save_read_file(path: &std::path::Path) -> Result<GameState, SaveError> {
+    let bytes = std::fs::read(path)?;
+    save_read(&bytes)
+}
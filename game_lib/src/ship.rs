@@ -0,0 +1,125 @@
+use crate::math::*;
+use crate::physics::*;
+use crate::state::*;
+use crate::world::*;
+
+// Hull damage, destruction and respawning.
+//
+// A ship whose hull reaches zero is destroyed and explodes where it is. After
+// SHIP_RESPAWN_DELAY_SEC it comes back on the pad of the last planet it landed on safely,
+// or the level spawn point, at the cost of a life. With no lives left it stays destroyed.
+// A ship left without fuel away from a pad for SHIP_STRANDED_SEC is lost the same way.
+
+pub const SHIP_START_LIVES: u32 = 3;
+pub const SHIP_RESPAWN_DELAY_SEC: f32 = 2.0;
+pub const SHIP_STRANDED_SEC: f32 = 8.0;
+pub const SHIP_LAUNCH_IMPULSE: f32 = 120.0;   // units/s straight off the surface
+pub const SHIP_LAUNCH_FUEL_COST: f32 = 0.02;
+
+pub fn ship_damage(ctx: &mut GameState, amount: f32) {
+    if ctx.ship.destroyed {
+        return;
+    }
+    ctx.ship.hull = (ctx.ship.hull - amount).max(0.0);
+    if ctx.ship.hull <= 0.0 {
+        ship_destroy(ctx);
+    }
+}
+
+pub fn ship_destroy(ctx: &mut GameState) {
+    ctx.ship.hull = 0.0;
+    ctx.ship.destroyed = true;
+    ctx.respawn_sec = 0.0;
+    ctx.player.d_pos = vector_2f_zero();
+    ctx.player.landed = false;
+    ctx.debug_player_forces = None;
+    ctx.events.push(GameEvent::Explosion { pos: ctx.player.pos });
+}
+
+// Position, rotation and velocity the ship respawns with, sitting upright on the last safe
+// landing pad and moving along with its planet
+pub fn ship_respawn_point(ctx: &GameState) -> (Vector2f, f32, Vector2f) {
+    let planet = ctx.last_landing_planet.and_then(|i| ctx.planets.as_ref()?.get(i));
+    match planet {
+        Some(p) => {
+            let up = vector_2f_normalize_or(p.lz_rel_pos, vector_2f_unitx());
+            let pos = vector_2f_add(p.pos, vector_2f_scale(up, p.surface_radius - PHYSICS_CONTACT_SKIN));
+            (pos, vector_2f_angle(up), p.d_pos)
+        },
+        None => (ctx.spawn_pos, ctx.spawn_rot, vector_2f_zero()),
+    }
+}
+
+fn ship_respawn(ctx: &mut GameState) {
+    let (pos, rot, d_pos) = ship_respawn_point(ctx);
+    let on_pad = ctx.last_landing_planet.is_some();
+
+    // the cargo went down with the old ship
+    ctx.ship.fuel_level = 1.0;
+    ctx.ship.hull = 1.0;
+    ctx.ship.inventory.clear();
+    ctx.ship.destroyed = false;
+    ctx.player = Player {
+        pos,
+        prev_pos: pos,
+        rot,
+        prev_rot: rot,
+        d_pos,
+        landed: on_pad,
+        ..Default::default()
+    };
+    ctx.landing_outcome = if on_pad { LandingOutcome::Landed } else { LandingOutcome::None };
+    ctx.stranded_sec = 0.0;
+}
+
+// Waits out the respawn delay of a destroyed ship
+pub fn ship_update_destroyed(ctx: &mut GameState, dt: f32) {
+    ctx.respawn_sec += dt;
+    if ctx.respawn_sec >= SHIP_RESPAWN_DELAY_SEC && ctx.lives > 0 {
+        ctx.lives -= 1;
+        ship_respawn(ctx);
+    }
+}
+
+// Destroys a ship that has been out of fuel for too long without a pad to refuel on
+pub fn ship_update_stranded(ctx: &mut GameState, dt: f32) {
+    let refuelling = ctx.player.landed && ctx.landing_outcome == LandingOutcome::Landed;
+    if ctx.ship.fuel_level > 0.0 || refuelling {
+        ctx.stranded_sec = 0.0;
+        return;
+    }
+    ctx.stranded_sec += dt;
+    if ctx.stranded_sec >= SHIP_STRANDED_SEC {
+        ship_destroy(ctx);
+    }
+}
+
+// Kicks a landed ship off the surface of the planet it's sitting on, false when it can't launch
+pub fn ship_launch(ctx: &mut GameState) -> bool {
+    if !ctx.player.landed || ctx.ship.fuel_level <= 0.0 {
+        return false;
+    }
+    let pos = ctx.player.pos;
+    let world_size = ctx.world_size;
+    let planets = ctx.planets.as_deref().unwrap_or(&[]);
+    let planet = match physics_surface_planet(planets, pos, world_size) {
+        Some(i) => &planets[i],
+        None => return false,
+    };
+
+    // lift just clear of the surface so gravity sees the ship in flight
+    let up = vector_2f_normalize_or(world_delta(planet.pos, pos, world_size), vector_2f_from_angle(ctx.player.rot));
+    let planet_pos = world_nearest(pos, planet.pos, world_size);
+    ctx.player.pos = world_wrap(vector_2f_add(planet_pos, vector_2f_scale(up, planet.surface_radius + PHYSICS_CONTACT_SKIN)), world_size);
+    ctx.player.d_pos = vector_2f_add(ctx.player.d_pos, vector_2f_scale(up, SHIP_LAUNCH_IMPULSE));
+    ctx.player.landed = false;
+    ctx.landing_outcome = LandingOutcome::None;
+    ctx.ship.fuel_level = (ctx.ship.fuel_level - SHIP_LAUNCH_FUEL_COST).max(0.0);
+    true
+}
+
+// Where the ship is drawn this frame, between its last two sim steps. The camera follows
+// this same point so the two can't drift apart.
+pub fn ship_drawn_pos(ctx: &GameState) -> Vector2f {
+    vector_2f_lerp(ctx.player.prev_pos, ctx.player.pos, ctx.sim_alpha)
+}
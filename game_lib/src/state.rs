@@ -1,25 +1,127 @@
 use crate::math::*;
+use crate::random::*;
 
-#[derive(Default)]
+#[derive(Default, Copy, Clone)]
 pub struct GameInput {
     pub screen_width: i32,
     pub screen_height: i32,
     pub frame_dt_sec: f32,
-    pub turn_left: bool,
-    pub turn_right: bool,
-    pub accelerate: bool,
-    pub decelerate: bool,
-    pub launch_down: bool,
+    pub turn_left: GameButton,
+    pub turn_right: GameButton,
+    pub accelerate: GameButton,
+    pub decelerate: GameButton,
+    pub launch: GameButton,
+    pub pause: GameButton,
+    // Window pixels with the origin at the top left and y down, the way platforms report it
+    pub mouse_pos: Vector2f,
+    // The world point under the mouse, see input_update_mouse_world_pos
+    pub mouse_world_pos: Vector2f,
+    pub mouse_left: GameButton,
+    pub mouse_right: GameButton,
+    pub mouse_middle: GameButton,
+    // Wheel notches turned this frame, positive away from the player
+    pub mouse_wheel: f32,
+    pub has_focus: bool,
+    // Window events this frame
+    pub focus_gained: bool,
+    pub focus_lost: bool,
+    pub resized: bool,
+    pub controller: GameController,
+}
+
+// The first connected gamepad, after the deadzones and response curve (see controller.rs)
+#[derive(Default, Copy, Clone)]
+pub struct GameController {
+    pub connected: bool,
+    // x right and y up, no longer than 1
+    pub left_stick: Vector2f,
+    pub right_stick: Vector2f,
+    // 0 to 1
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+    pub a: GameButton,
+    pub b: GameButton,
+    pub x: GameButton,
+    pub y: GameButton,
+    pub start: GameButton,
+    pub back: GameButton,
+    pub left_shoulder: GameButton,
+    pub right_shoulder: GameButton,
+    pub left_thumb: GameButton,
+    pub right_thumb: GameButton,
+    pub dpad_up: GameButton,
+    pub dpad_down: GameButton,
+    pub dpad_left: GameButton,
+    pub dpad_right: GameButton,
+}
+
+#[derive(Default, Copy, Clone, PartialEq, Debug)]
+pub struct GameButton {
+    pub ended_down: bool,
+    // Presses and releases seen this frame
+    pub half_transition_count: u32,
+    // How far the button is pushed, 0 to 1. Digital buttons are 1 while down.
+    pub value: f32,
 }
 
 #[derive(Default)]
 pub struct GameState {
+    pub initialized: bool,
+    pub mode: GameMode,
+    // Time since the mode last changed
+    pub mode_sec: f32,
+    // Levels completed this game
+    pub level_number: u32,
+    pub seed: u64,
+    pub rng: RandomSeries,
+    // Generate the solar system from the seed instead of using the default level
+    pub generate_planets: bool,
+
+    // Fixed timestep, sim_alpha is the interpolation factor between the last two steps
+    pub sim_step_sec: f32,
+    pub sim_accumulator_sec: f32,
+    pub sim_alpha: f32,
+    // Time simulated since the level started, places the planets on their orbits
+    pub sim_time_sec: f32,
+
+    // Twice the screen size, set every frame. The world wraps around at its edges.
+    pub world_size: Vector2f,
+
     pub player: Player,
     pub ship: Ship,
+    pub camera: Camera,
+    // Gravitational constant, every pull scales with it
+    pub gravity_g: f32,
     pub sun: Sun,
     pub planets: Option<Vec<Planet>>,
     pub space_stars: Option<Vec<Star>>,
 
+    // Result of the last time the ship touched a planet, for the HUD
+    pub landing_outcome: LandingOutcome,
+    pub landing_outcome_sec: f32,
+
+    pub score: u32,
+
+    // Ships left after the current one
+    pub lives: u32,
+    // Level spawn point, used for respawning until the ship lands safely somewhere
+    pub spawn_pos: Vector2f,
+    pub spawn_rot: f32,
+    // Planet whose landing zone the ship last landed on safely
+    pub last_landing_planet: Option<usize>,
+    // Time since the ship was destroyed
+    pub respawn_sec: f32,
+    // Time the ship has been out of fuel away from a pad
+    pub stranded_sec: f32,
+
+    // Pool of at most MAX_PROJECTILES, dead ones are reused
+    pub projectiles: Vec<Projectile>,
+    // Launch was pressed and the next sim step acts on it
+    pub launch_queued: bool,
+
+    // Things that happened this frame for the platform to react to, cleared every frame
+    pub events: Vec<GameEvent>,
+
     pub title_fade: f32,
     pub nav_path: Option<NavPath>,
 
@@ -31,16 +133,56 @@ pub struct Star {
     pub size: f32,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Sun {
     pub pos: Vector2f,
-    pub g_force: f32,
+    pub mass: f32,
+    // The sun has no surface, its pull is softened inside this distance instead of blowing up
+    pub softening: f32,
+}
+
+// Follows the ship every rendered frame, see camera.rs
+#[derive(Copy, Clone, Debug)]
+pub struct Camera {
+    // Where the camera looks before any shake
+    pub pos: Vector2f,
+    pub d_pos: Vector2f,
+    // Above 1 zooms in, below 1 shows more of the world
+    pub zoom: f32,
+    pub d_zoom: f32,
+    pub rot: f32,
+    // 0 to 1, fades over time and the shake grows with its square
+    pub shake: f32,
+    // Drives the shake pattern
+    pub shake_sec: f32,
+    // pos stays inside min, max when set, or the copy of it nearest the ship in a wrapping world
+    pub bounds: Option<(Vector2f, Vector2f)>,
+    // Cleared to jump straight to the ship on the next update instead of gliding there
+    pub initialized: bool,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            pos: vector_2f_zero(),
+            d_pos: vector_2f_zero(),
+            zoom: 1.0,
+            d_zoom: 0.0,
+            rot: 0.0,
+            shake: 0.0,
+            shake_sec: 0.0,
+            bounds: None,
+            initialized: false,
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct Player {
     pub rot: f32,
     pub pos:    Vector2f,
+    pub prev_rot: f32,
+    pub prev_pos: Vector2f,
     pub d_pos:  Vector2f,
     pub dd_pos: f32,
     pub landed: bool,
@@ -50,36 +192,122 @@ pub struct Player {
 pub struct Ship {
     pub fuel_level: f32,
     pub fuel_burn_rate: f32,
+    // 1 is undamaged, the ship can't fly at 0
+    pub hull: f32,
+    // Items carried instead of used on pickup, at most inventory_capacity
+    pub inventory: Vec<PlanetItemType>,
+    pub inventory_capacity: u32,
+    pub destroyed: bool,
 }
 
-const MAX_PLANET_ITEMS: usize = 5;
-#[derive(Default)]
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LandingOutcome {
+    #[default]
+    None,
+    // Slow and upright on the landing zone, the ship is snapped to the pad and refuelled
+    Landed,
+    // Slow and upright somewhere else on the surface
+    TouchedDown,
+    // Too fast or not upright, the hull is damaged and the ship bounces off
+    Crashed,
+}
+
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GameMode {
+    // Title over the starting system until the player does something
+    #[default]
+    Title,
+    Playing,
+    Paused,
+    // Every gem collected and back on a pad, launch moves on to the next system
+    LevelComplete,
+    // Destroyed with no lives left, launch starts a new game
+    GameOver,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    Explosion { pos: Vector2f },
+    ProjectileHit { pos: Vector2f, target: ProjectileTarget },
+}
+
+// What a projectile ran into
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ProjectileTarget {
+    // Index into GameState::planets
+    Planet(usize),
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Projectile {
+    pub pos: Vector2f,
+    pub prev_pos: Vector2f,
+    pub d_pos: Vector2f,
+    pub age_sec: f32,
+    pub alive: bool,
+}
+
+pub const MAX_PLANET_ITEMS: usize = 5;
+#[derive(Default, Clone)]
 pub struct Planet {
     pub radius: f32,
     pub pos: Vector2f,
+    // Where the planet was before the last sim step, for interpolation
+    pub prev_pos: Vector2f,
+    pub d_pos: Vector2f,
     pub color: Vector4f,
 
+    // Well the planet's pull dominates, kept clear of other planets
     pub g_radius: f32,
-    pub g_force: f32,
-    
+    pub mass: f32,
+    // Planets without an orbit stay where they are
+    pub orbit: Option<Orbit>,
+
     pub surface_radius: f32,
     pub lz_rel_pos: Vector2f,
     pub lz_color: Vector4f,
 
-    pub item: PlanetItem,
+    // At most MAX_PLANET_ITEMS, positions are relative to the planet
+    pub items: Vec<PlanetItem>,
 }
 
-#[derive(Default)]
+// What an orbit goes around
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OrbitParent {
+    Sun,
+    // Index into GameState::planets, always before the moon itself
+    Planet(usize),
+}
+
+// Kepler ellipse around the parent, see orbit.rs
+#[derive(Copy, Clone, Debug)]
+pub struct Orbit {
+    pub parent: OrbitParent,
+    pub semi_major: f32,
+    // 0 is a circle, up to ORBIT_MAX_ECCENTRICITY
+    pub eccentricity: f32,
+    // Direction from the parent to the closest point of the orbit
+    pub periapsis_rot: f32,
+    // Mean anomaly at time 0, radians past periapsis
+    pub phase: f32,
+    pub clockwise: bool,
+}
+
+#[derive(Default, Clone)]
 pub struct PlanetItem {
     pub itype: PlanetItemType,
     pub pos: Vector2f,
 }
 
-#[derive(Default)]
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
 pub enum PlanetItemType {
     #[default] 
     None,
-    Fuel
+    Fuel,
+    Repair,
+    Cargo,
+    Key,
+    Gem,
 }
 
 pub struct NavPath {
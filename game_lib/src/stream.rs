@@ -0,0 +1,124 @@
+use crate::math::*;
+
+// Little endian binary writing and reading used by the replay and save formats.
+// Readers return None when they run out of bytes so callers can treat a short
+// buffer as a corrupt file instead of panicking.
+
+pub struct StreamReader<'a> {
+    pub bytes: &'a [u8],
+    pub at: usize,
+}
+
+pub fn stream_reader(bytes: &[u8]) -> StreamReader<'_> {
+    StreamReader { bytes, at: 0 }
+}
+
+pub fn stream_remaining(reader: &StreamReader) -> usize {
+    reader.bytes.len() - reader.at
+}
+
+pub fn stream_write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(bytes);
+}
+
+pub fn stream_write_u8(out: &mut Vec<u8>, v: u8) {
+    out.push(v);
+}
+
+pub fn stream_write_bool(out: &mut Vec<u8>, v: bool) {
+    out.push(v as u8);
+}
+
+pub fn stream_write_u16(out: &mut Vec<u8>, v: u16) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+pub fn stream_write_u32(out: &mut Vec<u8>, v: u32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+pub fn stream_write_i32(out: &mut Vec<u8>, v: i32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+pub fn stream_write_u64(out: &mut Vec<u8>, v: u64) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+pub fn stream_write_f32(out: &mut Vec<u8>, v: f32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+pub fn stream_write_vector_2f(out: &mut Vec<u8>, v: Vector2f) {
+    stream_write_f32(out, v.x);
+    stream_write_f32(out, v.y);
+}
+
+pub fn stream_write_vector_3f(out: &mut Vec<u8>, v: Vector3f) {
+    stream_write_f32(out, v.x);
+    stream_write_f32(out, v.y);
+    stream_write_f32(out, v.z);
+}
+
+pub fn stream_write_vector_4f(out: &mut Vec<u8>, v: Vector4f) {
+    stream_write_f32(out, v.x);
+    stream_write_f32(out, v.y);
+    stream_write_f32(out, v.z);
+    stream_write_f32(out, v.w);
+}
+
+pub fn stream_read_bytes<'a>(reader: &mut StreamReader<'a>, count: usize) -> Option<&'a [u8]> {
+    let end = reader.at.checked_add(count)?;
+    let bytes = reader.bytes.get(reader.at..end)?;
+    reader.at = end;
+    Some(bytes)
+}
+
+fn stream_read_array<const N: usize>(reader: &mut StreamReader) -> Option<[u8; N]> {
+    let bytes = stream_read_bytes(reader, N)?;
+    bytes.try_into().ok()
+}
+
+pub fn stream_read_u8(reader: &mut StreamReader) -> Option<u8> {
+    Some(stream_read_array::<1>(reader)?[0])
+}
+
+pub fn stream_read_bool(reader: &mut StreamReader) -> Option<bool> {
+    match stream_read_u8(reader)? {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
+pub fn stream_read_u16(reader: &mut StreamReader) -> Option<u16> {
+    Some(u16::from_le_bytes(stream_read_array(reader)?))
+}
+
+pub fn stream_read_u32(reader: &mut StreamReader) -> Option<u32> {
+    Some(u32::from_le_bytes(stream_read_array(reader)?))
+}
+
+pub fn stream_read_i32(reader: &mut StreamReader) -> Option<i32> {
+    Some(i32::from_le_bytes(stream_read_array(reader)?))
+}
+
+pub fn stream_read_u64(reader: &mut StreamReader) -> Option<u64> {
+    Some(u64::from_le_bytes(stream_read_array(reader)?))
+}
+
+pub fn stream_read_f32(reader: &mut StreamReader) -> Option<f32> {
+    Some(f32::from_le_bytes(stream_read_array(reader)?))
+}
+
+pub fn stream_read_vector_2f(reader: &mut StreamReader) -> Option<Vector2f> {
+    Some(vector_2f(stream_read_f32(reader)?, stream_read_f32(reader)?))
+}
+
+pub fn stream_read_vector_3f(reader: &mut StreamReader) -> Option<Vector3f> {
+    Some(vector_3f(stream_read_f32(reader)?, stream_read_f32(reader)?, stream_read_f32(reader)?))
+}
+
+pub fn stream_read_vector_4f(reader: &mut StreamReader) -> Option<Vector4f> {
+    Some(vector_4f(stream_read_f32(reader)?, stream_read_f32(reader)?, stream_read_f32(reader)?, stream_read_f32(reader)?))
+}
@@ -0,0 +1,61 @@
+use crate::math::*;
+
+// The world is a torus: leaving one edge comes back in at the opposite one.
+//
+// Positions are kept inside -world_size/2 to world_size/2 and anything that measures between
+// two points goes the shortest way round, so gravity, collisions and pickups work the same
+// across the seam as anywhere else. Rendering draws ghost copies of the world next to the
+// real one wherever the view reaches past an edge, so the seam can't be seen either.
+
+pub fn world_wrap(pos: Vector2f, world_size: Vector2f) -> Vector2f {
+    if world_size.x <= 0.0 || world_size.y <= 0.0 {
+        return pos;
+    }
+    let half_world = vector_2f_scale(world_size, 0.5);
+    vector_2f(
+        pos.x.wrap(-half_world.x, half_world.x),
+        pos.y.wrap(-half_world.y, half_world.y),
+    )
+}
+
+// Shortest displacement from one point to another, no more than half the world each way
+pub fn world_delta(from: Vector2f, to: Vector2f, world_size: Vector2f) -> Vector2f {
+    let d = vector_2f_sub(to, from);
+    if world_size.x <= 0.0 || world_size.y <= 0.0 {
+        return d;
+    }
+    vector_2f(
+        d.x - world_size.x * (d.x / world_size.x).round(),
+        d.y - world_size.y * (d.y / world_size.y).round(),
+    )
+}
+
+pub fn world_distance(a: Vector2f, b: Vector2f, world_size: Vector2f) -> f32 {
+    vector_2f_length(world_delta(a, b, world_size))
+}
+
+// The copy of pos closest to near, which can be outside the world when near is by an edge
+pub fn world_nearest(near: Vector2f, pos: Vector2f, world_size: Vector2f) -> Vector2f {
+    vector_2f_add(near, world_delta(near, pos, world_size))
+}
+
+// Offsets of every copy of the world a view circle overlaps, the real world is the zero
+// offset. Views wider than the world only get the copies right next to it.
+pub fn world_ghost_offsets(view_center: Vector2f, view_radius: f32, world_size: Vector2f) -> Vec<Vector2f> {
+    let half_world = vector_2f_scale(world_size, 0.5);
+    let mut offsets = Vec::new();
+    for y in -1..=1 {
+        for x in -1..=1 {
+            let offset = vector_2f(x as f32 * world_size.x, y as f32 * world_size.y);
+            // closest point of the copy to the view center
+            let closest = vector_2f(
+                view_center.x.clamp(offset.x - half_world.x, offset.x + half_world.x),
+                view_center.y.clamp(offset.y - half_world.y, offset.y + half_world.y),
+            );
+            if vector_2f_distance(closest, view_center) <= view_radius {
+                offsets.push(offset);
+            }
+        }
+    }
+    offsets
+}
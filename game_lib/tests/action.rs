@@ -0,0 +1,83 @@
+use game::{
+    action::*,
+    input::*,
+    state::*,
+};
+
+#[test]
+fn action_multiple_bindings() {
+    let mut map = action_map_default();
+    let mut input = GameInput::default();
+
+    input_begin_frame(&mut input);
+    action_map_process(&mut map, &mut input, InputSource::Key(Key::Char('W')), 1.0);
+    action_map_process(&mut map, &mut input, InputSource::Key(Key::Up), 1.0);
+    assert!(button_pressed(&input.accelerate));
+    assert_eq!(input.accelerate.half_transition_count, 1);
+
+    // still held by the other binding
+    input_begin_frame(&mut input);
+    action_map_process(&mut map, &mut input, InputSource::Key(Key::Char('W')), 0.0);
+    assert!(input.accelerate.ended_down);
+    assert!(!button_released(&input.accelerate));
+
+    action_map_process(&mut map, &mut input, InputSource::Key(Key::Up), 0.0);
+    assert!(button_released(&input.accelerate));
+
+    // unbound inputs don't touch anything
+    input_begin_frame(&mut input);
+    action_map_process(&mut map, &mut input, InputSource::Key(Key::Char('Q')), 1.0);
+    assert!(input_buttons(&input).iter().all(|b| !button_held(b)));
+
+    // nothing stays stuck down after losing the releases
+    action_map_process(&mut map, &mut input, InputSource::Key(Key::Space), 1.0);
+    action_map_process_axis(&mut map, &mut input, GamepadAxis::LeftX, -1.0);
+    action_map_release_all(&mut map, &mut input);
+    assert!(!input.launch.ended_down && !input.turn_left.ended_down);
+    assert!(map.bindings.iter().all(|b| b.value == 0.0));
+}
+
+#[test]
+fn action_analog_axis() {
+    let mut map = action_map_default();
+    let mut input = GameInput::default();
+
+    action_map_process_axis(&mut map, &mut input, GamepadAxis::LeftX, -0.6);
+    assert!(input.turn_left.ended_down);
+    assert_eq!(button_value(&input.turn_left), 0.6);
+    assert!(!input.turn_right.ended_down);
+
+    // below the threshold the value is kept but the action isn't down
+    action_map_process_axis(&mut map, &mut input, GamepadAxis::LeftX, 0.1);
+    assert!(!input.turn_left.ended_down);
+    assert!(!input.turn_right.ended_down);
+    assert_eq!(input.turn_right.value, 0.1);
+
+    // a key on the same action wins over a half pushed stick
+    action_map_process_axis(&mut map, &mut input, GamepadAxis::LeftX, 0.5);
+    action_map_process(&mut map, &mut input, InputSource::Key(Key::Char('D')), 1.0);
+    assert_eq!(button_value(action_button(&input, GameAction::TurnRight)), 1.0);
+}
+
+#[test]
+fn action_map_parse_errors() {
+    let map = action_map_parse("launch key:f1 mouse:right # fire\n\nlaunch pad:x\npause axis:right_y-").unwrap();
+    assert_eq!(map.bindings.len(), 4);
+    assert_eq!(map.bindings[0].source, InputSource::Key(Key::F(1)));
+    assert_eq!(map.bindings[2].action, GameAction::Launch);
+    assert_eq!(map.bindings[3].source, InputSource::GamepadAxis { axis: GamepadAxis::RightY, positive: false });
+
+    let errors = [
+        ("jump key:space", 1, "unknown action 'jump'"),
+        ("launch\n", 1, "launch has no inputs"),
+        ("\nlaunch space", 2, "expected device:input but found 'space'"),
+        ("launch joy:a", 1, "unknown device 'joy'"),
+        ("launch key:f13", 1, "unknown key input 'f13'"),
+        ("launch axis:left_x", 1, "unknown axis input 'left_x'"),
+        ("launch key:a\nlaunch key:A", 2, "'key:A' is bound to launch more than once"),
+    ];
+    for (text, line, message) in errors {
+        let e = action_map_parse(text).unwrap_err();
+        assert_eq!((e.line, e.message.as_str()), (line, message), "{}", text);
+    }
+}
@@ -0,0 +1,134 @@
+use game::{
+    camera::*,
+    math::*,
+    ship::*,
+    state::*,
+    world::*,
+};
+
+const CAMERA_WORLD_SIZE: Vector2f = Vector2f { x: 3200.0, y: 1800.0 };
+const CAMERA_DT: f32 = 1.0 / 60.0;
+
+fn camera_state(pos: Vector2f) -> GameState {
+    let mut ctx = GameState { world_size: CAMERA_WORLD_SIZE, ..GameState::default() };
+    ctx.player.pos = pos;
+    ctx.player.prev_pos = pos;
+    ctx.sim_alpha = 1.0;
+    camera_update(&mut ctx, CAMERA_DT);
+    ctx
+}
+
+fn camera_run(ctx: &mut GameState, seconds: f32) {
+    for _ in 0..(seconds / CAMERA_DT) as u32 {
+        camera_update(ctx, CAMERA_DT);
+    }
+}
+
+#[test]
+fn camera_spring_settles_without_overshoot() {
+    let (mut x, mut v) = (0.0, 0.0);
+    let mut max: f32 = 0.0;
+    for _ in 0..120 {
+        (x, v) = camera_spring(x, v, 10.0, CAMERA_FOLLOW_OMEGA, CAMERA_DT);
+        max = max.max(x);
+    }
+    assert!(max <= 10.0);
+    assert!((x - 10.0).abs() < 0.01);
+
+    // a huge step is still stable
+    let (x, _) = camera_spring(0.0, 0.0, 10.0, CAMERA_FOLLOW_OMEGA, 5.0);
+    assert!(x > 0.0 && x <= 10.0);
+}
+
+#[test]
+fn camera_follows_and_looks_ahead() {
+    let mut ctx = camera_state(vector_2f(100.0, 0.0));
+    // snaps to the ship the first time
+    assert_eq!(ctx.camera.pos, vector_2f(100.0, 0.0));
+
+    // and aims at the ship where it's drawn, between its last two steps
+    ctx.player.prev_pos = vector_2f(50.0, 0.0);
+    ctx.sim_alpha = 0.25;
+    assert_eq!(camera_target_pos(&ctx), ship_drawn_pos(&ctx));
+    assert_eq!(ship_drawn_pos(&ctx), vector_2f(62.5, 0.0));
+    ctx.player.prev_pos = ctx.player.pos;
+    ctx.sim_alpha = 1.0;
+
+    ctx.player.pos = vector_2f(300.0, 0.0);
+    ctx.player.prev_pos = ctx.player.pos;
+    camera_update(&mut ctx, CAMERA_DT);
+    assert!(ctx.camera.pos.x > 100.0 && ctx.camera.pos.x < 300.0);
+
+    ctx.player.d_pos = vector_2f(0.0, 1000.0);
+    camera_run(&mut ctx, 3.0);
+    assert!((ctx.camera.pos.x - 300.0).abs() < 0.1);
+    assert!((ctx.camera.pos.y - CAMERA_LOOK_AHEAD_MAX).abs() < 0.1);
+
+    // the ship wrapping around the world doesn't send the camera back across it
+    ctx.player.d_pos = vector_2f_zero();
+    ctx.player.pos = vector_2f(1590.0, 0.0);
+    camera_run(&mut ctx, 3.0);
+    ctx.player.pos = vector_2f(-1590.0, 0.0);
+    ctx.player.prev_pos = ctx.player.pos;
+    camera_update(&mut ctx, CAMERA_DT);
+    assert!((ctx.camera.pos.x - -1590.0).abs() < 30.0);
+
+    ctx.camera.bounds = Some((vector_2f(-100.0, -100.0), vector_2f(100.0, 100.0)));
+    camera_update(&mut ctx, CAMERA_DT);
+    assert_eq!(ctx.camera.pos.x, -100.0);
+}
+
+#[test]
+fn camera_bounds_hold_across_the_seam() {
+    // bounds near the right edge, with the ship just past them
+    let mut ctx = camera_state(vector_2f(1590.0, 0.0));
+    ctx.camera.bounds = Some((vector_2f(1000.0, -100.0), vector_2f(1500.0, 100.0)));
+    camera_run(&mut ctx, 3.0);
+    assert_eq!(ctx.camera.pos.x, 1500.0);
+
+    // crossing the seam the ship is still nearest the right edge of the bounds, so the
+    // camera stays there instead of snapping over to the left edge
+    ctx.player.pos = vector_2f(-1590.0, 0.0);
+    ctx.player.prev_pos = ctx.player.pos;
+    camera_run(&mut ctx, 3.0);
+    assert_eq!(world_wrap(ctx.camera.pos, CAMERA_WORLD_SIZE).x, 1500.0);
+
+    // and coming back over the seam too
+    ctx.player.pos = vector_2f(1590.0, 0.0);
+    ctx.player.prev_pos = ctx.player.pos;
+    camera_run(&mut ctx, 3.0);
+    assert_eq!(world_wrap(ctx.camera.pos, CAMERA_WORLD_SIZE).x, 1500.0);
+
+    // heading on towards the middle from the other side it reaches the left edge
+    ctx.player.pos = vector_2f(-100.0, 0.0);
+    ctx.player.prev_pos = ctx.player.pos;
+    camera_run(&mut ctx, 3.0);
+    assert_eq!(world_wrap(ctx.camera.pos, CAMERA_WORLD_SIZE).x, 1000.0);
+}
+
+#[test]
+fn camera_zooms_out_near_wells() {
+    let mut ctx = camera_state(vector_2f(0.0, 0.0));
+    ctx.planets = Some(vec![Planet { pos: vector_2f(600.0, 0.0), radius: 100.0, g_radius: 250.0, ..Planet::default() }]);
+    camera_run(&mut ctx, 3.0);
+    assert_eq!(ctx.camera.zoom, 1.0);
+
+    ctx.player.pos = vector_2f(400.0, 0.0);
+    camera_run(&mut ctx, 3.0);
+    assert!((ctx.camera.zoom - CAMERA_WELL_ZOOM).abs() < 0.01);
+}
+
+#[test]
+fn camera_shakes_on_explosion() {
+    let mut ctx = camera_state(vector_2f(0.0, 0.0));
+    ctx.lives = 1;
+    ship_destroy(&mut ctx);
+    camera_update(&mut ctx, CAMERA_DT);
+    ctx.events.clear();
+    assert!(ctx.camera.shake > 0.5);
+    assert_ne!(camera_view(&ctx.camera).pos, ctx.camera.pos);
+
+    camera_run(&mut ctx, 1.0 / CAMERA_SHAKE_DECAY);
+    assert_eq!(ctx.camera.shake, 0.0);
+    assert_eq!(camera_view(&ctx.camera).pos, ctx.camera.pos);
+}
@@ -0,0 +1,41 @@
+// Fixtures shared by the integration tests, not every test uses all of them
+#![allow(dead_code)]
+
+use game::{
+    render::*,
+    state::*,
+    update_and_render,
+};
+
+// A 1600x900 window at 60 frames a second
+pub fn test_input() -> GameInput {
+    GameInput {
+        screen_width: 1600,
+        screen_height: 900,
+        frame_dt_sec: 1.0 / 60.0,
+        ..GameInput::default()
+    }
+}
+
+// The default level mid game, built by one zero length frame so nothing has moved yet
+pub fn test_state(input: &GameInput) -> GameState {
+    let mut ctx = GameState { mode: GameMode::Playing, ..GameState::default() };
+    test_frame(&GameInput { frame_dt_sec: 0.0, ..*input }, &mut ctx);
+    ctx
+}
+
+// One frame, returns the events it raised
+pub fn test_frame(input: &GameInput, ctx: &mut GameState) -> Vec<GameEvent> {
+    let mut render_buffer = RenderBuffer::default();
+    update_and_render(input, ctx, &mut render_buffer);
+    ctx.events.clone()
+}
+
+// Frames until seconds have passed, returns every event raised on the way
+pub fn test_run(input: &GameInput, ctx: &mut GameState, seconds: f32) -> Vec<GameEvent> {
+    let mut events = Vec::new();
+    for _ in 0..(seconds / input.frame_dt_sec).round() as u32 {
+        events.extend(test_frame(input, ctx));
+    }
+    events
+}
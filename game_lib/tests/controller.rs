@@ -0,0 +1,72 @@
+use game::{
+    action::*,
+    controller::*,
+    input::*,
+    math::*,
+    state::*,
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn controller_deadzone_and_curve() {
+    assert_eq!(controller_axis_response(0.2, 0.24, 1.5), 0.0);
+    assert_eq!(controller_axis_response(1.3, 0.24, 1.5), 1.0);
+    let half = controller_axis_response(0.62, 0.24, 1.0);
+    assert!((half - 0.5).abs() < 0.0001);
+    assert!(controller_axis_response(0.62, 0.24, 2.0) < half);
+
+    // radial, so a diagonal keeps its direction
+    let stick = controller_stick(vector_2f(0.5, 0.5), 0.24, 1.0);
+    assert!((stick.x - stick.y).abs() < 0.0001);
+    assert!(vector_2f_length(stick) < vector_2f_length(vector_2f(0.5, 0.5)));
+    assert_eq!(controller_stick(vector_2f(0.1, -0.15), 0.24, 1.0), vector_2f_zero());
+}
+
+#[test]
+fn controller_drives_actions() {
+    let mut map = action_map_default();
+    let mut input = GameInput::default();
+
+    let mut reading = ControllerReading { connected: true, left_trigger: 0.05, ..ControllerReading::default() };
+    reading.left_stick = vector_2f(-0.6, 0.0);
+    reading.right_trigger = 0.7;
+    reading.buttons[GamepadButton::A as usize] = true;
+    input_begin_frame(&mut input);
+    controller_update(&mut input.controller, &reading);
+    controller_process_actions(&mut map, &mut input);
+
+    assert!(button_pressed(&input.controller.a));
+    assert!(button_pressed(&input.launch));
+    assert_eq!(input.decelerate.value, 0.0);
+    let thrust = button_value(&input.accelerate);
+    assert!(thrust > 0.0 && thrust < 1.0);
+    let turn = button_value(&input.turn_left);
+    assert!(turn > 0.0 && turn < 1.0);
+
+    // unplugged mid game everything lets go
+    input_begin_frame(&mut input);
+    controller_update(&mut input.controller, &ControllerReading::default());
+    controller_process_actions(&mut map, &mut input);
+    assert!(button_released(&input.launch));
+    assert_eq!(button_value(&input.accelerate), 0.0);
+    assert_eq!(button_value(&input.turn_left), 0.0);
+}
+
+#[test]
+fn controller_partial_turn_rate() {
+    let input = test_input();
+    let turn_for = |turn_left: GameButton| {
+        let mut ctx = test_state(&input);
+        let rot = ctx.player.rot;
+        test_run(&GameInput { turn_left, ..input }, &mut ctx, 0.5);
+        ctx.player.rot - rot
+    };
+
+    // a stick pushed under the down threshold still turns, slower than the key
+    let key = turn_for(game_button(true, 0));
+    let stick = turn_for(GameButton { ended_down: false, half_transition_count: 0, value: 0.2 });
+    assert!(stick > 0.0);
+    assert!((stick / key - 0.2).abs() < 0.01);
+}
@@ -0,0 +1,108 @@
+// Generated solar systems must always be levels the game accepts.
+
+use game::{
+    generate::*,
+    level::*,
+    math::*,
+    orbit::*,
+    random::*,
+    state::*,
+};
+
+fn generate_check_seed(seed: u64) -> Level {
+    let params = generate_params(vector_2f(3200.0, 1800.0));
+    let level = generate_level(&params, &mut random_seed(seed));
+    let half_world = vector_2f_scale(params.world_size, 0.5);
+
+    for (i, p) in level.planets.iter().enumerate() {
+        assert!(p.g_radius > p.radius, "seed {}: planet {} well is inside the planet", seed, i);
+        assert!(p.pos.x.abs() + p.g_radius <= half_world.x && p.pos.y.abs() + p.g_radius <= half_world.y,
+            "seed {}: planet {} well leaves the world", seed, i);
+        assert!((vector_2f_length(p.lz_rel_pos) - p.surface_radius).abs() < 0.01,
+            "seed {}: planet {} landing zone is not on the surface", seed, i);
+        assert!(p.orbit.is_some(), "seed {}: planet {} has no orbit", seed, i);
+    }
+
+    // wells stay apart however far the system turns, and moons stay inside their planet's well
+    let mut planets = level.planets.clone();
+    for step in 0..16 {
+        orbit_place_planets(level.gravity_g, &level.sun, &mut planets, step as f32 * 20.0, params.world_size);
+        for (i, p) in planets.iter().enumerate() {
+            match p.orbit.map(|o| o.parent) {
+                Some(OrbitParent::Planet(j)) => {
+                    let q = &planets[j];
+                    assert!(vector_2f_distance(p.pos, q.pos) + p.g_radius <= q.g_radius + 0.01,
+                        "seed {}: moon {} leaves the well of planet {}", seed, i, j);
+                },
+                _ => {
+                    assert!(vector_2f_length(p.pos) + p.g_radius <= half_world.x.min(half_world.y) + 0.01,
+                        "seed {}: planet {} well leaves the world", seed, i);
+                    for (j, q) in planets[..i].iter().enumerate() {
+                        if q.orbit.is_some_and(|o| o.parent != OrbitParent::Sun) {
+                            continue;
+                        }
+                        let dist = vector_2f_distance(p.pos, q.pos);
+                        assert!(dist >= p.g_radius + q.g_radius + params.min_spacing - 0.01,
+                            "seed {}: planets {} and {} have overlapping wells", seed, j, i);
+                    }
+                },
+            }
+        }
+    }
+
+    // the written level has to survive the same validation as a hand authored one
+    let text = level_write(&level);
+    let parsed = level_parse(&text).unwrap_or_else(|e| panic!("seed {}: {}\n{}", seed, e, text));
+    assert_eq!(parsed.planets.len(), level.planets.len());
+    for (p, q) in parsed.planets.iter().zip(level.planets.iter()) {
+        assert!(vector_2f_distance(p.pos, q.pos) < 0.01, "seed {}: written planet moved", seed);
+    }
+    level
+}
+
+#[test]
+fn generate_levels_are_valid() {
+    for seed in 0..64 {
+        let level = generate_check_seed(seed);
+        assert!(!level.planets.is_empty(), "seed {}: no planets were placed", seed);
+    }
+}
+
+#[test]
+fn generate_moons_and_shared_rings() {
+    // over enough seeds the generator uses both
+    let params = generate_params(vector_2f(3200.0, 1800.0));
+    let mut moons = 0;
+    let mut shared_rings = 0;
+    for seed in 0..64 {
+        let level = generate_level(&params, &mut random_seed(seed));
+        let rings: Vec<f32> = level.planets.iter()
+            .filter_map(|p| p.orbit.filter(|o| o.parent == OrbitParent::Sun).map(|o| o.semi_major))
+            .collect();
+        moons += level.planets.len() - rings.len();
+        shared_rings += rings.iter().enumerate().filter(|(i, r)| rings[..*i].contains(r)).count();
+    }
+    assert!(moons > 0);
+    assert!(shared_rings > 0);
+}
+
+#[test]
+fn generate_is_deterministic() {
+    let a = level_write(&generate_check_seed(1234));
+    let b = level_write(&generate_check_seed(1234));
+    assert_eq!(a, b);
+}
+
+#[test]
+fn generate_levels_always_have_a_gem() {
+    // even when the planets are given no items of their own
+    let mut params = generate_params(vector_2f(3200.0, 1800.0));
+    for max_items in [params.max_items, 0] {
+        params.max_items = max_items;
+        for seed in 0..64 {
+            let level = generate_level(&params, &mut random_seed(seed));
+            let gems = level.planets.iter().flat_map(|p| p.items.iter()).filter(|i| i.itype == PlanetItemType::Gem).count();
+            assert!(gems > 0, "seed {} with at most {} items: no gems", seed, max_items);
+        }
+    }
+}
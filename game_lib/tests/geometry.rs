@@ -0,0 +1,130 @@
+use std::f32::consts::FRAC_PI_4;
+
+use game::{
+    geometry::*,
+    math::*,
+};
+
+const EPSILON: f32 = 1.0e-4;
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() <= EPSILON, "{} != {}", a, b);
+}
+
+fn assert_close_2f(a: Vector2f, b: Vector2f) {
+    assert!(vector_2f_distance(a, b) <= EPSILON, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn geometry_overlaps() {
+    let c = circle(vector_2f(0.0, 0.0), 1.0);
+    assert!(circle_overlaps_circle(&c, &circle(vector_2f(2.0, 0.0), 1.0)));
+    assert!(!circle_overlaps_circle(&c, &circle(vector_2f(2.1, 0.0), 1.0)));
+
+    let b = aabb(vector_2f(1.5, -1.0), vector_2f(3.0, 1.0));
+    assert!(!circle_overlaps_aabb(&c, &b));
+    assert!(circle_overlaps_aabb(&circle(vector_2f(1.0, 0.0), 0.6), &b));
+    // near the corner the circle is closer to the box on each axis than its radius but still misses
+    assert!(!circle_overlaps_aabb(&circle(vector_2f(0.9, 1.6), 0.8), &b));
+
+    assert!(aabb_overlaps_aabb(&b, &aabb_from_center(vector_2f(3.5, 0.0), vector_2f(0.5, 0.5))));
+    assert!(!aabb_overlaps_aabb(&b, &aabb_from_center(vector_2f(4.0, 0.0), vector_2f(0.5, 0.5))));
+
+    // a diamond reaches further along the axes than the box it was rotated from
+    let diamond = obb(vector_2f(0.0, 0.0), vector_2f(1.0, 1.0), FRAC_PI_4);
+    assert!(obb_contains_point(&diamond, vector_2f(1.4, 0.0)));
+    assert!(!obb_contains_point(&diamond, vector_2f(0.9, 0.9)));
+    assert!(circle_overlaps_obb(&circle(vector_2f(1.6, 0.0), 0.25), &diamond));
+    assert!(obb_overlaps_obb(&diamond, &obb(vector_2f(2.3, 0.0), vector_2f(1.0, 1.0), 0.0)));
+    assert!(!obb_overlaps_obb(&diamond, &obb(vector_2f(1.6, 1.6), vector_2f(0.5, 0.5), 0.0)));
+
+    let s = segment(vector_2f(-2.0, 0.5), vector_2f(2.0, 0.5));
+    assert!(circle_overlaps_segment(&c, &s));
+    assert!(!circle_overlaps_segment(&circle(vector_2f(3.0, 3.0), 1.0), &s));
+}
+
+#[test]
+fn geometry_closest_points() {
+    let c = circle(vector_2f(1.0, 1.0), 2.0);
+    assert_close_2f(circle_closest_point(&c, vector_2f(1.0, 5.0)), vector_2f(1.0, 3.0));
+    assert_close_2f(circle_closest_point(&c, vector_2f(1.5, 1.0)), vector_2f(1.5, 1.0));
+
+    let b = aabb(vector_2f(0.0, 0.0), vector_2f(2.0, 1.0));
+    assert_close_2f(aabb_closest_point(&b, vector_2f(5.0, -3.0)), vector_2f(2.0, 0.0));
+
+    let o = obb(vector_2f(0.0, 0.0), vector_2f(2.0, 1.0), std::f32::consts::FRAC_PI_2);
+    assert_close_2f(obb_closest_point(&o, vector_2f(3.0, 0.0)), vector_2f(1.0, 0.0));
+
+    let s = segment(vector_2f(0.0, 0.0), vector_2f(4.0, 0.0));
+    let (p, t) = segment_closest_point(&s, vector_2f(1.0, 3.0));
+    assert_close_2f(p, vector_2f(1.0, 0.0));
+    assert_close(t, 0.25);
+    assert_close_2f(segment_closest_point(&s, vector_2f(-3.0, 1.0)).0, vector_2f(0.0, 0.0));
+
+    let cross = segment(vector_2f(2.0, -1.0), vector_2f(2.0, 1.0));
+    assert_close_2f(segment_intersect_segment(&s, &cross).unwrap(), vector_2f(2.0, 0.0));
+    assert!(segment_intersect_segment(&s, &segment(vector_2f(5.0, -1.0), vector_2f(5.0, 1.0))).is_none());
+}
+
+#[test]
+fn geometry_ray_casts() {
+    let r = ray(vector_2f(-5.0, 0.0), vector_2f(1.0, 0.0));
+
+    let hit = ray_cast_circle(&r, &circle(vector_2f(0.0, 0.0), 2.0), 100.0).unwrap();
+    assert_close(hit.t, 3.0);
+    assert_close_2f(hit.point, vector_2f(-2.0, 0.0));
+    assert_close_2f(hit.normal, vector_2f(-1.0, 0.0));
+    assert!(ray_cast_circle(&r, &circle(vector_2f(0.0, 0.0), 2.0), 2.0).is_none());
+    assert!(ray_cast_circle(&r, &circle(vector_2f(0.0, 3.0), 2.0), 100.0).is_none());
+    // starting inside never hits
+    assert!(ray_cast_circle(&r, &circle(vector_2f(-5.0, 0.0), 1.0), 100.0).is_none());
+
+    let hit = ray_cast_aabb(&r, &aabb(vector_2f(-1.0, -1.0), vector_2f(1.0, 3.0)), 100.0).unwrap();
+    assert_close(hit.t, 4.0);
+    assert_close_2f(hit.normal, vector_2f(-1.0, 0.0));
+    let down = ray(vector_2f(0.0, 5.0), vector_2f(0.0, -2.0));
+    let hit = ray_cast_aabb(&down, &aabb(vector_2f(-1.0, -1.0), vector_2f(1.0, 3.0)), 100.0).unwrap();
+    assert_close(hit.t, 1.0);
+    assert_close_2f(hit.normal, vector_2f(0.0, 1.0));
+    assert!(ray_cast_aabb(&r, &aabb(vector_2f(-1.0, 1.0), vector_2f(1.0, 3.0)), 100.0).is_none());
+
+    let diamond = obb(vector_2f(0.0, 0.0), vector_2f(1.0, 1.0), FRAC_PI_4);
+    let hit = ray_cast_obb(&r, &diamond, 100.0).unwrap();
+    assert_close(hit.t, 5.0 - 2.0_f32.sqrt());
+    assert_close(hit.normal.x, -std::f32::consts::FRAC_1_SQRT_2);
+
+    let wall = segment(vector_2f(1.0, -1.0), vector_2f(1.0, 1.0));
+    let hit = ray_cast_segment(&r, &wall, 100.0).unwrap();
+    assert_close(hit.t, 6.0);
+    assert_close_2f(hit.normal, vector_2f(-1.0, 0.0));
+    assert!(ray_cast_segment(&ray(vector_2f(0.0, 2.0), vector_2f(1.0, 0.0)), &wall, 100.0).is_none());
+}
+
+#[test]
+fn geometry_swept_circles() {
+    // moving far enough in one frame to pass straight through without a sweep
+    let ship = circle(vector_2f(-10.0, 0.0), 1.0);
+    let planet = circle(vector_2f(0.0, 0.0), 2.0);
+    let delta = vector_2f(20.0, 0.0);
+    assert!(!circle_overlaps_circle(&circle(vector_2f_add(ship.center, delta), 1.0), &planet));
+
+    let hit = circle_sweep_circle(&ship, delta, &planet).unwrap();
+    assert_close(hit.t, 7.0 / 20.0);
+    assert_close_2f(hit.point, vector_2f(-2.0, 0.0));
+    assert_close_2f(hit.normal, vector_2f(-1.0, 0.0));
+    assert!(circle_sweep_circle(&ship, vector_2f(0.0, 20.0), &planet).is_none());
+    assert!(circle_sweep_circle(&ship, vector_2f(5.0, 0.0), &planet).is_none());
+
+    let b = aabb(vector_2f(-1.0, -1.0), vector_2f(1.0, 1.0));
+    let hit = circle_sweep_aabb(&ship, delta, &b).unwrap();
+    assert_close(hit.t, 8.0 / 20.0);
+    assert_close_2f(hit.point, vector_2f(-1.0, 0.0));
+
+    // cutting across the grown box's corner but missing the rounded corner
+    let corner_miss = circle(vector_2f(-10.0, -6.5), 1.0);
+    assert!(circle_sweep_aabb(&corner_miss, vector_2f(20.0, 20.0), &b).is_none());
+    let corner_hit = circle(vector_2f(-10.0, 1.5), 1.0);
+    let hit = circle_sweep_aabb(&corner_hit, delta, &b).unwrap();
+    assert_close_2f(hit.point, vector_2f(-1.0, 1.0));
+    assert_close(vector_2f_distance(vector_2f_add(corner_hit.center, vector_2f_scale(delta, hit.t)), hit.point), 1.0);
+}
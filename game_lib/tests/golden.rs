@@ -0,0 +1,308 @@
+// Golden image regression tests.
+//
+// Each test builds a deterministic GameState, renders it with the software renderer and
+// compares the frame against the reference image checked in under tests/golden/.
+// When a frame doesn't match, the actual frame and a diff image are written to
+// target/golden/ so the change can be inspected.
+//
+// To accept new visuals run the tests with GOLDEN_UPDATE=1 to rewrite the references.
+
+use std::path::{Path, PathBuf};
+
+use game::{
+    bitmap::*,
+    camera::*,
+    math::*,
+    nav::*,
+    orbit::*,
+    render::*,
+    render_software::*,
+    ship::*,
+    state::*,
+    update_and_render,
+    game_render,
+};
+
+const GOLDEN_WIDTH: i32 = 320;
+const GOLDEN_HEIGHT: i32 = 180;
+
+// Max difference allowed per color channel before a pixel counts as mismatched
+const GOLDEN_CHANNEL_TOLERANCE: u8 = 8;
+// Max number of mismatched pixels before the frame fails
+const GOLDEN_MAX_BAD_PIXELS: usize = 16;
+
+struct PpmImage {
+    width: i32,
+    height: i32,
+    rgb: Vec<u8>,
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn golden_output_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("target").join("golden")
+}
+
+fn ppm_from_bitmap(bitmap: &LoadedBitmap) -> PpmImage {
+    let mut rgb = Vec::with_capacity((bitmap.width * bitmap.height * 3) as usize);
+    for y in 0..bitmap.height {
+        for x in 0..bitmap.width {
+            let p = bitmap_get_pixel(bitmap, x, y);
+            rgb.extend_from_slice(&p[0..3]);
+        }
+    }
+    PpmImage { width: bitmap.width, height: bitmap.height, rgb }
+}
+
+fn ppm_write(image: &PpmImage, path: &Path) {
+    let mut bytes = format!("P6\n{} {}\n255\n", image.width, image.height).into_bytes();
+    bytes.extend_from_slice(&image.rgb);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, bytes).unwrap();
+}
+
+fn ppm_read(path: &Path) -> Option<PpmImage> {
+    let bytes = std::fs::read(path).ok()?;
+
+    // header is 4 whitespace separated tokens: magic, width, height, max value
+    let mut tokens = Vec::new();
+    let mut at = 0;
+    while tokens.len() < 4 {
+        while at < bytes.len() && bytes[at].is_ascii_whitespace() {
+            at += 1;
+        }
+        let start = at;
+        while at < bytes.len() && !bytes[at].is_ascii_whitespace() {
+            at += 1;
+        }
+        if start == at {
+            return None;
+        }
+        tokens.push(std::str::from_utf8(&bytes[start..at]).ok()?.to_string());
+    }
+    // single whitespace byte before the pixel data
+    at += 1;
+
+    if tokens[0] != "P6" || tokens[3] != "255" {
+        return None;
+    }
+    let width: i32 = tokens[1].parse().ok()?;
+    let height: i32 = tokens[2].parse().ok()?;
+    let rgb = bytes.get(at..)?.to_vec();
+    if rgb.len() != (width * height * 3) as usize {
+        return None;
+    }
+    Some(PpmImage { width, height, rgb })
+}
+
+fn golden_input() -> GameInput {
+    GameInput {
+        screen_width: GOLDEN_WIDTH,
+        screen_height: GOLDEN_HEIGHT,
+        frame_dt_sec: 1.0 / 30.0,
+        ..Default::default()
+    }
+}
+
+const GOLDEN_SEED: u64 = 0x5eed;
+const GOLDEN_WORLD_SIZE: Vector2f = Vector2f { x: 3200.0, y: 1800.0 };
+
+// Runs the init block of update_and_render once with a fixed seed and no input
+fn golden_state(input: &GameInput) -> GameState {
+    let mut ctx = GameState {
+        seed: GOLDEN_SEED,
+        ..Default::default()
+    };
+
+    let mut render_buffer = RenderBuffer::default();
+    update_and_render(input, &mut ctx, &mut render_buffer);
+    // the golden frame is too small a world to hold the planets, use the world of a 1600x900 window
+    ctx.world_size = GOLDEN_WORLD_SIZE;
+    if let Some(planets) = ctx.planets.as_mut() {
+        orbit_place_planets(ctx.gravity_g, &ctx.sun, planets, ctx.sim_time_sec, GOLDEN_WORLD_SIZE);
+    }
+    ctx.camera.initialized = false;
+    camera_update(&mut ctx, 0.0);
+    ctx
+}
+
+fn golden_place_player(ctx: &mut GameState, pos: Vector2f, rot: f32) {
+    ctx.player.pos = pos;
+    ctx.player.prev_pos = pos;
+    ctx.player.rot = rot;
+    ctx.player.prev_rot = rot;
+    ctx.camera = Camera { pos, initialized: true, ..Camera::default() };
+    ctx.mode = GameMode::Playing;
+    // the predicted path belongs to where the player was
+    ctx.nav_path = None;
+}
+
+fn golden_render(input: &GameInput, ctx: &GameState) -> PpmImage {
+    let mut render_buffer = RenderBuffer::default();
+    game_render(input, ctx, &mut render_buffer);
+
+    let mut renderer = software_renderer_create(input.screen_width, input.screen_height);
+    renderer.render(0, 0, input.screen_width, input.screen_height, &render_buffer);
+    ppm_from_bitmap(&renderer.framebuffer)
+}
+
+fn golden_check(name: &str, actual: &PpmImage) {
+    let reference_path = golden_dir().join(format!("{}.ppm", name));
+
+    if std::env::var("GOLDEN_UPDATE").is_ok() {
+        ppm_write(actual, &reference_path);
+        return;
+    }
+
+    let actual_path = golden_output_dir().join(format!("{}.actual.ppm", name));
+    let diff_path = golden_output_dir().join(format!("{}.diff.ppm", name));
+
+    let reference = match ppm_read(&reference_path) {
+        Some(r) => r,
+        None => {
+            ppm_write(actual, &actual_path);
+            panic!("Missing or unreadable golden image {:?}, actual frame written to {:?}. Run with GOLDEN_UPDATE=1 to create it.",
+                reference_path, actual_path);
+        }
+    };
+
+    assert!(reference.width == actual.width && reference.height == actual.height,
+        "{}: golden image is {}x{} but the frame is {}x{}",
+        name, reference.width, reference.height, actual.width, actual.height);
+
+    // diff image is black where pixels match and red where they don't
+    let mut diff = PpmImage { width: actual.width, height: actual.height, rgb: vec![0; actual.rgb.len()] };
+    let mut bad_pixels = 0;
+    let mut max_channel_diff = 0;
+    for (i, (a, r)) in actual.rgb.chunks_exact(3).zip(reference.rgb.chunks_exact(3)).enumerate() {
+        let channel_diff = (0..3).map(|c| a[c].abs_diff(r[c])).max().unwrap();
+        max_channel_diff = max_channel_diff.max(channel_diff);
+        if channel_diff > GOLDEN_CHANNEL_TOLERANCE {
+            bad_pixels += 1;
+            diff.rgb[i*3] = 0xFF;
+        }
+        else {
+            // keep a faint copy of the frame so the red pixels have context
+            diff.rgb[i*3 + 1] = a[1] / 4;
+            diff.rgb[i*3 + 2] = a[2] / 4;
+        }
+    }
+
+    if bad_pixels > GOLDEN_MAX_BAD_PIXELS {
+        ppm_write(actual, &actual_path);
+        ppm_write(&diff, &diff_path);
+        panic!("{}: {} pixels differ from the golden image (max channel diff {}). Actual frame {:?}, diff {:?}",
+            name, bad_pixels, max_channel_diff, actual_path, diff_path);
+    }
+}
+
+#[test]
+fn golden_title_screen() {
+    let input = golden_input();
+    let ctx = golden_state(&input);
+    golden_check("title_screen", &golden_render(&input, &ctx));
+}
+
+#[test]
+fn golden_planet_approach() {
+    let input = golden_input();
+    let mut ctx = golden_state(&input);
+
+    // hovering at the edge of the first planet with the nose pointing away from it
+    ctx.title_fade = 1.0;
+    golden_place_player(&mut ctx, vector_2f(-520.0, -330.0), 0.6);
+    ctx.debug_player_forces = Some(vec![
+        vector_2f(300.0, 200.0),
+        vector_2f(-200.0, -250.0),
+    ]);
+    golden_check("planet_approach", &golden_render(&input, &ctx));
+}
+
+#[test]
+fn golden_nav_path_and_hud() {
+    let input = golden_input();
+    let mut ctx = golden_state(&input);
+
+    ctx.title_fade = 1.0;
+    golden_place_player(&mut ctx, vector_2f(-200.0, 100.0), -2.0);
+    ctx.ship.fuel_level = 0.35;
+
+    let mut points = Vec::new();
+    for i in 0..32 {
+        let t = i as f32 / 31.0;
+        let p = vector_2f(-200.0 + 150.0 * t, 100.0 - 80.0 * t * t);
+        points.push(NavPoint::new(p, vector_3f(1.0 - t, t, 0.2)));
+    }
+    ctx.nav_path = Some(NavPath { points });
+    golden_check("nav_path_and_hud", &golden_render(&input, &ctx));
+}
+
+#[test]
+fn golden_predicted_path() {
+    let input = golden_input();
+    let mut ctx = golden_state(&input);
+
+    // drifting past the first planet close enough to be pulled into it
+    ctx.title_fade = 1.0;
+    golden_place_player(&mut ctx, vector_2f(-470.0, -250.0), -2.4);
+    ctx.player.d_pos = vector_2f(-60.0, 30.0);
+    ctx.nav_path = Some(nav_predict(&ctx, ctx.world_size, NAV_PREDICT_SEC));
+    golden_check("predicted_path", &golden_render(&input, &ctx));
+}
+
+#[test]
+fn golden_ship_explosion() {
+    let input = golden_input();
+    let mut ctx = golden_state(&input);
+
+    // part way through the explosion of a ship that crashed on its last life
+    ctx.title_fade = 1.0;
+    golden_place_player(&mut ctx, vector_2f(-300.0, -200.0), 0.0);
+    ship_destroy(&mut ctx);
+    ctx.respawn_sec = 0.4;
+    ctx.lives = 0;
+    golden_check("ship_explosion", &golden_render(&input, &ctx));
+}
+
+#[test]
+fn golden_paused() {
+    let input = golden_input();
+    let mut ctx = golden_state(&input);
+
+    ctx.title_fade = 1.0;
+    golden_place_player(&mut ctx, vector_2f(-200.0, 100.0), 1.0);
+    ctx.score = 120;
+    ctx.mode = GameMode::Paused;
+    golden_check("paused", &golden_render(&input, &ctx));
+}
+
+#[test]
+fn golden_game_over() {
+    let input = golden_input();
+    let mut ctx = golden_state(&input);
+
+    ctx.title_fade = 1.0;
+    golden_place_player(&mut ctx, vector_2f(-200.0, 100.0), 1.0);
+    ctx.lives = 0;
+    ship_destroy(&mut ctx);
+    ctx.respawn_sec = SHIP_RESPAWN_DELAY_SEC;
+    ctx.mode = GameMode::GameOver;
+    ctx.mode_sec = 1.0;
+    golden_check("game_over", &golden_render(&input, &ctx));
+}
+
+#[test]
+fn golden_camera_zoomed() {
+    let input = golden_input();
+    let mut ctx = golden_state(&input);
+
+    // pulled out over the first planet and turned a little, the HUD stays put
+    ctx.title_fade = 1.0;
+    golden_place_player(&mut ctx, vector_2f(-520.0, -330.0), 0.6);
+    ctx.camera.pos = vector_2f(-560.0, -370.0);
+    ctx.camera.zoom = 0.5;
+    ctx.camera.rot = 0.3;
+    golden_check("camera_zoomed", &golden_render(&input, &ctx));
+}
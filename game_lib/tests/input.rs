@@ -0,0 +1,73 @@
+use game::{
+    camera::*,
+    input::*,
+    math::*,
+    render::*,
+    state::*,
+};
+
+#[test]
+fn input_button_transitions() {
+    let mut input = GameInput::default();
+
+    // pressed and held
+    input_begin_frame(&mut input);
+    button_process(&mut input.launch, true);
+    button_process(&mut input.launch, true);
+    assert!(button_pressed(&input.launch));
+    assert!(!button_released(&input.launch));
+    assert!(button_held(&input.launch));
+
+    input_begin_frame(&mut input);
+    assert!(!button_pressed(&input.launch));
+    assert!(button_held(&input.launch));
+
+    // released then tapped again before the next frame
+    input_begin_frame(&mut input);
+    button_process(&mut input.launch, false);
+    button_process(&mut input.launch, true);
+    button_process(&mut input.launch, false);
+    assert_eq!(input.launch.half_transition_count, 3);
+    assert!(button_pressed(&input.launch));
+    assert!(button_released(&input.launch));
+    assert!(button_held(&input.launch));
+
+    input_begin_frame(&mut input);
+    assert!(!button_held(&input.launch));
+    assert!(!button_released(&input.launch));
+}
+
+#[test]
+fn input_mouse_world_pos() {
+    let mut input = GameInput { screen_width: 1600, screen_height: 900, ..GameInput::default() };
+    let mut ctx = GameState::default();
+    ctx.camera.pos = vector_2f(100.0, -50.0);
+
+    // the window center is the camera and y goes up in the world
+    input.mouse_pos = vector_2f(800.0, 450.0);
+    input_update_mouse_world_pos(&mut input, &ctx);
+    assert_eq!(input.mouse_world_pos, ctx.camera.pos);
+    input.mouse_pos = vector_2f(0.0, 0.0);
+    input_update_mouse_world_pos(&mut input, &ctx);
+    assert_eq!(input.mouse_world_pos, vector_2f(-700.0, 400.0));
+
+    // zoomed out twice as much world fits
+    ctx.camera.zoom = 0.5;
+    input_update_mouse_world_pos(&mut input, &ctx);
+    assert_eq!(input.mouse_world_pos, vector_2f(-1500.0, 850.0));
+
+    ctx.camera.rot = 0.7;
+    let view = camera_view(&ctx.camera);
+    let world = vector_2f(333.0, 12.5);
+    let screen = render_world_to_screen(input.screen_width, input.screen_height, &view, world);
+    let back = render_screen_to_world(input.screen_width, input.screen_height, &view, screen);
+    assert!(vector_2f_distance(world, back) < 0.001);
+
+    // per frame events don't carry over
+    input.mouse_wheel = 1.0;
+    input.focus_lost = true;
+    input.resized = true;
+    input_begin_frame(&mut input);
+    assert_eq!(input.mouse_wheel, 0.0);
+    assert!(!input.focus_lost && !input.resized);
+}
@@ -0,0 +1,112 @@
+use game::{
+    item::*,
+    math::*,
+    state::*,
+};
+
+mod common;
+use common::*;
+
+// The ship moved from prev_pos to pos this step
+fn item_move_ship(ctx: &mut GameState, prev_pos: Vector2f, pos: Vector2f) {
+    ctx.player.prev_pos = prev_pos;
+    ctx.player.pos = pos;
+}
+
+fn item_ship() -> Ship {
+    Ship {
+        fuel_level: 0.25,
+        fuel_burn_rate: 0.03,
+        hull: 0.25,
+        inventory: Vec::new(),
+        inventory_capacity: 2,
+        ..Ship::default()
+    }
+}
+
+#[test]
+fn item_effects() {
+    let mut ship = item_ship();
+    let mut score = 0;
+
+    assert!(item_collect(&mut ship, &mut score, PlanetItemType::Fuel));
+    assert!(ship.fuel_level > 0.25 && ship.fuel_level <= 1.0);
+    assert!(item_collect(&mut ship, &mut score, PlanetItemType::Repair));
+    assert!(ship.hull > 0.25 && ship.hull <= 1.0);
+
+    // consumables are left behind when they would do nothing
+    ship.fuel_level = 1.0;
+    ship.hull = 1.0;
+    assert!(!item_collect(&mut ship, &mut score, PlanetItemType::Fuel));
+    assert!(!item_collect(&mut ship, &mut score, PlanetItemType::Repair));
+    assert!(!item_collect(&mut ship, &mut score, PlanetItemType::None));
+
+    let gem_score = score;
+    assert!(item_collect(&mut ship, &mut score, PlanetItemType::Gem));
+    assert!(score > gem_score);
+    assert!(ship.inventory.is_empty());
+}
+
+#[test]
+fn item_inventory_capacity() {
+    let mut ship = item_ship();
+    let mut score = 0;
+
+    assert!(item_collect(&mut ship, &mut score, PlanetItemType::Cargo));
+    assert!(item_collect(&mut ship, &mut score, PlanetItemType::Key));
+    assert!(!item_collect(&mut ship, &mut score, PlanetItemType::Key));
+    assert_eq!(ship.inventory, vec![PlanetItemType::Cargo, PlanetItemType::Key]);
+}
+
+#[test]
+fn item_pickup_in_reach() {
+    let ctx = &mut test_state(&test_input());
+
+    let planet = &ctx.planets.as_ref().unwrap()[0];
+    let item = planet.items[0].clone();
+    let item_count = planet.items.len();
+    let item_pos = vector_2f_add(planet.pos, item.pos);
+    let gem = planet.items.iter().find(|i| i.itype == PlanetItemType::Gem).unwrap();
+    let gem_pos = vector_2f_add(planet.pos, gem.pos);
+    assert_eq!(item.itype, PlanetItemType::Fuel);
+
+    // a full tank leaves the fuel where it is
+    let pos = vector_2f_add(item_pos, vector_2f(0.5 * ITEM_PICKUP_RADIUS, 0.0));
+    item_move_ship(ctx, pos, pos);
+    item_pickup(ctx);
+    assert_eq!(ctx.planets.as_ref().unwrap()[0].items.len(), item_count);
+
+    ctx.ship.fuel_level = 0.1;
+    item_pickup(ctx);
+    assert_eq!(ctx.planets.as_ref().unwrap()[0].items.len(), item_count - 1);
+    assert!(ctx.ship.fuel_level > 0.1);
+
+    let pos = vector_2f_add(gem_pos, vector_2f(0.0, 2.0 * ITEM_PICKUP_RADIUS));
+    item_move_ship(ctx, pos, pos);
+    item_pickup(ctx);
+    assert_eq!(ctx.score, 0);
+    item_move_ship(ctx, pos, gem_pos);
+    item_pickup(ctx);
+    assert!(ctx.score > 0);
+}
+
+#[test]
+fn item_pickup_sweeps_fast_ships() {
+    let ctx = &mut test_state(&test_input());
+    let planet = &mut ctx.planets.as_mut().unwrap()[0];
+    planet.prev_pos = planet.pos;
+    let gem = planet.items.iter().find(|i| i.itype == PlanetItemType::Gem).unwrap();
+    let gem_pos = vector_2f_add(planet.pos, gem.pos);
+    let across = vector_2f(10.0 * ITEM_PICKUP_RADIUS, 0.0);
+
+    // flying past to one side in a single step misses it
+    let beside = vector_2f_add(gem_pos, vector_2f(0.0, 2.0 * ITEM_PICKUP_RADIUS));
+    item_move_ship(ctx, vector_2f_sub(beside, across), vector_2f_add(beside, across));
+    item_pickup(ctx);
+    assert_eq!(ctx.score, 0);
+
+    // straight over it in a single step, out of reach at both ends, still picks it up
+    item_move_ship(ctx, vector_2f_sub(gem_pos, across), vector_2f_add(gem_pos, across));
+    item_pickup(ctx);
+    assert!(ctx.score > 0);
+}
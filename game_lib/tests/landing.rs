@@ -0,0 +1,73 @@
+use game::{
+    math::*,
+    physics::*,
+    state::*,
+};
+
+mod common;
+use common::*;
+
+// Default level with the ship just above the first planet's landing zone falling straight
+// down onto it at speed
+fn landing_state(input: &GameInput, speed: f32) -> GameState {
+    let mut ctx = test_state(input);
+
+    let planet = &ctx.planets.as_ref().unwrap()[0];
+    let up = vector_2f_normalize(planet.lz_rel_pos);
+    let pos = vector_2f_add(planet.pos, vector_2f_scale(up, planet.surface_radius + 1.0));
+    ctx.player.pos = pos;
+    ctx.player.prev_pos = pos;
+    ctx.player.rot = vector_2f_angle(up);
+    ctx.player.prev_rot = ctx.player.rot;
+    ctx.player.d_pos = vector_2f_scale(up, -speed);
+    ctx
+}
+
+#[test]
+fn landing_outcome_rules() {
+    let planet = Planet {
+        pos: vector_2f(0.0, 0.0),
+        radius: 100.0,
+        surface_radius: 100.0,
+        lz_rel_pos: vector_2f(0.0, 100.0),
+        ..Planet::default()
+    };
+    let up = vector_2f(0.0, 1.0);
+    let rot = std::f32::consts::FRAC_PI_2;
+    let slow = vector_2f(0.0, -0.5 * LANDING_MAX_SPEED);
+    let world = vector_2f(3200.0, 1800.0);
+
+    let on_pad = vector_2f(0.0, 100.0);
+    assert_eq!(physics_landing_outcome(&planet, on_pad, up, slow, rot, world), LandingOutcome::Landed);
+    assert_eq!(physics_landing_outcome(&planet, on_pad, up, vector_2f(0.0, -2.0 * LANDING_MAX_SPEED), rot, world), LandingOutcome::Crashed);
+    assert_eq!(physics_landing_outcome(&planet, on_pad, up, slow, rot + 2.0 * LANDING_MAX_TILT, world), LandingOutcome::Crashed);
+
+    let off_pad = vector_2f(100.0, 0.0);
+    assert_eq!(physics_landing_outcome(&planet, off_pad, vector_2f(1.0, 0.0), slow, 0.0, world), LandingOutcome::TouchedDown);
+}
+
+#[test]
+fn landing_on_pad_refuels() {
+    let input = test_input();
+    let mut ctx = landing_state(&input, 20.0);
+    ctx.ship.fuel_level = 0.5;
+
+    test_run(&input, &mut ctx, 1.0);
+    assert_eq!(ctx.landing_outcome, LandingOutcome::Landed);
+    assert!(ctx.player.landed);
+    assert!(ctx.ship.fuel_level > 0.5);
+    assert_eq!(ctx.ship.hull, 1.0);
+}
+
+#[test]
+fn landing_too_fast_crashes() {
+    let input = test_input();
+    let mut ctx = landing_state(&input, 4.0 * LANDING_MAX_SPEED);
+
+    test_run(&input, &mut ctx, 2.0 * input.frame_dt_sec);
+    assert_eq!(ctx.landing_outcome, LandingOutcome::Crashed);
+    assert!(ctx.ship.hull < 1.0);
+    // bounced back off the surface
+    let planet = &ctx.planets.as_ref().unwrap()[0];
+    assert!(vector_2f_dot(ctx.player.d_pos, vector_2f_normalize(planet.lz_rel_pos)) > 0.0);
+}
@@ -0,0 +1,50 @@
+use game::{
+    level::*,
+    state::*,
+};
+
+#[test]
+fn level_default_round_trip() {
+    let level = level_parse(DEFAULT_LEVEL).unwrap();
+    assert_eq!(level.planets.len(), 4);
+
+    // writing and reading back loses nothing, so a second write is the same text
+    let text = level_write(&level);
+    let parsed = level_parse(&text).unwrap();
+    assert_eq!(level_write(&parsed), text);
+
+    assert_eq!(parsed.gravity_g, level.gravity_g);
+    assert_eq!((parsed.sun.pos, parsed.sun.mass), (level.sun.pos, level.sun.mass));
+    assert_eq!((parsed.spawn_pos, parsed.spawn_rot), (level.spawn_pos, level.spawn_rot));
+    for (a, b) in parsed.planets.iter().zip(level.planets.iter()) {
+        assert_eq!((a.pos, a.radius, a.g_radius, a.mass), (b.pos, b.radius, b.g_radius, b.mass));
+        assert_eq!((a.color, a.lz_rel_pos, a.lz_color), (b.color, b.lz_rel_pos, b.lz_color));
+        let items = |p: &Planet| p.items.iter().map(|i| (i.itype, i.pos)).collect::<Vec<_>>();
+        assert_eq!(items(a), items(b));
+    }
+}
+
+#[test]
+fn level_parse_errors() {
+    let header = "sun mass=1\nspawn pos=0,0\n";
+    let planet = "planet pos=100,0 radius=10 g_radius=20 mass=1 color=1,1,1,1\n";
+    let items: String = (0..=MAX_PLANET_ITEMS).map(|_| "item type=fuel pos=15,0\n").collect();
+    let errors = [
+        (format!("{}planet pos=100,0 radius=10 g_radius=10 mass=1 color=1,1,1,1\n", header), 3, "planet g_radius 10 must be greater than radius 10".to_string()),
+        (format!("{}{}{}", header, planet, items), 4 + MAX_PLANET_ITEMS, format!("a planet can hold at most {} items", MAX_PLANET_ITEMS)),
+        (format!("{}planet pos=100,0 radius=10 g_radius=20 mass=1 color=1,1,1,1 size=3\n", header), 3, "unknown planet key 'size'".to_string()),
+        (format!("{}planet pos=100,0 radius=ten g_radius=20 mass=1 color=1,1,1,1\n", header), 3, "'radius=ten' is not a number".to_string()),
+        (format!("{}{}item type=fuel pos=15\n", header, planet), 4, "'pos' expects 2 values but has 1".to_string()),
+        (format!("# no sun\nspawn pos=0,0\n{}", planet), 0, "level has no sun".to_string()),
+        (format!("sun mass=1\n{}", planet), 0, "level has no spawn".to_string()),
+        (format!("{}\nsun mass=2\n", header), 4, "only one sun is allowed, the first is on line 1".to_string()),
+        (format!("{}moon pos=0,0\n", header), 3, "unknown entity 'moon'".to_string()),
+    ];
+    for (text, line, message) in errors {
+        let e = match level_parse(&text) {
+            Ok(_) => panic!("level was accepted:\n{}", text),
+            Err(e) => e,
+        };
+        assert_eq!((e.line, e.message.as_str()), (line, message.as_str()), "{}", text);
+    }
+}
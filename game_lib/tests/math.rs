@@ -0,0 +1,301 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use game::{
+    math::*,
+    render::*,
+};
+
+const EPSILON: f32 = 1.0e-5;
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() <= EPSILON, "{} != {}", a, b);
+}
+
+fn assert_close_2f(a: Vector2f, b: Vector2f) {
+    assert!(vector_2f_distance(a, b) <= EPSILON, "{:?} != {:?}", a, b);
+}
+
+fn assert_close_3f(a: Vector3f, b: Vector3f) {
+    assert!(vector_3f_distance(a, b) <= EPSILON, "{:?} != {:?}", a, b);
+}
+
+fn assert_close_4f(a: Vector4f, b: Vector4f) {
+    assert!(vector_4f_distance(a, b) <= EPSILON, "{:?} != {:?}", a, b);
+}
+
+//
+// Vector2f
+//
+
+#[test]
+fn vector_2f_component_ops() {
+    let a = vector_2f(1.0, -2.0);
+    let b = vector_2f(3.0, 4.0);
+    assert_eq!(vector_2f_add(a, b), vector_2f(4.0, 2.0));
+    assert_eq!(vector_2f_sub(a, b), vector_2f(-2.0, -6.0));
+    assert_eq!(vector_2f_scale(a, 2.0), vector_2f(2.0, -4.0));
+    assert_eq!(vector_2f_neg(a), vector_2f(-1.0, 2.0));
+    assert_eq!(vector_2f_hadamard(a, b), vector_2f(3.0, -8.0));
+    assert_eq!(vector_2f_div(b, vector_2f(2.0, 8.0)), vector_2f(1.5, 0.5));
+    assert_eq!(vector_2f_min(a, b), vector_2f(1.0, -2.0));
+    assert_eq!(vector_2f_max(a, b), vector_2f(3.0, 4.0));
+    assert_eq!(vector_2f_clamp(vector_2f(5.0, -5.0), vector_2f(0.0, 0.0), vector_2f(1.0, 1.0)), vector_2f(1.0, 0.0));
+    assert_eq!(vector_2f_lerp(a, b, 0.5), vector_2f(2.0, 1.0));
+}
+
+#[test]
+fn vector_2f_products_and_lengths() {
+    let a = vector_2f(3.0, 4.0);
+    assert_close(vector_2f_dot(a, vector_2f(2.0, 1.0)), 10.0);
+    assert_close(vector_2f_length(a), 5.0);
+    assert_close(vector_2f_length_squared(a), 25.0);
+    assert_close(vector_2f_distance(a, vector_2f(0.0, 0.0)), 5.0);
+    assert_close(vector_2f_distance_squared(a, vector_2f(3.0, 0.0)), 16.0);
+
+    // perp dot is positive when b is counter clockwise from a
+    assert_close(vector_2f_cross(vector_2f_unitx(), vector_2f_unity()), 1.0);
+    assert_close(vector_2f_cross(vector_2f_unity(), vector_2f_unitx()), -1.0);
+    assert_close(vector_2f_cross(a, a), 0.0);
+    assert_eq!(vector_2f_perp(vector_2f_unitx()), vector_2f_unity());
+}
+
+#[test]
+fn vector_2f_normalize_is_safe() {
+    assert_close_2f(vector_2f_normalize(vector_2f(0.0, 5.0)), vector_2f_unity());
+    assert_eq!(vector_2f_normalize(vector_2f_zero()), vector_2f_zero());
+    assert_eq!(vector_2f_normalize_or(vector_2f_zero(), vector_2f_unitx()), vector_2f_unitx());
+    assert_close_2f(vector_2f_normalize_or(vector_2f(-2.0, 0.0), vector_2f_unity()), vector_2f(-1.0, 0.0));
+}
+
+#[test]
+fn vector_2f_reflect_and_project() {
+    // ball falling onto the floor bounces up
+    assert_close_2f(vector_2f_reflect(vector_2f(1.0, -1.0), vector_2f_unity()), vector_2f(1.0, 1.0));
+    assert_close_2f(vector_2f_project(vector_2f(3.0, 4.0), vector_2f(10.0, 0.0)), vector_2f(3.0, 0.0));
+    assert_eq!(vector_2f_project(vector_2f(3.0, 4.0), vector_2f_zero()), vector_2f_zero());
+}
+
+#[test]
+fn vector_2f_angles() {
+    assert_close_2f(vector_2f_rotate(vector_2f_unitx(), FRAC_PI_2), vector_2f_unity());
+    assert_close_2f(vector_2f_rotate(vector_2f(1.0, 1.0), PI), vector_2f(-1.0, -1.0));
+    assert_close_2f(vector_2f_from_angle(FRAC_PI_2), vector_2f_unity());
+    assert_close(vector_2f_angle(vector_2f(0.0, -2.0)), -FRAC_PI_2);
+    assert_close(vector_2f_angle_between(vector_2f_unitx(), vector_2f(0.0, -3.0)), FRAC_PI_2);
+    assert_close(vector_2f_angle_between(vector_2f_unitx(), vector_2f(-1.0, 0.0)), PI);
+    assert_close(vector_2f_angle_between(vector_2f_unitx(), vector_2f_zero()), 0.0);
+    assert_close(vector_2f_signed_angle(vector_2f_unitx(), vector_2f(0.0, -3.0)), -FRAC_PI_2);
+    assert_close(vector_2f_signed_angle(vector_2f_unity(), vector_2f(-1.0, 0.0)), FRAC_PI_2);
+}
+
+//
+// Vector3f
+//
+
+#[test]
+fn vector_3f_component_ops() {
+    let a = vector_3f(1.0, -2.0, 3.0);
+    let b = vector_3f(4.0, 5.0, -6.0);
+    assert_eq!(vector_3f_add(a, b), vector_3f(5.0, 3.0, -3.0));
+    assert_eq!(vector_3f_sub(a, b), vector_3f(-3.0, -7.0, 9.0));
+    assert_eq!(vector_3f_scale(a, -1.0), vector_3f_neg(a));
+    assert_eq!(vector_3f_hadamard(a, b), vector_3f(4.0, -10.0, -18.0));
+    assert_eq!(vector_3f_div(b, vector_3f(2.0, 5.0, -3.0)), vector_3f(2.0, 1.0, 2.0));
+    assert_eq!(vector_3f_min(a, b), vector_3f(1.0, -2.0, -6.0));
+    assert_eq!(vector_3f_max(a, b), vector_3f(4.0, 5.0, 3.0));
+    assert_eq!(vector_3f_clamp(a, vector_3f_zero(), vector_3f(2.0, 2.0, 2.0)), vector_3f(1.0, 0.0, 2.0));
+    assert_eq!(vector_3f_lerp(vector_3f_zero(), b, 0.25), vector_3f(1.0, 1.25, -1.5));
+}
+
+#[test]
+fn vector_3f_products_and_lengths() {
+    let x = vector_3f(1.0, 0.0, 0.0);
+    let y = vector_3f(0.0, 1.0, 0.0);
+    let z = vector_3f(0.0, 0.0, 1.0);
+    assert_close_3f(vector_3f_cross(x, y), z);
+    assert_close_3f(vector_3f_cross(y, x), vector_3f_neg(z));
+    assert_close(vector_3f_dot(vector_3f(1.0, 2.0, 3.0), vector_3f(4.0, -5.0, 6.0)), 12.0);
+    assert_close(vector_3f_length(vector_3f(2.0, 3.0, 6.0)), 7.0);
+    assert_close(vector_3f_distance(vector_3f(1.0, 1.0, 1.0), vector_3f(3.0, 4.0, 7.0)), 7.0);
+    assert_close(vector_3f_distance_squared(x, y), 2.0);
+}
+
+#[test]
+fn vector_3f_normalize_reflect_project() {
+    assert_close_3f(vector_3f_normalize(vector_3f(0.0, 0.0, -4.0)), vector_3f(0.0, 0.0, -1.0));
+    assert_eq!(vector_3f_normalize(vector_3f_zero()), vector_3f_zero());
+    let up = vector_3f(0.0, 1.0, 0.0);
+    assert_eq!(vector_3f_normalize_or(vector_3f_zero(), up), up);
+    assert_close_3f(vector_3f_reflect(vector_3f(1.0, -2.0, 3.0), up), vector_3f(1.0, 2.0, 3.0));
+    assert_close_3f(vector_3f_project(vector_3f(1.0, 2.0, 3.0), up), vector_3f(0.0, 2.0, 0.0));
+}
+
+#[test]
+fn vector_3f_angles() {
+    let x = vector_3f(1.0, 0.0, 0.0);
+    let y = vector_3f(0.0, 1.0, 0.0);
+    let z = vector_3f(0.0, 0.0, 1.0);
+    assert_close_3f(vector_3f_rotate(x, z, FRAC_PI_2), y);
+    assert_close_3f(vector_3f_rotate(x, vector_3f_scale(x, 3.0), 1.0), x);
+    assert_close(vector_3f_angle_between(x, vector_3f(1.0, 1.0, 0.0)), PI / 4.0);
+    assert_close(vector_3f_angle_between(y, vector_3f_neg(y)), PI);
+}
+
+#[test]
+fn vector_3f_conversions() {
+    let v = vector_3f_from_2f(vector_2f(1.0, 2.0), 3.0);
+    assert_eq!(v, vector_3f(1.0, 2.0, 3.0));
+    assert_eq!(vector_3f_xy(v), vector_2f(1.0, 2.0));
+}
+
+//
+// Vector4f
+//
+
+#[test]
+fn vector_4f_component_ops() {
+    let a = vector_4f(1.0, 2.0, 3.0, 4.0);
+    let b = vector_4f(-1.0, 0.5, 3.0, 8.0);
+    assert_eq!(vector_4f_add(a, b), vector_4f(0.0, 2.5, 6.0, 12.0));
+    assert_eq!(vector_4f_sub(a, b), vector_4f(2.0, 1.5, 0.0, -4.0));
+    assert_eq!(vector_4f_scale(a, 0.5), vector_4f(0.5, 1.0, 1.5, 2.0));
+    assert_eq!(vector_4f_neg(b), vector_4f(1.0, -0.5, -3.0, -8.0));
+    assert_eq!(vector_4f_hadamard(a, b), vector_4f(-1.0, 1.0, 9.0, 32.0));
+    assert_eq!(vector_4f_div(a, vector_4f(1.0, 2.0, 3.0, 4.0)), vector_4f(1.0, 1.0, 1.0, 1.0));
+    assert_eq!(vector_4f_min(a, b), vector_4f(-1.0, 0.5, 3.0, 4.0));
+    assert_eq!(vector_4f_max(a, b), vector_4f(1.0, 2.0, 3.0, 8.0));
+    let zero = vector_4f_zero();
+    let one = vector_4f(1.0, 1.0, 1.0, 1.0);
+    assert_eq!(vector_4f_clamp(b, zero, one), vector_4f(0.0, 0.5, 1.0, 1.0));
+    assert_close_4f(vector_4f_lerp(zero, a, 0.5), vector_4f(0.5, 1.0, 1.5, 2.0));
+}
+
+#[test]
+fn vector_4f_products_and_lengths() {
+    let a = vector_4f(1.0, 1.0, 1.0, 1.0);
+    assert_close(vector_4f_dot(a, vector_4f(1.0, 2.0, 3.0, 4.0)), 10.0);
+    assert_close(vector_4f_length(a), 2.0);
+    assert_close(vector_4f_length_squared(a), 4.0);
+    assert_close(vector_4f_distance(a, vector_4f_zero()), 2.0);
+    assert_close(vector_4f_distance_squared(a, vector_4f_zero()), 4.0);
+    assert_close_4f(vector_4f_normalize(a), vector_4f(0.5, 0.5, 0.5, 0.5));
+    assert_eq!(vector_4f_normalize(vector_4f_zero()), vector_4f_zero());
+    assert_eq!(vector_4f_normalize_or(vector_4f_zero(), a), a);
+}
+
+#[test]
+fn vector_4f_reflect_project_angles() {
+    let w = vector_4f(0.0, 0.0, 0.0, 1.0);
+    assert_close_4f(vector_4f_reflect(vector_4f(1.0, 2.0, 3.0, -4.0), w), vector_4f(1.0, 2.0, 3.0, 4.0));
+    assert_close_4f(vector_4f_project(vector_4f(1.0, 2.0, 3.0, 4.0), w), vector_4f(0.0, 0.0, 0.0, 4.0));
+    assert_close(vector_4f_angle_between(w, vector_4f(1.0, 0.0, 0.0, 0.0)), FRAC_PI_2);
+}
+
+#[test]
+fn vector_4f_conversions() {
+    let v = vector_4f_from_3f(vector_3f(0.1, 0.2, 0.3), 1.0);
+    assert_eq!(v, vector_4f(0.1, 0.2, 0.3, 1.0));
+    assert_eq!(vector_4f_xyz(v), vector_3f(0.1, 0.2, 0.3));
+}
+
+//
+// Matrices
+//
+
+fn assert_close_matrix_3f(a: &Matrix3f, b: &Matrix3f) {
+    for i in 0..9 {
+        assert!((a.e[i] - b.e[i]).abs() <= EPSILON, "{:?} != {:?}", a, b);
+    }
+}
+
+fn assert_close_matrix_4f(a: &Matrix4f, b: &Matrix4f) {
+    for i in 0..16 {
+        assert!((a.e[i] - b.e[i]).abs() <= EPSILON, "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn matrix_3f_transforms() {
+    let p = vector_2f(2.0, 3.0);
+    assert_close_2f(matrix_3f_transform_point(&matrix_3f_translate(vector_2f(1.0, -1.0)), p), vector_2f(3.0, 2.0));
+    assert_close_2f(matrix_3f_transform_vector(&matrix_3f_translate(vector_2f(1.0, -1.0)), p), p);
+    assert_close_2f(matrix_3f_transform_point(&matrix_3f_scale(vector_2f(2.0, 0.5)), p), vector_2f(4.0, 1.5));
+    assert_close_2f(matrix_3f_transform_point(&matrix_3f_rotate_z(FRAC_PI_2), p), vector_2f(-3.0, 2.0));
+
+    // trs is the same as composing the three
+    let t = vector_2f(10.0, -4.0);
+    let s = vector_2f(3.0, 2.0);
+    let composed = matrix_3f_multiply(&matrix_3f_translate(t),
+        &matrix_3f_multiply(&matrix_3f_rotate_z(0.7), &matrix_3f_scale(s)));
+    assert_close_matrix_3f(&matrix_3f_trs(t, 0.7, s), &composed);
+
+    // multiply applies the right hand side first
+    let scale_then_move = matrix_3f_multiply(&matrix_3f_translate(t), &matrix_3f_scale(s));
+    assert_close_2f(matrix_3f_transform_point(&scale_then_move, vector_2f(1.0, 1.0)), vector_2f(13.0, -2.0));
+}
+
+#[test]
+fn matrix_3f_inverse_and_projection() {
+    let m = matrix_3f_trs(vector_2f(5.0, 7.0), 1.3, vector_2f(2.0, 4.0));
+    let inv = matrix_3f_inverse(&m).unwrap();
+    assert_close_matrix_3f(&matrix_3f_multiply(&m, &inv), &matrix_3f_identity());
+    assert!(matrix_3f_inverse(&matrix_3f_scale(vector_2f(0.0, 1.0))).is_none());
+
+    // a tiny determinant is still invertible
+    let small = matrix_3f_scale(vector_2f(1.0e-4, 1.0e-4));
+    let inv = matrix_3f_inverse(&small).unwrap();
+    assert_close_matrix_3f(&matrix_3f_multiply(&small, &inv), &matrix_3f_identity());
+
+    let ortho = matrix_3f_orthographic(-100.0, 100.0, -50.0, 50.0);
+    assert_close_2f(matrix_3f_transform_point(&ortho, vector_2f(100.0, -50.0)), vector_2f(1.0, -1.0));
+}
+
+#[test]
+fn matrix_4f_transforms() {
+    let p = vector_3f(2.0, 3.0, 4.0);
+    assert_close_3f(matrix_4f_transform_point(&matrix_4f_translate(vector_3f(1.0, 2.0, 3.0)), p), vector_3f(3.0, 5.0, 7.0));
+    assert_close_3f(matrix_4f_transform_vector(&matrix_4f_translate(vector_3f(1.0, 2.0, 3.0)), p), p);
+    assert_close_3f(matrix_4f_transform_point(&matrix_4f_scale(vector_3f(2.0, 3.0, 4.0)), p), vector_3f(4.0, 9.0, 16.0));
+    assert_close_3f(matrix_4f_transform_point(&matrix_4f_rotate_z(FRAC_PI_2), p), vector_3f(-3.0, 2.0, 4.0));
+
+    let t = vector_3f(1.0, 2.0, 3.0);
+    let s = vector_3f(2.0, 2.0, 0.5);
+    let composed = matrix_4f_multiply(&matrix_4f_translate(t),
+        &matrix_4f_multiply(&matrix_4f_rotate_z(-0.4), &matrix_4f_scale(s)));
+    assert_close_matrix_4f(&matrix_4f_trs(t, -0.4, s), &composed);
+
+    // a 2D transform embedded in 4D moves points the same way
+    let m3 = matrix_3f_trs(vector_2f(4.0, -2.0), 0.3, vector_2f(2.0, 5.0));
+    let m4 = matrix_4f_from_3f(&m3);
+    let q = matrix_4f_transform_point(&m4, vector_3f(1.0, 1.0, 0.0));
+    assert_close_2f(vector_3f_xy(q), matrix_3f_transform_point(&m3, vector_2f(1.0, 1.0)));
+}
+
+#[test]
+fn matrix_4f_inverse_and_projection() {
+    let m = matrix_4f_multiply(
+        &matrix_4f_trs(vector_3f(5.0, -7.0, 2.0), 0.9, vector_3f(2.0, 3.0, 4.0)),
+        &matrix_4f_orthographic(-10.0, 30.0, -5.0, 15.0, 0.0, 100.0));
+    let inv = matrix_4f_inverse(&m).unwrap();
+    assert_close_matrix_4f(&matrix_4f_multiply(&m, &inv), &matrix_4f_identity());
+    assert_close_matrix_4f(&matrix_4f_multiply(&inv, &m), &matrix_4f_identity());
+    assert!(matrix_4f_inverse(&matrix_4f_scale(vector_3f(1.0, 0.0, 1.0))).is_none());
+
+    // the world projection maps whole pixels to tiny clip space steps, its determinant
+    // is tiny but it still has to invert for screen to world
+    let camera = render_camera(vector_2f(120.0, -40.0), 1.0, 0.0);
+    for camera in [RenderCamera::default(), camera] {
+        let projection = render_layer_projection(1600, 900, &camera, RenderLayer::World);
+        let inv = matrix_4f_inverse(&projection).unwrap();
+        assert_close_matrix_4f(&matrix_4f_multiply(&projection, &inv), &matrix_4f_identity());
+    }
+    let small = matrix_4f_scale(vector_3f(1.0e-3, 1.0e-3, 1.0e-3));
+    let inv = matrix_4f_inverse(&small).unwrap();
+    assert_close_matrix_4f(&matrix_4f_multiply(&small, &inv), &matrix_4f_identity());
+
+    // same mapping as glOrtho, the near plane goes to -1
+    let ortho = matrix_4f_orthographic(-100.0, 100.0, -50.0, 50.0, 0.0, 100.0);
+    assert_close_3f(matrix_4f_transform_point(&ortho, vector_3f(-100.0, 50.0, 0.0)), vector_3f(-1.0, 1.0, -1.0));
+    assert_close_3f(matrix_4f_transform_point(&ortho, vector_3f(0.0, 0.0, -100.0)), vector_3f(0.0, 0.0, 1.0));
+    assert_eq!(matrix_4f_as_ptr(&ortho), ortho.e.as_ptr());
+}
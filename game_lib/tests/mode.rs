@@ -0,0 +1,138 @@
+use game::{
+    input::*,
+    math::*,
+    ship::*,
+    state::*,
+    game_set_mode,
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn mode_title_waits_for_input() {
+    let input = test_input();
+    let mut ctx = GameState::default();
+    test_frame(&input, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::Title);
+
+    // nothing moves on the title screen
+    ctx.player.d_pos = vector_2f(50.0, 0.0);
+    let pos = ctx.player.pos;
+    for _ in 0..10 {
+        test_frame(&input, &mut ctx);
+    }
+    assert_eq!(ctx.player.pos, pos);
+
+    test_frame(&GameInput { accelerate: game_button(true, 1), ..input }, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::Playing);
+}
+
+#[test]
+fn mode_pause_toggles_once_per_press() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+    ctx.player.d_pos = vector_2f(50.0, 0.0);
+
+    let pressed = GameInput { pause: game_button(true, 1), ..input };
+    let held = GameInput { pause: game_button(true, 0), ..input };
+    let released = GameInput { pause: game_button(false, 1), ..input };
+    test_frame(&pressed, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::Paused);
+
+    // holding the button keeps it paused and frozen
+    let pos = ctx.player.pos;
+    for _ in 0..10 {
+        test_frame(&held, &mut ctx);
+    }
+    test_frame(&released, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::Paused);
+    assert_eq!(ctx.player.pos, pos);
+
+    // a tap that starts and ends between two frames still counts
+    test_frame(&GameInput { pause: game_button(false, 2), ..input }, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::Playing);
+    test_frame(&input, &mut ctx);
+    assert_ne!(ctx.player.pos, pos);
+
+    // losing focus pauses too
+    test_frame(&GameInput { focus_lost: true, ..input }, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::Paused);
+}
+
+#[test]
+fn mode_game_over_and_restart() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+    ctx.lives = 0;
+    ctx.score = 50;
+    ship_destroy(&mut ctx);
+
+    for _ in 0..(SHIP_RESPAWN_DELAY_SEC / input.frame_dt_sec) as u32 + 2 {
+        test_frame(&input, &mut ctx);
+    }
+    assert_eq!(ctx.mode, GameMode::GameOver);
+
+    // launch is ignored until the overlay has been up for a moment
+    let launch = GameInput { launch: game_button(true, 1), ..input };
+    test_frame(&launch, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::GameOver);
+    for _ in 0..60 {
+        test_frame(&input, &mut ctx);
+    }
+    test_frame(&launch, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::Playing);
+    assert_eq!(ctx.score, 0);
+    assert_eq!(ctx.lives, SHIP_START_LIVES);
+    assert!(!ctx.ship.destroyed);
+    assert!(ctx.planets.is_some());
+}
+
+#[test]
+fn mode_level_complete_on_pad_without_gems() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+
+    // collected every gem and sitting on the first pad
+    for planet in ctx.planets.as_mut().unwrap() {
+        planet.items.retain(|item| item.itype != PlanetItemType::Gem);
+    }
+    let planet = &ctx.planets.as_ref().unwrap()[0];
+    let up = vector_2f_normalize(planet.lz_rel_pos);
+    ctx.player.pos = vector_2f_add(planet.pos, vector_2f_scale(up, planet.surface_radius - 0.01));
+    ctx.player.landed = true;
+    ctx.landing_outcome = LandingOutcome::Landed;
+    test_frame(&input, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::LevelComplete);
+
+    for _ in 0..60 {
+        test_frame(&input, &mut ctx);
+    }
+    test_frame(&GameInput { launch: game_button(true, 1), ..input }, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::Playing);
+    assert_eq!(ctx.level_number, 1);
+    assert_eq!(ctx.player.pos, ctx.spawn_pos);
+}
+
+#[test]
+fn mode_generated_level_is_not_complete_on_landing() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+    game_set_mode(&mut ctx, GameMode::LevelComplete);
+    for _ in 0..60 {
+        test_frame(&input, &mut ctx);
+    }
+    test_frame(&GameInput { launch: game_button(true, 1), ..input }, &mut ctx);
+    assert_eq!(ctx.level_number, 1);
+
+    // the next level is generated and always has a gem left to find, so a safe landing
+    // straight away doesn't finish it
+    let planet = &ctx.planets.as_ref().unwrap()[0];
+    let up = vector_2f_normalize(planet.lz_rel_pos);
+    ctx.player.pos = vector_2f_add(planet.pos, vector_2f_scale(up, planet.surface_radius - 0.01));
+    ctx.player.d_pos = planet.d_pos;
+    ctx.player.landed = true;
+    ctx.landing_outcome = LandingOutcome::Landed;
+    test_frame(&input, &mut ctx);
+    assert_eq!(ctx.mode, GameMode::Playing);
+}
@@ -0,0 +1,167 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+mod common;
+use common::*;
+
+use game::{
+    level::*,
+    math::*,
+    orbit::*,
+    physics::*,
+    state::*,
+};
+
+fn orbit_assert_near(a: Vector2f, b: Vector2f, tolerance: f32) {
+    assert!(vector_2f_distance(a, b) <= tolerance, "{:?} is not near {:?}", a, b);
+}
+
+#[test]
+fn orbit_circular_and_elliptical() {
+    let mu = 1.0e6;
+
+    // a circle starts where it was made through and goes round counter clockwise
+    let circle = orbit_circular(OrbitParent::Sun, vector_2f(300.0, 0.0), false);
+    let period = orbit_period(&circle, mu);
+    orbit_assert_near(orbit_state(&circle, mu, 0.0).0, vector_2f(300.0, 0.0), 0.01);
+    orbit_assert_near(orbit_state(&circle, mu, 0.25 * period).0, vector_2f(0.0, 300.0), 0.1);
+    orbit_assert_near(orbit_state(&circle, mu, period).0, vector_2f(300.0, 0.0), 0.1);
+    let clockwise = Orbit { clockwise: true, ..circle };
+    orbit_assert_near(orbit_state(&clockwise, mu, 0.25 * period).0, vector_2f(0.0, -300.0), 0.1);
+
+    // an ellipse starts at periapsis, is at apoapsis half a period later and is fastest
+    // at periapsis, by vis viva
+    let ellipse = Orbit {
+        parent: OrbitParent::Sun,
+        semi_major: 400.0,
+        eccentricity: 0.5,
+        periapsis_rot: FRAC_PI_2,
+        phase: 0.0,
+        clockwise: false,
+    };
+    let period = orbit_period(&ellipse, mu);
+    let (periapsis, periapsis_d_pos) = orbit_state(&ellipse, mu, 0.0);
+    orbit_assert_near(periapsis, vector_2f(0.0, 200.0), 0.01);
+    orbit_assert_near(orbit_state(&ellipse, mu, 0.5 * period).0, vector_2f(0.0, -600.0), 0.1);
+    let vis_viva = (mu * (2.0 / 200.0 - 1.0 / 400.0)).sqrt();
+    assert!((vector_2f_length(periapsis_d_pos) - vis_viva).abs() < 0.01 * vis_viva);
+}
+
+#[test]
+fn orbit_moons_follow_their_planet() {
+    let sun = Sun { mass: 1000.0, ..Sun::default() };
+    let planet = Planet {
+        mass: 200.0,
+        orbit: Some(orbit_circular(OrbitParent::Sun, vector_2f(600.0, 0.0), false)),
+        ..Planet::default()
+    };
+    let moon = Planet {
+        orbit: Some(orbit_circular(OrbitParent::Planet(0), vector_2f(0.0, 80.0), true)),
+        ..Planet::default()
+    };
+    let mut planets = vec![planet, moon];
+
+    for step in 0..20 {
+        orbit_place_planets(PHYSICS_DEFAULT_G, &sun, &mut planets, step as f32 * 3.0, vector_2f_zero());
+        assert!((vector_2f_length(planets[0].pos) - 600.0).abs() < 0.1);
+        assert!((vector_2f_distance(planets[1].pos, planets[0].pos) - 80.0).abs() < 0.1);
+    }
+}
+
+#[test]
+fn orbit_leapfrog_keeps_a_coasting_ship_in_orbit() {
+    // a ship on a circular orbit around a planet that keeps still, stepped the way the
+    // simulation steps it, neither spirals in nor flies off over many orbits
+    let sun = Sun::default();
+    let planet = Planet { radius: 50.0, surface_radius: 50.0, g_radius: 150.0, mass: 500.0, ..Planet::default() };
+    let planets = [planet];
+    let world = vector_2f(3200.0, 1800.0);
+    let r = 200.0;
+    let mu = PHYSICS_DEFAULT_G * planets[0].mass;
+    let dt = 1.0 / 60.0;
+
+    let mut pos = vector_2f(r, 0.0);
+    let mut d_pos = vector_2f(0.0, (mu / r).sqrt());
+    let orbits = 10.0;
+    let steps = (orbits * TAU * (r * r * r / mu).sqrt() / dt) as u32;
+    for _ in 0..steps {
+        let gravity = physics_gravity(PHYSICS_DEFAULT_G, &sun, &planets, pos, d_pos, world, None);
+        d_pos = vector_2f_add(d_pos, vector_2f_scale(gravity.accel, 0.5 * dt));
+        pos = vector_2f_add(pos, vector_2f_scale(d_pos, dt));
+        let gravity = physics_gravity(PHYSICS_DEFAULT_G, &sun, &planets, pos, d_pos, world, None);
+        d_pos = vector_2f_add(d_pos, vector_2f_scale(gravity.accel, 0.5 * dt));
+        assert!((vector_2f_length(pos) - r).abs() < 0.01 * r, "orbit drifted to {}", vector_2f_length(pos));
+    }
+}
+
+#[test]
+fn orbit_level_lines() {
+    let text = "
+        gravity g=500
+        sun     pos=0,0 mass=2000
+        spawn   pos=0,0 rot=0
+        planet  pos=400,0 radius=50 g_radius=150 mass=800 color=1,1,1,1
+        orbit   around=sun
+        planet  pos=0,0 radius=10 g_radius=15 mass=1 color=1,1,1,1
+        orbit   around=1 a=100 e=0.2 periapsis=0 phase=0 dir=cw
+    ";
+    let level = level_parse(text).unwrap();
+    assert_eq!(level.gravity_g, 500.0);
+    assert_eq!(level.sun.softening, PHYSICS_DEFAULT_SUN_SOFTENING);
+    orbit_assert_near(level.planets[0].pos, vector_2f(400.0, 0.0), 0.01);
+    // the moon's pos is wherever its orbit puts it, at periapsis to start with
+    orbit_assert_near(level.planets[1].pos, vector_2f(480.0, 0.0), 0.01);
+    assert_eq!(level.planets[1].orbit.unwrap().parent, OrbitParent::Planet(0));
+
+    let written = level_parse(&level_write(&level)).unwrap();
+    for (a, b) in written.planets.iter().zip(level.planets.iter()) {
+        orbit_assert_near(a.pos, b.pos, 0.01);
+        assert_eq!(a.orbit.unwrap().semi_major, b.orbit.unwrap().semi_major);
+        assert_eq!(a.orbit.unwrap().clockwise, b.orbit.unwrap().clockwise);
+    }
+
+    let planet = "sun mass=1\nspawn pos=0,0\nplanet pos=100,0 radius=10 g_radius=20 mass=1 color=1,1,1,1\n";
+    let errors = [
+        ("sun mass=1\nspawn pos=0,0\norbit around=sun\n".to_string(), 3, "orbit must come after the planet it belongs to"),
+        (format!("{}orbit around=1\n", planet), 4, "an orbit goes around the sun or an earlier planet, not '1'"),
+        (format!("{}orbit a=50 e=0.95\n", planet), 4, "orbit e must be between 0 and 0.9"),
+        (format!("{}orbit e=0.5\n", planet), 4, "orbit needs 'a' for e, periapsis or phase"),
+        (format!("{}orbit\norbit\n", planet), 5, "a planet can only have one orbit"),
+        (format!("{}orbit dir=up\n", planet), 4, "unknown orbit dir 'up'"),
+        (format!("gravity g=0\n{}", planet), 1, "gravity g must be greater than 0"),
+        ("sun mass=1\nspawn pos=0,0\nplanet pos=0,0 radius=10 g_radius=20 mass=1 color=1,1,1,1\norbit\n".to_string(), 0, "planet 1 sits at the center of its own orbit"),
+    ];
+    for (text, line, message) in errors {
+        let e = match level_parse(&text) {
+            Ok(_) => panic!("level was accepted:\n{}", text),
+            Err(e) => e,
+        };
+        assert_eq!((e.line, e.message.as_str()), (line, message), "{}", text);
+    }
+}
+
+#[test]
+fn orbit_landed_ship_rides_its_planet() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+
+    // sitting on the first planet's pad
+    let planet = &ctx.planets.as_ref().unwrap()[0];
+    let up = vector_2f_normalize(planet.lz_rel_pos);
+    let pos = vector_2f_add(planet.pos, vector_2f_scale(up, planet.surface_radius - PHYSICS_CONTACT_SKIN));
+    ctx.player.pos = pos;
+    ctx.player.prev_pos = pos;
+    ctx.player.rot = vector_2f_angle(up);
+    ctx.player.prev_rot = ctx.player.rot;
+    ctx.player.d_pos = planet.d_pos;
+    ctx.player.landed = true;
+    ctx.landing_outcome = LandingOutcome::Landed;
+    let start_planet_pos = planet.pos;
+    let start_rel_pos = vector_2f_sub(pos, planet.pos);
+
+    test_run(&input, &mut ctx, 5.0);
+    let planet = &ctx.planets.as_ref().unwrap()[0];
+    assert!(vector_2f_distance(planet.pos, start_planet_pos) > 50.0);
+    assert!(ctx.player.landed);
+    assert_eq!(ctx.landing_outcome, LandingOutcome::Landed);
+    orbit_assert_near(vector_2f_sub(ctx.player.pos, planet.pos), start_rel_pos, 1.0);
+}
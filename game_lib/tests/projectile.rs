@@ -0,0 +1,98 @@
+use game::{
+    input::*,
+    math::*,
+    projectile::*,
+    state::*,
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn projectile_pool_reuses_slots() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+
+    for _ in 0..MAX_PROJECTILES + 5 {
+        projectile_fire(&mut ctx);
+    }
+    assert_eq!(ctx.projectiles.len(), MAX_PROJECTILES);
+
+    ctx.projectiles[3].alive = false;
+    projectile_fire(&mut ctx);
+    assert_eq!(ctx.projectiles.len(), MAX_PROJECTILES);
+    assert!(ctx.projectiles.iter().all(|p| p.alive));
+}
+
+#[test]
+fn projectile_fired_in_flight_hits_planet() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+
+    // in the first planet's well with the nose pointing at it
+    let planet = ctx.planets.as_ref().unwrap()[0].clone();
+    ctx.player.pos = vector_2f_add(planet.pos, vector_2f(0.0, planet.radius + 60.0));
+    ctx.player.rot = -std::f32::consts::FRAC_PI_2;
+
+    test_frame(&GameInput { launch: game_button(true, 1), ..input }, &mut ctx);
+    // held down it only fires once
+    test_frame(&GameInput { launch: game_button(true, 0), ..input }, &mut ctx);
+    assert_eq!(ctx.projectiles.iter().filter(|p| p.alive).count(), 1);
+
+    let mut hit = None;
+    for _ in 0..60 {
+        let events = test_frame(&input, &mut ctx);
+        if let Some(e) = events.iter().find(|e| matches!(e, GameEvent::ProjectileHit { .. })) {
+            hit = Some(*e);
+            break;
+        }
+    }
+    match hit {
+        Some(GameEvent::ProjectileHit { pos, target }) => {
+            assert_eq!(target, ProjectileTarget::Planet(0));
+            // on the surface where the planet has got to on its orbit
+            let planet_pos = ctx.planets.as_ref().unwrap()[0].pos;
+            assert!((vector_2f_distance(pos, planet_pos) - planet.surface_radius).abs() < 0.1);
+        },
+        _ => panic!("projectile never hit the planet"),
+    }
+    assert!(ctx.projectiles.iter().all(|p| !p.alive));
+}
+
+#[test]
+fn projectile_expires_and_falls() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+
+    // fired sideways near a planet the shot curves toward it
+    let planet = ctx.planets.as_ref().unwrap()[0].clone();
+    ctx.player.pos = vector_2f_add(planet.pos, vector_2f(0.0, planet.g_radius - 10.0));
+    ctx.player.rot = 0.0;
+    projectile_fire(&mut ctx);
+    test_frame(&input, &mut ctx);
+    assert!(ctx.projectiles[0].d_pos.y < 0.0);
+
+    for _ in 0..(PROJECTILE_LIFETIME_SEC / input.frame_dt_sec) as u32 + 2 {
+        test_frame(&input, &mut ctx);
+    }
+    assert!(!ctx.projectiles[0].alive);
+}
+
+#[test]
+fn projectile_launch_when_landed() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+
+    let planet = ctx.planets.as_ref().unwrap()[0].clone();
+    let up = vector_2f_normalize(planet.lz_rel_pos);
+    ctx.player.pos = vector_2f_add(planet.pos, vector_2f_scale(up, planet.surface_radius - 0.01));
+    ctx.player.rot = vector_2f_angle(up);
+    ctx.player.landed = true;
+    let fuel = ctx.ship.fuel_level;
+
+    test_frame(&GameInput { launch: game_button(true, 1), ..input }, &mut ctx);
+    assert!(!ctx.player.landed);
+    assert!(vector_2f_dot(ctx.player.d_pos, up) > 0.0);
+    assert!(ctx.ship.fuel_level < fuel);
+    assert!(ctx.projectiles.is_empty());
+}
@@ -0,0 +1,63 @@
+use game::{
+    math::*,
+    random::*,
+};
+
+fn random_sequence(seed: u64, count: usize) -> Vec<u32> {
+    let mut series = random_seed(seed);
+    (0..count).map(|_| random_next_u32(&mut series)).collect()
+}
+
+#[test]
+fn random_seed_reproduces_sequence() {
+    assert_eq!(random_sequence(42, 64), random_sequence(42, 64));
+    assert_eq!(random_seed(0), RandomSeries::default());
+
+    // neighbouring seeds share nothing, not even a shifted copy of each other
+    let a = random_sequence(42, 64);
+    let b = random_sequence(43, 64);
+    assert_ne!(a, b);
+    assert!(a.iter().zip(b.iter()).filter(|(x, y)| x == y).count() <= 1);
+    assert_ne!(random_sequence(0, 64), random_sequence(u64::MAX, 64));
+}
+
+#[test]
+fn random_choice_stays_below_count() {
+    let mut series = random_seed(7);
+    for _ in 0..1000 {
+        assert_eq!(random_choice(&mut series, 1), 0);
+    }
+
+    for count in [2, 3, 6, 1000, u32::MAX] {
+        let mut seen_last = false;
+        for _ in 0..10000 {
+            let choice = random_choice(&mut series, count);
+            assert!(choice < count, "{} is not below {}", choice, count);
+            seen_last |= choice == count - 1;
+        }
+        // small counts reach every value, the top one included
+        if count <= 6 {
+            assert!(seen_last, "{} never came up", count - 1);
+        }
+    }
+}
+
+#[test]
+fn random_floats_stay_in_range() {
+    let mut series = random_seed(1234);
+    for _ in 0..10000 {
+        let u = random_unilateral(&mut series);
+        assert!((0.0..1.0).contains(&u), "unilateral {}", u);
+
+        let b = random_bilateral(&mut series);
+        assert!((-1.0..1.0).contains(&b), "bilateral {}", b);
+
+        let between = random_between(&mut series, -5.0, 3.0);
+        assert!((-5.0..3.0).contains(&between), "between {}", between);
+
+        let v = random_unit_vector_2f(&mut series);
+        assert!((vector_2f_length(v) - 1.0).abs() < 1.0e-5);
+
+        assert!(random_gaussian(&mut series, 0.0, 1.0).is_finite());
+    }
+}
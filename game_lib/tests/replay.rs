@@ -0,0 +1,112 @@
+use game::{
+    input::*,
+    math::*,
+    replay::*,
+    save::*,
+    state::*,
+};
+
+fn replay_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    replay_write_header(&mut bytes, &GameState::default());
+    replay_write_input(&mut bytes, &GameInput { frame_dt_sec: 1.0 / 60.0, ..GameInput::default() });
+    bytes
+}
+
+#[test]
+fn replay_round_trip() {
+    let inputs = [
+        GameInput {
+            screen_width: 1600,
+            screen_height: 900,
+            frame_dt_sec: 1.0 / 60.0,
+            accelerate: game_button(true, 0),
+            turn_left: game_button(false, 2),
+            pause: game_button(true, 3),
+            ..GameInput::default()
+        },
+        GameInput {
+            screen_width: 800,
+            screen_height: 450,
+            frame_dt_sec: 1.0 / 30.0,
+            launch: game_button(false, 1),
+            accelerate: GameButton { ended_down: true, half_transition_count: 0, value: 0.6 },
+            mouse_pos: vector_2f(12.0, 340.5),
+            mouse_world_pos: vector_2f(-400.0, 25.0),
+            mouse_right: game_button(true, 1),
+            mouse_wheel: -2.0,
+            has_focus: true,
+            focus_gained: true,
+            controller: GameController {
+                connected: true,
+                left_stick: vector_2f(0.5, -0.25),
+                right_trigger: 0.75,
+                dpad_left: game_button(true, 1),
+                ..GameController::default()
+            },
+            ..GameInput::default()
+        },
+    ];
+
+    let mut bytes = Vec::new();
+    replay_write_header(&mut bytes, &GameState { seed: 77, ..GameState::default() });
+    for input in inputs.iter() {
+        replay_write_input(&mut bytes, input);
+    }
+    // a record cut short by a crash is dropped
+    bytes.extend_from_slice(&[1, 2, 3]);
+
+    let replay = replay_read(&bytes).unwrap();
+    assert_eq!(save_read(&replay.snapshot).unwrap().seed, 77);
+    assert_eq!(replay.inputs.len(), inputs.len());
+    for (read, written) in replay.inputs.iter().zip(inputs.iter()) {
+        assert_eq!(read.screen_width, written.screen_width);
+        assert_eq!(read.frame_dt_sec, written.frame_dt_sec);
+        assert_eq!(input_buttons(read), input_buttons(written));
+        assert_eq!(read.mouse_pos, written.mouse_pos);
+        assert_eq!(read.mouse_world_pos, written.mouse_world_pos);
+        assert_eq!(read.mouse_wheel, written.mouse_wheel);
+        assert_eq!(
+            (read.has_focus, read.focus_gained, read.focus_lost, read.resized),
+            (written.has_focus, written.focus_gained, written.focus_lost, written.resized));
+        assert_eq!(read.controller.connected, written.controller.connected);
+        assert_eq!(read.controller.left_stick, written.controller.left_stick);
+        assert_eq!(read.controller.right_trigger, written.controller.right_trigger);
+    }
+}
+
+#[test]
+fn replay_rejects_bad_headers() {
+    let good = replay_bytes();
+    assert!(replay_read(&good).is_some());
+
+    let mut bad_magic = good.clone();
+    bad_magic[0] = b'X';
+    assert!(replay_read(&bad_magic).is_none());
+
+    // any other version is refused rather than misread, older or newer
+    for version in [REPLAY_VERSION - 1, REPLAY_VERSION + 1] {
+        let mut other_version = good.clone();
+        other_version[4..8].copy_from_slice(&version.to_le_bytes());
+        assert!(replay_read(&other_version).is_none(), "version {} was read", version);
+    }
+
+    assert!(replay_read(&[]).is_none());
+    assert!(replay_read(&REPLAY_MAGIC).is_none());
+}
+
+#[test]
+fn replay_rejects_truncated_snapshot() {
+    let bytes = replay_bytes();
+    let snapshot_size = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+    let snapshot_end = 12 + snapshot_size;
+
+    // cut anywhere before the snapshot ends there is nothing to restore
+    for len in 0..snapshot_end {
+        assert!(replay_read(&bytes[..len]).is_none(), "replay cut at {} was read", len);
+    }
+    // cut in the first input record the snapshot is still there, with no inputs
+    let replay = replay_read(&bytes[..snapshot_end + 1]).unwrap();
+    assert!(replay.inputs.is_empty());
+    assert!(save_read(&replay.snapshot).is_ok());
+}
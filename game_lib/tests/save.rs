@@ -0,0 +1,117 @@
+use game::{
+    math::*,
+    save::*,
+    state::*,
+};
+
+mod common;
+use common::*;
+
+// A game part way through: the default level with its orbits and items, a shot in flight,
+// a predicted path and a few things changed from their defaults
+fn save_state() -> GameState {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+
+    let pos = vector_2f(0.0, 300.0);
+    ctx.player.pos = pos;
+    ctx.player.prev_pos = pos;
+    ctx.player.d_pos = vector_2f(40.0, -10.0);
+    test_run(&input, &mut ctx, 0.5);
+    ctx.seed = 99;
+
+    ctx.projectiles.push(Projectile {
+        pos: vector_2f(10.0, 20.0),
+        prev_pos: vector_2f(8.0, 19.0),
+        d_pos: vector_2f(120.0, 60.0),
+        age_sec: 0.5,
+        alive: true,
+    });
+    ctx.ship.inventory.push(PlanetItemType::Gem);
+    ctx.score = 1234;
+    ctx.lives = 2;
+    ctx.last_landing_planet = Some(1);
+    ctx.mode = GameMode::Paused;
+    ctx
+}
+
+#[test]
+fn save_round_trip() {
+    let ctx = save_state();
+    let planets = ctx.planets.as_ref().unwrap();
+    assert!(planets.iter().all(|p| p.orbit.is_some()));
+    assert!(planets.iter().any(|p| !p.items.is_empty()));
+    assert!(ctx.nav_path.is_some());
+
+    let bytes = save_write(&ctx);
+    let loaded = save_read(&bytes).unwrap();
+    // everything saved comes back, so writing it again gives the same file
+    assert_eq!(save_write(&loaded), bytes);
+
+    assert_eq!(loaded.mode, GameMode::Paused);
+    assert_eq!(loaded.seed, 99);
+    assert_eq!(loaded.rng, ctx.rng);
+    assert_eq!(loaded.sim_time_sec, ctx.sim_time_sec);
+    assert_eq!(loaded.gravity_g, ctx.gravity_g);
+    assert_eq!(loaded.player.pos, ctx.player.pos);
+    assert_eq!(loaded.ship.inventory, vec![PlanetItemType::Gem]);
+    assert_eq!((loaded.score, loaded.lives, loaded.last_landing_planet), (1234, 2, Some(1)));
+
+    let loaded_planets = loaded.planets.as_ref().unwrap();
+    assert_eq!(loaded_planets.len(), planets.len());
+    for (a, b) in loaded_planets.iter().zip(planets.iter()) {
+        assert_eq!((a.pos, a.prev_pos, a.d_pos, a.mass), (b.pos, b.prev_pos, b.d_pos, b.mass));
+        assert_eq!(a.items.len(), b.items.len());
+        let (oa, ob) = (a.orbit.unwrap(), b.orbit.unwrap());
+        assert_eq!((oa.parent, oa.semi_major, oa.periapsis_rot, oa.clockwise), (ob.parent, ob.semi_major, ob.periapsis_rot, ob.clockwise));
+    }
+
+    assert_eq!(loaded.projectiles.len(), 1);
+    assert_eq!(loaded.projectiles[0].d_pos, vector_2f(120.0, 60.0));
+    assert!(loaded.projectiles[0].alive);
+    assert_eq!(loaded.nav_path.unwrap().points.len(), ctx.nav_path.unwrap().points.len());
+}
+
+#[test]
+fn save_skips_unknown_fields() {
+    let ctx = save_state();
+    let mut bytes = save_write(&ctx);
+
+    // a field from a newer version, slipped in right after the header
+    let mut unknown = Vec::new();
+    unknown.extend_from_slice(&999u16.to_le_bytes());
+    unknown.extend_from_slice(&3u32.to_le_bytes());
+    unknown.extend_from_slice(&[1, 2, 3]);
+    bytes.splice(8..8, unknown);
+
+    let loaded = save_read(&bytes).unwrap();
+    assert_eq!(save_write(&loaded), save_write(&ctx));
+}
+
+#[test]
+fn save_rejects_other_versions_and_bad_magic() {
+    // older versions are refused too, their fields may have meant something else
+    for version in [SAVE_VERSION + 1, SAVE_VERSION - 1] {
+        let mut bytes = save_write(&save_state());
+        bytes[4..8].copy_from_slice(&version.to_le_bytes());
+        match save_read(&bytes) {
+            Err(SaveError::UnsupportedVersion { found, supported }) => {
+                assert_eq!((found, supported), (version, SAVE_VERSION));
+            },
+            _ => panic!("a version {} save was not rejected", version),
+        }
+    }
+
+    let mut bytes = save_write(&save_state());
+    bytes[0..4].copy_from_slice(b"NOPE");
+    assert!(matches!(save_read(&bytes), Err(SaveError::NotASaveFile)));
+    assert!(matches!(save_read(&[]), Err(SaveError::NotASaveFile)));
+}
+
+#[test]
+fn save_rejects_truncated_files() {
+    let bytes = save_write(&save_state());
+    for len in 0..bytes.len() {
+        assert!(save_read(&bytes[..len]).is_err(), "save cut at {} of {} bytes was read", len, bytes.len());
+    }
+}
@@ -0,0 +1,67 @@
+use game::{
+    math::*,
+    ship::*,
+    state::*,
+};
+
+mod common;
+use common::*;
+
+#[test]
+fn ship_destroyed_and_respawned_on_last_pad() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+    let lives = ctx.lives;
+    ctx.last_landing_planet = Some(1);
+    ctx.ship.inventory.push(PlanetItemType::Cargo);
+
+    ship_damage(&mut ctx, 0.4);
+    assert!(!ctx.ship.destroyed);
+    ship_damage(&mut ctx, 0.7);
+    assert!(ctx.ship.destroyed);
+    assert_eq!(ctx.events, vec![GameEvent::Explosion { pos: ctx.player.pos }]);
+
+    test_run(&input, &mut ctx, 0.5 * SHIP_RESPAWN_DELAY_SEC);
+    assert!(ctx.ship.destroyed);
+    assert!(ctx.nav_path.is_none());
+
+    test_run(&input, &mut ctx, SHIP_RESPAWN_DELAY_SEC);
+    assert!(!ctx.ship.destroyed);
+    assert_eq!(ctx.lives, lives - 1);
+    assert_eq!(ctx.ship.hull, 1.0);
+    assert!(ctx.ship.inventory.is_empty());
+    assert!(ctx.player.landed);
+
+    let planet = &ctx.planets.as_ref().unwrap()[1];
+    let pad = vector_2f_add(planet.pos, planet.lz_rel_pos);
+    assert!(vector_2f_distance(ctx.player.pos, pad) < 1.0);
+}
+
+#[test]
+fn ship_without_lives_stays_destroyed() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+    ctx.lives = 0;
+
+    ship_destroy(&mut ctx);
+    test_run(&input, &mut ctx, 2.0 * SHIP_RESPAWN_DELAY_SEC);
+    assert!(ctx.ship.destroyed);
+}
+
+#[test]
+fn ship_stranded_without_fuel() {
+    let input = test_input();
+    let mut ctx = test_state(&input);
+    let spawn_pos = ctx.player.pos;
+    ctx.ship.fuel_level = 0.0;
+
+    let events = test_run(&input, &mut ctx, SHIP_STRANDED_SEC + 0.1);
+    assert!(ctx.ship.destroyed);
+    assert_eq!(events.len(), 1);
+
+    // nowhere landed yet so it's back to the level spawn
+    test_run(&input, &mut ctx, SHIP_RESPAWN_DELAY_SEC);
+    assert!(!ctx.ship.destroyed);
+    assert_eq!(ctx.player.pos, spawn_pos);
+    assert_eq!(ctx.ship.fuel_level, 1.0);
+}
@@ -0,0 +1,76 @@
+use game::{
+    math::*,
+    state::*,
+    SIM_DEFAULT_STEP_SEC,
+    SIM_MAX_FRAME_SEC,
+};
+
+mod common;
+use common::*;
+
+fn sim_input(frame_dt_sec: f32) -> GameInput {
+    GameInput { frame_dt_sec, ..test_input() }
+}
+
+// Default level with the ship coasting through open space
+fn sim_state() -> GameState {
+    let mut ctx = test_state(&test_input());
+
+    let pos = vector_2f(0.0, 300.0);
+    ctx.player.pos = pos;
+    ctx.player.prev_pos = pos;
+    ctx.player.d_pos = vector_2f(50.0, 0.0);
+    ctx
+}
+
+// Runs frames of frame_dt_sec until total_sec has passed and returns where the ship is drawn
+fn sim_run(frame_dt_sec: f32, total_sec: f32) -> Vector2f {
+    let mut ctx = sim_state();
+    let input = sim_input(frame_dt_sec);
+    let frames = (total_sec / frame_dt_sec).round() as u32;
+    for _ in 0..frames {
+        test_frame(&input, &mut ctx);
+        assert!((0.0..1.0).contains(&ctx.sim_alpha), "sim_alpha {} at frame dt {}", ctx.sim_alpha, frame_dt_sec);
+    }
+    assert!(!ctx.player.landed);
+    vector_2f_lerp(ctx.player.prev_pos, ctx.player.pos, ctx.sim_alpha)
+}
+
+#[test]
+fn sim_fixed_step_is_independent_of_frame_rate() {
+    // the drawn position is interpolated between steps, so it's the same whether the last
+    // step landed just before or just after the end of the last frame
+    let at_30 = sim_run(1.0 / 30.0, 2.0);
+    let at_144 = sim_run(1.0 / 144.0, 2.0);
+    assert!(vector_2f_distance(at_30, at_144) < 0.05, "{:?} != {:?}", at_30, at_144);
+    let at_60 = sim_run(SIM_DEFAULT_STEP_SEC, 2.0);
+    assert!(vector_2f_distance(at_30, at_60) < 0.05, "{:?} != {:?}", at_30, at_60);
+}
+
+#[test]
+fn sim_long_frames_are_capped() {
+    let mut ctx = sim_state();
+
+    // a half second frame only simulates SIM_MAX_FRAME_SEC of it
+    test_frame(&sim_input(0.5), &mut ctx);
+    let steps = (ctx.sim_time_sec / ctx.sim_step_sec).round();
+    let max_steps = (SIM_MAX_FRAME_SEC / ctx.sim_step_sec).round();
+    assert!(steps <= max_steps && steps >= max_steps - 1.0, "{} steps in one frame", steps);
+    assert!((0.0..1.0).contains(&ctx.sim_alpha));
+
+    // so two seconds of half second frames fall behind real time
+    let capped = sim_run(0.5, 2.0);
+    let smooth = sim_run(1.0 / 60.0, 2.0);
+    assert!(vector_2f_distance(capped, smooth) > 10.0);
+    let quarter = sim_run(1.0 / 60.0, 4.0 * SIM_MAX_FRAME_SEC);
+    assert!(vector_2f_distance(capped, quarter) < 0.05, "{:?} != {:?}", capped, quarter);
+}
+
+#[test]
+fn sim_bad_step_falls_back_to_default() {
+    for step in [0.0, -1.0, f32::INFINITY, f32::NAN] {
+        let mut ctx = GameState { sim_step_sec: step, ..GameState::default() };
+        test_frame(&test_input(), &mut ctx);
+        assert_eq!(ctx.sim_step_sec, SIM_DEFAULT_STEP_SEC);
+    }
+}
@@ -0,0 +1,79 @@
+use game::{
+    math::*,
+    physics::*,
+    state::*,
+    world::*,
+};
+
+const WORLD_SIZE: Vector2f = Vector2f { x: 1000.0, y: 600.0 };
+
+fn world_planet(pos: Vector2f) -> Planet {
+    Planet {
+        radius: 40.0,
+        pos,
+        prev_pos: pos,
+        g_radius: 150.0,
+        mass: 100.0,
+        surface_radius: 40.0,
+        ..Planet::default()
+    }
+}
+
+#[test]
+fn world_wrap_and_delta() {
+    // anything past an edge comes back in however far out it is
+    assert_eq!(world_wrap(vector_2f(510.0, -310.0), WORLD_SIZE), vector_2f(-490.0, 290.0));
+    assert_eq!(world_wrap(vector_2f(2510.0, 0.0), WORLD_SIZE), vector_2f(-490.0, 0.0));
+    assert_eq!(world_wrap(vector_2f(-500.0, 0.0), WORLD_SIZE), vector_2f(-500.0, 0.0));
+    assert_eq!(world_wrap(vector_2f(500.0, 0.0), WORLD_SIZE), vector_2f(-500.0, 0.0));
+
+    // across the seam is shorter than across the world
+    let a = vector_2f(480.0, 0.0);
+    let b = vector_2f(-480.0, 280.0);
+    assert_eq!(world_delta(a, b, WORLD_SIZE), vector_2f(40.0, 280.0));
+    assert_eq!(world_delta(b, a, WORLD_SIZE), vector_2f(-40.0, -280.0));
+    assert_eq!(world_delta(a, vector_2f(0.0, 290.0), WORLD_SIZE), vector_2f(-480.0, 290.0));
+    assert_eq!(world_nearest(a, b, WORLD_SIZE), vector_2f(520.0, 280.0));
+
+    // a zero world doesn't wrap
+    assert_eq!(world_delta(a, b, vector_2f_zero()), vector_2f(-960.0, 280.0));
+}
+
+#[test]
+fn world_gravity_and_sweep_across_seam() {
+    let sun = Sun::default();
+    let planets = [world_planet(vector_2f(-470.0, 0.0))];
+
+    // just across the right edge from the planet it pulls right, toward the seam
+    let pos = vector_2f(450.0, 0.0);
+    let gravity = physics_gravity(PHYSICS_DEFAULT_G, &sun, &planets, pos, vector_2f_zero(), WORLD_SIZE, None);
+    assert!(gravity.accel.x > 0.0);
+    assert!(gravity.accel.y.abs() < 0.001);
+
+    // flying right through the seam hits the planet on the other side
+    let hit = physics_sweep_planets(&planets, pos, vector_2f(100.0, 0.0), WORLD_SIZE);
+    match hit {
+        Some((i, hit)) => {
+            assert_eq!(i, 0);
+            assert!((hit.point.x - 490.0).abs() < 0.01);
+        },
+        None => panic!("sweep missed the planet across the seam"),
+    }
+    assert!(physics_sweep_planets(&planets, pos, vector_2f(-100.0, 0.0), WORLD_SIZE).is_none());
+}
+
+#[test]
+fn world_ghost_copies() {
+    // in the middle of the world only the world itself is seen
+    assert_eq!(world_ghost_offsets(vector_2f_zero(), 200.0, WORLD_SIZE), vec![vector_2f_zero()]);
+
+    // at the right edge the copy to the right shows
+    let offsets = world_ghost_offsets(vector_2f(450.0, 0.0), 200.0, WORLD_SIZE);
+    assert_eq!(offsets.len(), 2);
+    assert!(offsets.contains(&vector_2f(1000.0, 0.0)));
+
+    // in a corner the three copies around it show
+    let offsets = world_ghost_offsets(vector_2f(-450.0, 250.0), 200.0, WORLD_SIZE);
+    assert_eq!(offsets.len(), 4);
+    assert!(offsets.contains(&vector_2f(-1000.0, 600.0)));
+}
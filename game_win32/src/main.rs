@@ -2,8 +2,19 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::collections::HashMap;
+use std::io::Write;
 use game::{
+    GAME_TITLE,
+    SIM_MIN_STEP_SEC,
+    action::*,
+    bitmap::*,
+    controller::*,
+    input::*,
+    level::*,
     math::*,
+    render::*,
+    replay::*,
+    save::*,
     state::*
 };
 
@@ -15,6 +26,7 @@ use windows_sys::{
     Win32::Media::{timeBeginPeriod, TIMERR_NOCANDO},
     Win32::System::LibraryLoader::*,
     Win32::UI::Input::KeyboardAndMouse::*,
+    Win32::UI::Input::XboxController::*,
     Win32::UI::WindowsAndMessaging::*,
 };
 
@@ -33,19 +45,17 @@ use windows_sys::{
 //     }
 // }
 
-const GAME_TITLE: &str = "MCV Game Template";
-
 const DEFAULT_SCREEN_WIDTH:  i32 = 1600;
 const DEFAULT_SCREEN_HEIGHT: i32 = 900;
 static mut GLOBAL_APP_RUNNING: bool = false;
-
-const BITMAP_BYTES_PER_PIXEL: i32 = 4; // RGBA
-#[derive(Default)]
-pub struct LoadedBitmap {
-    pub width: i32,
-    pub height: i32,
-    pub pitch: i32,
-    pub data: Option<Box<[u8]>>,
+// Focus and size messages are sent straight to the window proc instead of the message queue,
+// it leaves them here for the main loop to pick up
+static mut GLOBAL_WINDOW_EVENTS: Win32WindowEvents = Win32WindowEvents { focus_gained: false, focus_lost: false, resized: false };
+
+struct Win32WindowEvents {
+    focus_gained: bool,
+    focus_lost: bool,
+    resized: bool,
 }
 
 pub const EMPTY_TEXTURE: u32 = 0;
@@ -256,7 +266,7 @@ mod hotreload {
 
     pub unsafe fn win32_reload_game_code(watch_file: &mut GameWatchAndCopy, gc: Option<GameApi>) -> Option<GameApi> {
         // The file watching comes first
-        let modified = win32_file_modified_get(watch_file.file_watch_path).unwrap();
+        let modified = win32_get_file_modified_time(watch_file.file_watch_path).unwrap();
         let duration = modified.duration_since(watch_file.last_modified);
         let reload_code = match duration {
             Ok(d) => !d.is_zero() || gc.is_none(),
@@ -302,6 +312,179 @@ mod hotreload {
     }
 }
 
+// Records every GameInput along with a snapshot of the GameState so a flight can be
+// looped while the game code is hot reloaded.
+// L starts recording, L again loops the recording and L once more stops the loop.
+const REPLAY_FILE_PATH: &str = "input_recording.mcvr";
+
+// F5 saves the session and F9 loads it back
+const QUICKSAVE_FILE_PATH: &str = "quicksave.mcvs";
+
+#[derive(Default, PartialEq, Eq)]
+pub enum Win32ReplayMode {
+    #[default]
+    Idle,
+    Recording,
+    Playing,
+}
+
+#[derive(Default)]
+pub struct Win32ReplayState {
+    pub mode: Win32ReplayMode,
+    pub record_file: Option<std::io::BufWriter<std::fs::File>>,
+    pub playback: Option<Replay>,
+    pub playback_index: usize,
+}
+
+fn win32_begin_recording(replay: &mut Win32ReplayState, ctx: &GameState, path: &str) {
+    let file = match std::fs::File::create(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("Unable to create recording {}: {}", path, e);
+            return;
+        }
+    };
+    let mut header = Vec::new();
+    replay_write_header(&mut header, ctx);
+
+    let mut writer = std::io::BufWriter::new(file);
+    if let Err(e) = writer.write_all(&header) {
+        println!("Unable to write recording {}: {}", path, e);
+        return;
+    }
+    println!("Recording input to {} ...", path);
+    replay.record_file = Some(writer);
+    replay.mode = Win32ReplayMode::Recording;
+}
+
+fn win32_record_input(replay: &mut Win32ReplayState, input: &GameInput) {
+    if let Some(writer) = replay.record_file.as_mut() {
+        let mut record = Vec::new();
+        replay_write_input(&mut record, input);
+        if let Err(e) = writer.write_all(&record) {
+            println!("Recording stopped, write failed: {}", e);
+            win32_end_recording(replay);
+        }
+    }
+}
+
+fn win32_end_recording(replay: &mut Win32ReplayState) {
+    if let Some(mut writer) = replay.record_file.take() {
+        // nothing to do about a failed flush other than report it
+        if let Err(e) = writer.flush() {
+            println!("Unable to flush recording: {}", e);
+        }
+    }
+    replay.mode = Win32ReplayMode::Idle;
+}
+
+fn win32_begin_playback(replay: &mut Win32ReplayState, ctx: &mut GameState, path: &str) {
+    let loaded = std::fs::read(path).ok().and_then(|bytes| replay_read(&bytes));
+    let loaded = match loaded {
+        Some(r) if !r.inputs.is_empty() => r,
+        _ => {
+            println!("Unable to play back recording {}", path);
+            return;
+        }
+    };
+    match save_read(&loaded.snapshot) {
+        Ok(restored) => *ctx = restored,
+        Err(e) => {
+            println!("Recording {} has a bad snapshot: {}", path, e);
+            return;
+        }
+    }
+    println!("Looping {} input frames from {} ...", loaded.inputs.len(), path);
+    replay.playback = Some(loaded);
+    replay.playback_index = 0;
+    replay.mode = Win32ReplayMode::Playing;
+}
+
+// Swaps the live input for the next recorded one, restarting from the snapshot at the end
+fn win32_playback_input(replay: &mut Win32ReplayState, ctx: &mut GameState, input: &mut GameInput) {
+    let playback = match replay.playback.as_ref() {
+        Some(p) => p,
+        None => return,
+    };
+    if replay.playback_index >= playback.inputs.len() {
+        replay.playback_index = 0;
+        if let Ok(restored) = save_read(&playback.snapshot) {
+            *ctx = restored;
+        }
+    }
+    *input = playback.inputs[replay.playback_index];
+    replay.playback_index += 1;
+}
+
+fn win32_end_playback(replay: &mut Win32ReplayState) {
+    replay.playback = None;
+    replay.playback_index = 0;
+    replay.mode = Win32ReplayMode::Idle;
+}
+
+// Translates a virtual key code into the game's platform independent key, None for keys the
+// action map has no name for
+fn win32_key(vk: usize) -> Option<Key> {
+    let vk = vk as u16;
+    match vk {
+        0x30..=0x39 | 0x41..=0x5A => Some(Key::Char(vk as u8 as char)),
+        VK_SPACE => Some(Key::Space),
+        VK_RETURN => Some(Key::Enter),
+        VK_ESCAPE => Some(Key::Escape),
+        VK_TAB => Some(Key::Tab),
+        VK_BACK => Some(Key::Backspace),
+        VK_LEFT => Some(Key::Left),
+        VK_RIGHT => Some(Key::Right),
+        VK_UP => Some(Key::Up),
+        VK_DOWN => Some(Key::Down),
+        VK_SHIFT => Some(Key::Shift),
+        VK_CONTROL => Some(Key::Control),
+        VK_MENU => Some(Key::Alt),
+        VK_F1..=VK_F12 => Some(Key::F((vk - VK_F1 + 1) as u8)),
+        _ => None,
+    }
+}
+
+// Reads the first connected XInput controller as is, the deadzones are the game's business
+fn win32_read_controller() -> ControllerReading {
+    let mut reading = ControllerReading::default();
+    for user_index in 0..XUSER_MAX_COUNT {
+        let mut state: XINPUT_STATE = unsafe { std::mem::zeroed() };
+        if unsafe { XInputGetState(user_index, &mut state) } != ERROR_SUCCESS {
+            continue;
+        }
+        let pad = state.Gamepad;
+        let stick = |v: i16| (v as f32 / 32767.0).max(-1.0);
+        reading.connected = true;
+        reading.left_stick = vector_2f(stick(pad.sThumbLX), stick(pad.sThumbLY));
+        reading.right_stick = vector_2f(stick(pad.sThumbRX), stick(pad.sThumbRY));
+        reading.left_trigger = pad.bLeftTrigger as f32 / 255.0;
+        reading.right_trigger = pad.bRightTrigger as f32 / 255.0;
+
+        let flags = [
+            (GamepadButton::A, XINPUT_GAMEPAD_A),
+            (GamepadButton::B, XINPUT_GAMEPAD_B),
+            (GamepadButton::X, XINPUT_GAMEPAD_X),
+            (GamepadButton::Y, XINPUT_GAMEPAD_Y),
+            (GamepadButton::Start, XINPUT_GAMEPAD_START),
+            (GamepadButton::Back, XINPUT_GAMEPAD_BACK),
+            (GamepadButton::LeftShoulder, XINPUT_GAMEPAD_LEFT_SHOULDER),
+            (GamepadButton::RightShoulder, XINPUT_GAMEPAD_RIGHT_SHOULDER),
+            (GamepadButton::LeftThumb, XINPUT_GAMEPAD_LEFT_THUMB),
+            (GamepadButton::RightThumb, XINPUT_GAMEPAD_RIGHT_THUMB),
+            (GamepadButton::DPadUp, XINPUT_GAMEPAD_DPAD_UP),
+            (GamepadButton::DPadDown, XINPUT_GAMEPAD_DPAD_DOWN),
+            (GamepadButton::DPadLeft, XINPUT_GAMEPAD_DPAD_LEFT),
+            (GamepadButton::DPadRight, XINPUT_GAMEPAD_DPAD_RIGHT),
+        ];
+        for (button, flag) in flags {
+            reading.buttons[button as usize] = pad.wButtons & flag != 0;
+        }
+        break;
+    }
+    reading
+}
+
 unsafe extern "system" fn window_proc_callback(
     window_handle: HWND,
     message: u32,
@@ -313,6 +496,15 @@ unsafe extern "system" fn window_proc_callback(
         WM_CLOSE | WM_QUIT => {
             GLOBAL_APP_RUNNING = false;
         },
+        WM_SETFOCUS => {
+            GLOBAL_WINDOW_EVENTS.focus_gained = true;
+        },
+        WM_KILLFOCUS => {
+            GLOBAL_WINDOW_EVENTS.focus_lost = true;
+        },
+        WM_SIZE => {
+            GLOBAL_WINDOW_EVENTS.resized = true;
+        },
         _ => {
             result = DefWindowProcA(window_handle, message, wparam, lparam);
         }
@@ -433,7 +625,61 @@ fn main() {
 
     
     let mut ctx = GameState::default();
+
+    // --seed <n> picks which universe gets generated
+    // --generate builds the solar system from the seed instead of the built in one
+    // --sim-hz <n> sets the fixed simulation rate, independent of the render rate
+    // --replay <file> loops a recorded input file, e.g. one attached to a bug report
+    // --load <file> starts from a save file
+    // --level <file> starts from a level file instead of the built in solar system
+    // --bindings <file> loads key, mouse and gamepad bindings instead of the default ones
+    let mut action_map = action_map_default();
+    let mut replay_path: Option<String> = None;
+    let mut args = std::env::args();
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            ctx.seed = args.next().and_then(|s| s.parse().ok()).expect("--seed expects an unsigned integer");
+        }
+        else if arg == "--generate" {
+            ctx.generate_planets = true;
+        }
+        else if arg == "--sim-hz" {
+            let sim_hz: f32 = args.next().and_then(|s| s.parse().ok())
+                .filter(|hz: &f32| hz.is_finite() && *hz > 0.0)
+                .expect("--sim-hz expects a number greater than 0");
+            ctx.sim_step_sec = sim_hz.min(SIM_MIN_STEP_SEC.recip()).recip();
+        }
+        else if arg == "--replay" {
+            replay_path = Some(args.next().expect("--replay expects a file path"));
+        }
+        else if arg == "--level" {
+            let path = args.next().expect("--level expects a file path");
+            match level_load_file(std::path::Path::new(&path)) {
+                Ok(level) => level_apply(&level, &mut ctx),
+                Err(e) => std::panic!("Unable to load {}: {}", path, e),
+            }
+        }
+        else if arg == "--bindings" {
+            let path = args.next().expect("--bindings expects a file path");
+            action_map = match action_map_load_file(std::path::Path::new(&path)) {
+                Ok(map) => map,
+                Err(e) => std::panic!("Unable to load {}: {}", path, e),
+            };
+        }
+        else if arg == "--load" {
+            let path = args.next().expect("--load expects a file path");
+            ctx = match save_read_file(std::path::Path::new(&path)) {
+                Ok(loaded) => loaded,
+                Err(e) => std::panic!("Unable to load {}: {}", path, e),
+            };
+        }
+    }
+    let mut replay = Win32ReplayState::default();
+    if let Some(path) = replay_path {
+        win32_begin_playback(&mut replay, &mut ctx, &path);
+    }
     let mut input = GameInput::default();
+    let mut render_buffer = RenderBuffer::default();
     let mut assets = GameAssets::default();
 
     let debug_bitmap = {
@@ -468,8 +714,8 @@ fn main() {
     unsafe {
         let arial_font = win32_create_font("Arial\0", FontOptions::default());
         
-        for c in GAME_TITLE.chars() {
-            if c == '\0' { break; }
+        // every printable ascii character so the game can draw any message
+        for c in (b' '..=b'~').map(|b| b as char) {
             let char_bitmap = win32_create_font_char_bitmap(arial_font, c as u16);
             let texture = win32_opengl_texture_create(
                 char_bitmap.width, 
@@ -480,18 +726,26 @@ fn main() {
 
         win32_destroy_font(arial_font);
     };
+
+    let mut renderer = Win32OpenGlRenderer { assets };
     
     // Target frame rate stuff
-    let target_frames_per_sec: f32 = 30.0;
+    // NOTE this is only the render rate, the game steps its simulation at its own fixed rate
+    let target_frames_per_sec: f32 = 60.0;
     let target_frame_rate_ms: f32 = 1000.0/target_frames_per_sec;
     
     // Initialize window stuff
+    let mut frame_timer = std::time::Instant::now();
     unsafe { GLOBAL_APP_RUNNING = true; }
     while unsafe { GLOBAL_APP_RUNNING } {
         let work_timer = std::time::Instant::now();
 
-        // should we always force the game step is the target vs actual ellapsed
-        input.frame_dt_sec = target_frames_per_sec.recip();
+        // pass the actual ellapsed frame time and let the game decide how many sim steps to run
+        input.frame_dt_sec = frame_timer.elapsed().as_secs_f32();
+        frame_timer = std::time::Instant::now();
+
+        // buttons stay down across frames, only this frame's transitions are counted
+        input_begin_frame(&mut input);
         
         // Peek window messages
         unsafe {
@@ -508,24 +762,77 @@ fn main() {
                         let KeyWasDownBitFlag = 1 << 30;
                         
                         let is_down   = (KeyIsDownBitFlag  & msg.lParam) == 0;
-                        let _was_down  = (KeyWasDownBitFlag & msg.lParam) != 0;
+                        let was_down  = (KeyWasDownBitFlag & msg.lParam) != 0;
                         
-                        if VK_SPACE as usize == msg.wParam {
-                            input.launch_down = is_down;
-                        }
-                        else if 'A' as usize == msg.wParam {
-                            input.turn_left = is_down;
+                        if 'L' as usize == msg.wParam {
+                            if is_down && !was_down {
+                                match replay.mode {
+                                    Win32ReplayMode::Idle => {
+                                        win32_begin_recording(&mut replay, &ctx, REPLAY_FILE_PATH);
+                                    },
+                                    Win32ReplayMode::Recording => {
+                                        win32_end_recording(&mut replay);
+                                        win32_begin_playback(&mut replay, &mut ctx, REPLAY_FILE_PATH);
+                                    },
+                                    Win32ReplayMode::Playing => {
+                                        win32_end_playback(&mut replay);
+                                    },
+                                }
+                            }
                         }
-                        else if 'D' as usize == msg.wParam {
-                            input.turn_right = is_down;
+                        else if VK_F5 as usize == msg.wParam {
+                            if is_down && !was_down {
+                                match save_write_file(&ctx, std::path::Path::new(QUICKSAVE_FILE_PATH)) {
+                                    Ok(()) => println!("Saved {}", QUICKSAVE_FILE_PATH),
+                                    Err(e) => println!("Unable to save {}: {}", QUICKSAVE_FILE_PATH, e),
+                                }
+                            }
                         }
-                        else if 'W' as usize == msg.wParam {
-                            input.accelerate = is_down;
+                        else if VK_F9 as usize == msg.wParam {
+                            if is_down && !was_down {
+                                match save_read_file(std::path::Path::new(QUICKSAVE_FILE_PATH)) {
+                                    Ok(loaded) => {
+                                        ctx = loaded;
+                                        println!("Loaded {}", QUICKSAVE_FILE_PATH);
+                                    },
+                                    Err(e) => println!("Unable to load {}: {}", QUICKSAVE_FILE_PATH, e),
+                                }
+                            }
                         }
-                        else if 'S' as usize == msg.wParam {
-                            input.decelerate = is_down;
+                        // the debug keys above are handled here, everything else goes through the bindings
+                        else if let Some(key) = win32_key(msg.wParam) {
+                            let value = if is_down { 1.0 } else { 0.0 };
+                            action_map_process(&mut action_map, &mut input, InputSource::Key(key), value);
                         }
                     },
+                    WM_MOUSEMOVE => {
+                        // client area coordinates, signed since they go negative on multiple monitors
+                        let x = (msg.lParam & 0xFFFF) as i16;
+                        let y = ((msg.lParam >> 16) & 0xFFFF) as i16;
+                        input.mouse_pos = vector_2f(x as f32, y as f32);
+                    },
+                    WM_MOUSEWHEEL => {
+                        let delta = ((msg.wParam >> 16) & 0xFFFF) as i16;
+                        input.mouse_wheel += delta as f32 / WHEEL_DELTA as f32;
+                    },
+                    WM_LBUTTONDOWN | WM_LBUTTONUP => {
+                        let is_down = msg.message == WM_LBUTTONDOWN;
+                        button_process(&mut input.mouse_left, is_down);
+                        let value = if is_down { 1.0 } else { 0.0 };
+                        action_map_process(&mut action_map, &mut input, InputSource::Mouse(MouseButton::Left), value);
+                    },
+                    WM_RBUTTONDOWN | WM_RBUTTONUP => {
+                        let is_down = msg.message == WM_RBUTTONDOWN;
+                        button_process(&mut input.mouse_right, is_down);
+                        let value = if is_down { 1.0 } else { 0.0 };
+                        action_map_process(&mut action_map, &mut input, InputSource::Mouse(MouseButton::Right), value);
+                    },
+                    WM_MBUTTONDOWN | WM_MBUTTONUP => {
+                        let is_down = msg.message == WM_MBUTTONDOWN;
+                        button_process(&mut input.mouse_middle, is_down);
+                        let value = if is_down { 1.0 } else { 0.0 };
+                        action_map_process(&mut action_map, &mut input, InputSource::Mouse(MouseButton::Middle), value);
+                    },
                     _ => {},
                 }
                 TranslateMessage(&msg);
@@ -533,30 +840,62 @@ fn main() {
             }
         }
 
+        unsafe {
+            let events = &mut *std::ptr::addr_of_mut!(GLOBAL_WINDOW_EVENTS);
+            input.focus_gained = events.focus_gained;
+            input.focus_lost = events.focus_lost;
+            input.resized = events.resized;
+            *events = Win32WindowEvents { focus_gained: false, focus_lost: false, resized: false };
+        }
+        if input.focus_gained {
+            input.has_focus = true;
+        }
+        if input.focus_lost {
+            // the key and button releases go to whichever window has focus now
+            input.has_focus = false;
+            action_map_release_all(&mut action_map, &mut input);
+            button_process(&mut input.mouse_left, false);
+            button_process(&mut input.mouse_right, false);
+            button_process(&mut input.mouse_middle, false);
+        }
+
+        controller_update(&mut input.controller, &win32_read_controller());
+        controller_process_actions(&mut action_map, &mut input);
+
+        // the client area, so it matches the mouse coordinates and what's actually drawn
         let (screen_width, screen_height) = unsafe {
-            let mut window_rect: RECT = std::mem::zeroed();
-            GetWindowRect(window_handle, &mut window_rect);
-            (window_rect.right - window_rect.left,
-             window_rect.bottom - window_rect.top)
+            let mut client_rect: RECT = std::mem::zeroed();
+            GetClientRect(window_handle, &mut client_rect);
+            (client_rect.right - client_rect.left,
+             client_rect.bottom - client_rect.top)
         };
         input.screen_width = screen_width;
         input.screen_height = screen_height;
+        input_update_mouse_world_pos(&mut input, &ctx);
+
+        // the live input is kept as is so it's still current when playback stops
+        let mut game_input = input;
+        match replay.mode {
+            Win32ReplayMode::Idle => {},
+            Win32ReplayMode::Recording => win32_record_input(&mut replay, &game_input),
+            Win32ReplayMode::Playing => win32_playback_input(&mut replay, &mut ctx, &mut game_input),
+        }
 
         #[cfg(feature = "hotreload")]
         {
             game_api = unsafe { win32_reload_game_code(&mut watch_file, game_api) };
             if let Some(api) = game_api {
-                (api.update_and_render)(&input, &mut ctx);
+                (api.update_and_render)(&game_input, &mut ctx, &mut render_buffer);
             }
         }
         #[cfg(not(feature = "hotreload"))]
-        game::update_and_render(&input, &mut ctx);
+        game::update_and_render(&game_input, &mut ctx, &mut render_buffer);
 
         // Render main game
-        win32_opengl_render(0, 0, screen_width, screen_height, &assets, &ctx);
+        renderer.render(0, 0, screen_width, screen_height, &render_buffer);
 
         // Render mini map
-        // win32_opengl_render(20, 20, 200, 200, &assets, &ctx);
+        // renderer.render(20, 20, 200, 200, &render_buffer);
 
         // Enforce the frame rate
         let mut time_ellapsed_ms = work_timer.elapsed().as_secs_f32() * 1000.0;
@@ -576,351 +915,136 @@ fn main() {
     }
 }
 
-fn win32_opengl_render(x: i32, y: i32, width: i32, height: i32, assets: &GameAssets, ctx: &GameState) { 
+pub struct Win32OpenGlRenderer {
+    pub assets: GameAssets,
+}
+
+impl IRenderer for Win32OpenGlRenderer {
+    fn render(&mut self, x: i32, y: i32, width: i32, height: i32, buffer: &RenderBuffer) {
+        win32_opengl_render(x, y, width, height, &self.assets, buffer);
+    }
+}
+
+unsafe fn win32_opengl_set_layer(width: i32, height: i32, camera: &RenderCamera, layer: RenderLayer) {
+    // Projection
+    let projection = render_layer_projection(width, height, camera, layer);
+    glMatrixMode(GL_PROJECTION);
+    glLoadMatrixf(matrix_4f_as_ptr(&projection));
+
+    // Start Model View spaces
+    glMatrixMode(GL_MODELVIEW);
+    glLoadIdentity(); // Reset the ModelView matrix to identity matrix
+}
+
+unsafe fn win32_opengl_push_matrix(m: &Matrix3f) {
+    glPushMatrix();
+    glMultMatrixf(matrix_4f_as_ptr(&matrix_4f_from_3f(m)));
+}
+
+fn win32_opengl_render(x: i32, y: i32, width: i32, height: i32, assets: &GameAssets, buffer: &RenderBuffer) { 
     unsafe {
         glViewport(x, y, width, height);
-        glClearColor(0.0, 0.05, 0.11, 1.0);
-        glClear(GL_COLOR_BUFFER_BIT);
-
-        // Projection
-        glMatrixMode(GL_PROJECTION);
-        glLoadIdentity();
-        let screen_half_width = width / 2;
-        let screen_half_height = height / 2;
-        glOrtho(-screen_half_width as f64,  screen_half_width as f64, 
-                -screen_half_height as f64, screen_half_height as f64, 
-                0.0, 100.0);
-
-        // will this work
-        let player_pos = ctx.player.pos;
-        glMultMatrixf([
-            1.0, 0.0, 0.0, 0.0,
-            0.0, 1.0, 0.0, 0.0,
-            0.0, 0.0, 1.0, 0.0,
-            -player_pos.x, -player_pos.y, 0.0, 1.0,
-        ].as_ptr());
-
-        // Start Model View spaces
-        glMatrixMode(GL_MODELVIEW);
-        glLoadIdentity(); // Reset the ModelView matrix to identity matrix
-        
-        // Render space background
-        glPushMatrix();
-        glColor3f(1.0, 1.0, 1.0);
-        glBegin(GL_POINTS);
-        let stars = ctx.space_stars.as_ref().unwrap();
-        for s in stars  {
-            glVertex2f(s.pos.x, s.pos.y);
-        }
-        glEnd();
-        glPopMatrix();
-
-        // Render the world map
-        // TODO not ready for world tile map yet until we want to scale the size of game
-        // let tile_pixel_dim :i32 = 64;
-        // let tile_map_cols = (width / tile_pixel_dim) + 1;
-        // let tile_map_rows = (height / tile_pixel_dim) + 1;
-        // glColor3f(0.5, 0.5, 0.5);
-        // glPolygonMode(GL_FRONT_AND_BACK, GL_LINE);
-        // for row in 0..tile_map_rows {
-        //     for col in 0..tile_map_cols {
-        //         let tx = col * tile_pixel_dim;
-        //         let ty = row * tile_pixel_dim;
-        //         let tx = tx + tile_pixel_dim / 2;
-        //         let ty = ty + tile_pixel_dim / 2;
-        //         let dim = tile_pixel_dim;
-        //         let mv = [
-        //             dim as f32, 0.0, 0.0, 0.0,
-        //             0.0, dim as f32, 0.0, 0.0,
-        //             0.0, 0.0, 1.0, 0.0,
-        //             tx as f32,  ty as f32, 0.0, 1.0,
-        //         ];
-        //         glPushMatrix();
-        //         glMultMatrixf(mv.as_ptr());
-        //         glBegin(GL_QUADS);
-        //         glVertex2f(-1.0,  1.0);
-        //         glVertex2f(-1.0, -1.0);
-        //         glVertex2f( 1.0, -1.0);
-        //         glVertex2f( 1.0,  1.0);
-        //         glEnd();
-        //         glPopMatrix();
-        //     }
-        // }
-        // glPolygonMode(GL_FRONT_AND_BACK, GL_FILL);
-
-        // Render planets
+        win32_opengl_set_layer(width, height, &buffer.camera, RenderLayer::World);
+
         let circle_points = 360;
         let circle_point_angle_step_radians: f32 = (360.0/circle_points as f32).to_radians();
-        let planets = ctx.planets.as_ref().unwrap();
-        for planet in planets {
-            glColor3f(planet.color.x, planet.color.y, planet.color.z);
-            let mv = [
-                planet.radius, 0.0, 0.0, 0.0,
-                0.0, planet.radius, 0.0, 0.0,
-                0.0, 0.0, 1.0, 0.0,
-                planet.pos.x, planet.pos.y, 0.0, 1.0,
-            ];
-            glPushMatrix();
-            glMultMatrixf(mv.as_ptr());
-            glBegin(GL_TRIANGLE_FAN);
-            for p in 0..circle_points {
-                let px = (p as f32 * circle_point_angle_step_radians).cos();
-                let py = (p as f32 * circle_point_angle_step_radians).sin();
-                glVertex2f(px, py);
-            }
-            glEnd();
-            glPopMatrix();
-
-            glColor3f(planet.color.x, planet.color.y, planet.color.z);
-            let mv = [
-                planet.g_radius, 0.0, 0.0, 0.0,
-                0.0, planet.g_radius, 0.0, 0.0,
-                0.0, 0.0, 1.0, 0.0,
-                planet.pos.x, planet.pos.y, 0.0, 1.0,
-            ];
-            glPushMatrix();
-            glMultMatrixf(mv.as_ptr());
-            glBegin(GL_LINE_STRIP);
-            for p in 0..circle_points {
-                let px = (p as f32 * circle_point_angle_step_radians).cos();
-                let py = (p as f32 * circle_point_angle_step_radians).sin();
-                glVertex2f(px, py);
-            }
-            glEnd();
-            glPopMatrix();
-            
-            // Render planet stuff
-            let fuel_pos = vector_2f_add(planet.pos, planet.item.pos);
-            let fuel_color = vector_4f(0.1, 0.2, 1.0, 1.0);
-            let fuel_dim = vector_2f(10.0, 10.0);
-            glColor3f(fuel_color.x, fuel_color.y, fuel_color.z);
-            let mv = [
-                fuel_dim.x, 0.0, 0.0, 0.0,
-                0.0, fuel_dim.y, 0.0, 0.0,
-                0.0, 0.0, 1.0, 0.0,
-                fuel_pos.x, fuel_pos.y, 0.0, 1.0,
-            ];
-            glPushMatrix();
-            glMultMatrixf(mv.as_ptr());
-            glBegin(GL_QUADS);
-            glVertex2f(-1.0,  1.0);
-            glVertex2f(-1.0, -1.0);
-            glVertex2f( 1.0, -1.0);
-            glVertex2f( 1.0,  1.0);
-            glEnd();
-            glPopMatrix();
-
-            // Render Landing Zone
-            let lz_pos = vector_2f_add(planet.pos, planet.lz_rel_pos);
-            let lz_dim = vector_2f(10.0, 10.0);
-            let lz_color = planet.lz_color;
-            glColor3f(lz_color.x, lz_color.y, lz_color.z);
-            let mv = [
-                lz_dim.x, 0.0, 0.0, 0.0,
-                0.0, lz_dim.y, 0.0, 0.0,
-                0.0, 0.0, 1.0, 0.0,
-                lz_pos.x, lz_pos.y, 0.0, 1.0,
-            ];
-            glPushMatrix();
-            glMultMatrixf(mv.as_ptr());
-            glBegin(GL_QUADS);
-            glVertex2f(-1.0,  1.0);
-            glVertex2f(-1.0, -1.0);
-            glVertex2f( 1.0, -1.0);
-            glVertex2f( 1.0,  1.0);
-            glEnd();
-            glPopMatrix();
-        }
-
-        // Render Player
-        glColor3f(1.0, 1.0, 1.0);
-        let s = vector_2f(10.0, 10.0);  // scale
-        let t = &ctx.player.pos;        // translate
-        let r = *&ctx.player.rot;       // rotation
-        let mv = [
-            s.x, 0.0, 0.0, 0.0,
-            0.0, s.y, 0.0, 0.0,
-            0.0, 0.0, 1.0, 0.0,
-            t.x, t.y, 0.0, 1.0,
-        ];
-        glPushMatrix();
-        glMultMatrixf(mv.as_ptr());
-        glMultMatrixf([
-            r.cos(),  r.sin(), 0.0, 0.0,
-           -r.sin(),  r.cos(), 0.0, 0.0,
-            0.0,      0.0,     1.0, 0.0,
-            0.0,      0.0,     0.0, 1.0,
-        ].as_ptr());
-        glBegin(GL_TRIANGLES);
-        glVertex2f( 1.0,  0.0);
-        glVertex2f(-1.0,  1.0);
-        glVertex2f(-0.5,  0.0);   
-
-        glVertex2f( 1.0,  0.0);
-        glVertex2f(-0.5,  0.0);
-        glVertex2f(-1.0, -1.0);
-        glEnd();
-        glPopMatrix();
-
-        // Render Navigation Path
-        if let Some(path) = &ctx.nav_path {
-            glEnable(GL_LINE_SMOOTH);
-            glLineWidth(2.0);
-            glBegin(GL_LINES);
-            for p in path.points.iter() {
-                glVertex2f(p.p.x, p.p.y);
-                glColor3f(p.c.x, p.c.y, p.c.z);
+
+        for command in buffer.commands.iter() {
+            match command {
+                RenderCommand::Clear { color } => {
+                    glClearColor(color.x, color.y, color.z, color.w);
+                    glClear(GL_COLOR_BUFFER_BIT);
+                },
+                RenderCommand::Quad { pos, half_dim, color } => {
+                    glColor4f(color.x, color.y, color.z, color.w);
+                    win32_opengl_push_matrix(&render_quad_transform(*pos, *half_dim));
+                    glBegin(GL_QUADS);
+                    glVertex2f(-1.0,  1.0);
+                    glVertex2f(-1.0, -1.0);
+                    glVertex2f( 1.0, -1.0);
+                    glVertex2f( 1.0,  1.0);
+                    glEnd();
+                    glPopMatrix();
+                },
+                RenderCommand::Circle { pos, radius, color, filled } => {
+                    glColor4f(color.x, color.y, color.z, color.w);
+                    win32_opengl_push_matrix(&render_quad_transform(*pos, vector_2f(*radius, *radius)));
+                    glBegin(if *filled { GL_TRIANGLE_FAN } else { GL_LINE_STRIP });
+                    for p in 0..circle_points {
+                        let px = (p as f32 * circle_point_angle_step_radians).cos();
+                        let py = (p as f32 * circle_point_angle_step_radians).sin();
+                        glVertex2f(px, py);
+                    }
+                    glEnd();
+                    glPopMatrix();
+                },
+                RenderCommand::LineStrip { points, width } => {
+                    glEnable(GL_LINE_SMOOTH);
+                    glLineWidth(*width);
+                    glBegin(GL_LINE_STRIP);
+                    for v in points.iter() {
+                        glColor4f(v.c.x, v.c.y, v.c.z, v.c.w);
+                        glVertex2f(v.p.x, v.p.y);
+                    }
+                    glEnd();
+                    glLineWidth(1.0);
+                    glDisable(GL_LINE_SMOOTH);
+                },
+                RenderCommand::PointCloud { points, color, size } => {
+                    glPointSize(*size);
+                    glColor4f(color.x, color.y, color.z, color.w);
+                    glBegin(GL_POINTS);
+                    for p in points.iter() {
+                        glVertex2f(p.x, p.y);
+                    }
+                    glEnd();
+                    glPointSize(1.0);
+                },
+                RenderCommand::Triangles { vertices, color } => {
+                    glColor4f(color.x, color.y, color.z, color.w);
+                    glBegin(GL_TRIANGLES);
+                    for v in vertices.iter() {
+                        glVertex2f(v.x, v.y);
+                    }
+                    glEnd();
+                },
+                RenderCommand::TexturedQuad { pos, half_dim, texture, color } => {
+                    let texture_id = match texture {
+                        RenderTexture::Debug => assets.debug_texture.id,
+                        RenderTexture::Glyph(c) => assets_get_char_texture(assets, *c).copied().unwrap_or_default().id,
+                    };
+                    glEnable(GL_BLEND);
+                    glBlendFunc(GL_SRC_ALPHA, GL_ONE_MINUS_SRC_ALPHA); 
+                    glColor4f(color.x, color.y, color.z, color.w);
+                    win32_opengl_push_matrix(&render_quad_transform(*pos, *half_dim));
+                    glEnable(GL_TEXTURE_2D);
+                    glBindTexture(GL_TEXTURE_2D, texture_id);
+                    glBegin(GL_QUADS);
+                    glTexCoord2f(0.0, 1.0);
+                    glVertex2f(-1.0,  1.0);
+                    glTexCoord2f(0.0, 0.0);
+                    glVertex2f(-1.0, -1.0);
+                    glTexCoord2f(1.0, 0.0);
+                    glVertex2f( 1.0, -1.0);
+                    glTexCoord2f(1.0, 1.0);
+                    glVertex2f( 1.0,  1.0);
+                    glEnd();
+                    glBindTexture(GL_TEXTURE_2D, 0);
+                    glDisable(GL_TEXTURE_2D);
+                    glPopMatrix();
+                    glDisable(GL_BLEND);
+                },
+                RenderCommand::PushTransform { pos, rot, scale } => {
+                    win32_opengl_push_matrix(&render_push_transform_matrix(*pos, *rot, *scale));
+                },
+                RenderCommand::PopTransform => {
+                    glPopMatrix();
+                },
+                RenderCommand::SetLayer(layer) => {
+                    win32_opengl_set_layer(width, height, &buffer.camera, *layer);
+                },
             }
-            glEnd();
-            glLineWidth(1.0);
-            glDisable(GL_LINE_SMOOTH);
         }
-        
-        // Render Forces
-        if let Some(forces) = &ctx.debug_player_forces {
-            let player_pos = ctx.player.pos;
-            glColor3f(1.0, 1.0, 0.0);
-            // glLoadIdentity();
-            glEnable(GL_LINE_SMOOTH);
-            glLineWidth(2.0);
-            glBegin(GL_LINES);
-            for f in forces {
-                let force = vector_2f_add(player_pos, vector_2f_scale(*f, 100.0));
-                glVertex2f(player_pos.x, player_pos.y);
-                glVertex2f(force.x, force.y);
-            }
-            glEnd();
-            glLineWidth(1.0);
-            glDisable(GL_LINE_SMOOTH);
-        }
-
-        // Render Text as quads
-        glLoadIdentity();
-        glEnable(GL_BLEND);
-        glBlendFunc(GL_SRC_ALPHA, GL_ONE_MINUS_SRC_ALPHA); 
-        // todo use actual font metrics to properly size and position sentences
-        let char_scale = vector_2f(40.0, 40.0);
-        let text_line_width = GAME_TITLE.len() as f32 * char_scale.x;
-        let mut text_char_pos = vector_2f(-text_line_width, 0.0);
-        for c in GAME_TITLE.chars() {
-            let texture = assets.char_textures.get(&c).expect("Missing char texture");
-            let s = char_scale;
-            let t = text_char_pos;
-            text_char_pos.x += char_scale.x * 2.0;
-            let mv = [
-                s.x, 0.0, 0.0, 0.0,
-                0.0, s.y, 0.0, 0.0,
-                0.0, 0.0, 1.0, 0.0,
-                t.x, t.y, 0.0, 1.0,
-            ];
-            glColor4f(1.0, 1.0, 0.0, 1.0 - ctx.title_fade);
-            glLoadMatrixf(mv.as_ptr());
-            glEnable(GL_TEXTURE_2D);
-            glBindTexture(GL_TEXTURE_2D, texture.id);
-            glBegin(GL_QUADS);
-            glTexCoord2f(0.0, 1.0);
-            glVertex2f(-1.0,  1.0);
-            glTexCoord2f(0.0, 0.0);
-            glVertex2f(-1.0, -1.0);
-            glTexCoord2f(1.0, 0.0);
-            glVertex2f( 1.0, -1.0);
-            glTexCoord2f(1.0, 1.0);
-            glVertex2f( 1.0,  1.0);
-            glEnd();
-            glBindTexture(GL_TEXTURE_2D, 0);
-            glDisable(GL_TEXTURE_2D);
-        }
-        glDisable(GL_BLEND);
-
-        // Render Text character as a quad
-        // glLoadIdentity();
-        // let texture = assets.char_b_texture.id;
-        // let s = vector_2f(50.0, 50.0);
-        // let t = vector_2f(200.0, 200.0);
-        // let mv = [
-        //     s.x, 0.0, 0.0, 0.0,
-        //     0.0, s.y, 0.0, 0.0,
-        //     0.0, 0.0, 1.0, 0.0,
-        //     t.x, t.y, 0.0, 1.0,
-        // ];
-        // glLoadMatrixf(mv.as_ptr());
-        // glEnable(GL_TEXTURE_2D);
-        // glBindTexture(GL_TEXTURE_2D, texture);
-        // glBegin(GL_QUADS);
-        // glTexCoord2f(0.0, 1.0);
-        // glVertex2f(-1.0,  1.0);
-        // glTexCoord2f(0.0, 0.0);
-        // glVertex2f(-1.0, -1.0);
-        // glTexCoord2f(1.0, 0.0);
-        // glVertex2f( 1.0, -1.0);
-        // glTexCoord2f(1.0, 1.0);
-        // glVertex2f( 1.0,  1.0);
-        // glEnd();
-        // glBindTexture(GL_TEXTURE_2D, 0);
-        // glDisable(GL_TEXTURE_2D);
-
-        // Render test bitmap to quad
-        // glLoadIdentity();
-        // let s = vector_2f(100.0, 100.0);
-        // let t = vector_2f(-400.0, -200.0);
-        // let mv = [
-        //     s.x, 0.0, 0.0, 0.0,
-        //     0.0, s.y, 0.0, 0.0,
-        //     0.0, 0.0, 1.0, 0.0,
-        //     t.x, t.y, 0.0, 1.0,
-        // ];
-        // glLoadMatrixf(mv.as_ptr());
-        // glEnable(GL_TEXTURE_2D);
-        // glBindTexture(GL_TEXTURE_2D, assets.debug_texture.id);
-        // glBegin(GL_QUADS);
-        // glTexCoord2f(0.0, 1.0);
-        // glVertex2f(-1.0,  1.0);
-        // glTexCoord2f(0.0, 0.0);
-        // glVertex2f(-1.0, -1.0);
-        // glTexCoord2f(1.0, 0.0);
-        // glVertex2f( 1.0, -1.0);
-        // glTexCoord2f(1.0, 1.0);
-        // glVertex2f( 1.0,  1.0);
-        // glEnd();
-        // glBindTexture(GL_TEXTURE_2D, 0);
-        // glDisable(GL_TEXTURE_2D);
-
-        // Center the world on the player
-
-        //
-        // Render HUD
-        //
-
-        // HUD Projection
-        glMatrixMode(GL_PROJECTION);
-        glLoadIdentity();
-        let screen_half_width = width / 2;
-        let screen_half_height = height / 2;
-        glOrtho(-screen_half_width as f64,  screen_half_width as f64, 
-                -screen_half_height as f64, screen_half_height as f64, 
-                0.0, 100.0);
-        // Start Model View spaces
-        glMatrixMode(GL_MODELVIEW);
-        glLoadIdentity(); // Reset the ModelView matrix to identity matrix
-
-        // Render fuel bar
-        let rect_padding = 10.0;
-        let rect_width = screen_half_width as f32 - (2.0 * rect_padding);
-        let rect_height = 10.0;
-        let rect_center = (
-            0.0, -(screen_half_height as f32 - (2.0 * rect_padding) - rect_height/2.0)
-        );
-        let rect_width = rect_width * ctx.ship.fuel_level;
-        glColor3f(0.0, 0.0, 1.0);
-        glLoadIdentity();
-        glTranslatef(rect_center.0, rect_center.1, 0.0);
-        glScalef(rect_width, rect_height, 1.0);
-        glBegin(GL_QUADS);
-        glVertex2f(-1.0,  1.0);
-        glVertex2f(-1.0, -1.0);
-        glVertex2f( 1.0, -1.0);
-        glVertex2f( 1.0,  1.0);
-        glEnd();
     }
 }
 